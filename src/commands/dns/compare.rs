@@ -0,0 +1,449 @@
+use hickory_proto::op::ResponseCode;
+use hickory_proto::rr::{Name, RecordType};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use super::query::{query, DnsResponse};
+use super::{mark_disagreement, ServerResult, ServerStatus, DNS_PORT};
+
+/// Resolver-Listen, die neben den Interfaces ausgewertet werden
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const RESOLVED_UPSTREAM: &str = "/run/systemd/resolve/resolv.conf";
+const RESOLVED_CONF: &str = "/etc/systemd/resolved.conf";
+const RESOLVED_CONF_DIR: &str = "/etc/systemd/resolved.conf.d";
+
+/// Ein Resolver mit allen Stellen, an denen er konfiguriert ist
+pub struct Resolver {
+    pub address: SocketAddr,
+    pub sources: Vec<String>,
+}
+
+/// DNSSEC- und EDNS-Verhalten eines Resolvers
+#[derive(Serialize)]
+struct ResolverInfo {
+    server: SocketAddr,
+    sources: Vec<String>,
+    /// AD-Bit in der Antwort; fehlt ohne Antwort
+    dnssec_validated: Option<bool>,
+    /// Antwort enthielt einen OPT-Record; fehlt ohne Antwort
+    edns: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edns_payload: Option<u16>,
+}
+
+/// Vergleich für `--format json`: Ergebnisse je Record-Typ und Resolver
+#[derive(Serialize)]
+struct CompareReport {
+    name: String,
+    started_at: String,
+    /// Alle Resolver haben geantwortet und stimmen überein
+    consistent: bool,
+    resolvers: Vec<ResolverInfo>,
+    results: BTreeMap<String, Vec<ServerResult>>,
+}
+
+/// Sammelt die Resolver aller Interfaces, aus `/etc/resolv.conf` und der
+/// systemd-resolved-Konfiguration; doppelte Adressen werden zusammengefasst
+pub fn collect_resolvers(extra: &[SocketAddr]) -> Vec<Resolver> {
+    let mut resolvers = Vec::new();
+    for interface in netdev::get_interfaces() {
+        for ip in &interface.dns_servers {
+            add_resolver(&mut resolvers, SocketAddr::new(*ip, DNS_PORT), &interface.name);
+        }
+    }
+    for address in nameservers(RESOLV_CONF) {
+        add_resolver(&mut resolvers, address, "resolv.conf");
+    }
+    // Bei aktivem Stub (127.0.0.53) stehen hier die eigentlichen Upstream-Server
+    for address in nameservers(RESOLVED_UPSTREAM) {
+        add_resolver(&mut resolvers, address, "systemd-resolved");
+    }
+    for (address, fallback) in resolved_conf_servers() {
+        add_resolver(&mut resolvers, address, if fallback { "resolved.conf (Fallback)" } else { "resolved.conf" });
+    }
+    for address in extra {
+        add_resolver(&mut resolvers, *address, "--server");
+    }
+    resolvers
+}
+
+fn add_resolver(resolvers: &mut Vec<Resolver>, address: SocketAddr, source: &str) {
+    match resolvers.iter_mut().find(|resolver| resolver.address == address) {
+        Some(resolver) if !resolver.sources.iter().any(|known| known == source) => resolver.sources.push(source.to_string()),
+        Some(_) => {}
+        None => resolvers.push(Resolver { address, sources: vec![source.to_string()] }),
+    }
+}
+
+/// `nameserver`-Zeilen einer resolv.conf; fehlende Dateien ergeben nichts
+fn nameservers(path: &str) -> Vec<SocketAddr> {
+    let Ok(content) = std::fs::read_to_string(path) else { return Vec::new() };
+    content.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != "nameserver" {
+                return None;
+            }
+            // Link-lokale IPv6-Adressen tragen eine Zone ("fe80::1%eth0")
+            let ip: IpAddr = fields.next()?.split('%').next()?.parse().ok()?;
+            Some(SocketAddr::new(ip, DNS_PORT))
+        })
+        .collect()
+}
+
+/// `DNS=` und `FallbackDNS=` aus resolved.conf und den Drop-ins; Einträge
+/// haben die Form `ADRESSE[:PORT][%INTERFACE][#SERVERNAME]`
+fn resolved_conf_servers() -> Vec<(SocketAddr, bool)> {
+    let mut files = vec![std::path::PathBuf::from(RESOLVED_CONF)];
+    if let Ok(entries) = std::fs::read_dir(RESOLVED_CONF_DIR) {
+        let mut drop_ins: Vec<_> = entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "conf"))
+            .collect();
+        drop_ins.sort();
+        files.extend(drop_ins);
+    }
+
+    let mut servers = Vec::new();
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(&file) else { continue };
+        for line in content.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else { continue };
+            let fallback = match key.trim() {
+                "DNS" => false,
+                "FallbackDNS" => true,
+                _ => continue,
+            };
+            for entry in value.split_whitespace() {
+                let entry = entry.split('#').next().unwrap_or_default();
+                let entry = entry.split('%').next().unwrap_or_default();
+                let address = entry.parse::<SocketAddr>().ok()
+                    .or_else(|| entry.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, DNS_PORT)));
+                if let Some(address) = address {
+                    servers.push((address, fallback));
+                }
+            }
+        }
+    }
+    servers
+}
+
+/// Fragt jeden Resolver nach allen Record-Typen und zeigt eine Matrix
+/// (Resolver × Typ) samt DNSSEC-Validierung und EDNS-Unterstützung
+pub async fn compare_resolvers(name: &Name, record_types: &[RecordType], resolvers: Vec<Resolver>, timeout: Duration, text: bool) {
+    if text {
+        let types: Vec<String> = record_types.iter().map(ToString::to_string).collect();
+        println!("🔎 Resolver-Vergleich für {} ({}), {} Resolver\n", name, types.join(", "), resolvers.len());
+    }
+
+    let started_at = chrono::Local::now();
+    let mut tasks = JoinSet::new();
+    for (index, resolver) in resolvers.iter().enumerate() {
+        for (column, record_type) in record_types.iter().copied().enumerate() {
+            let (server, name) = (resolver.address, name.clone());
+            tasks.spawn(async move { (index, column, query_with_fallback(server, &name, record_type, timeout).await) });
+        }
+    }
+
+    let mut outcomes: BTreeMap<(usize, usize), Result<Option<DnsResponse>, String>> = BTreeMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, column, outcome)) = joined {
+            outcomes.insert((index, column), outcome);
+        }
+    }
+
+    // DNSSEC und EDNS aus der ersten beantworteten Abfrage je Resolver
+    let infos: Vec<ResolverInfo> = resolvers.iter().enumerate()
+        .map(|(index, resolver)| {
+            let response = (0..record_types.len())
+                .find_map(|column| match outcomes.get(&(index, column)) {
+                    Some(Ok(Some(response))) => Some(response),
+                    _ => None,
+                });
+            ResolverInfo {
+                server: resolver.address,
+                sources: resolver.sources.clone(),
+                dnssec_validated: response.map(|response| response.authentic_data),
+                edns: response.map(|response| response.edns_payload.is_some()),
+                edns_payload: response.and_then(|response| response.edns_payload),
+            }
+        })
+        .collect();
+
+    let mut columns = result_columns(&resolvers, record_types.len(), outcomes);
+    let variants: Vec<usize> = columns.iter_mut().map(|column| mark_disagreement(column)).collect();
+    let consistent = variants.iter().all(|count| *count <= 1)
+        && columns.iter().flatten().all(|result| result.status == ServerStatus::Ok);
+
+    if text {
+        print_matrix(record_types, &infos, &columns);
+        print_findings(record_types, &infos, &columns, &variants);
+    } else {
+        let report = CompareReport {
+            name: name.to_string(),
+            started_at: started_at.to_rfc3339(),
+            consistent,
+            resolvers: infos,
+            results: record_types.iter().map(ToString::to_string).zip(columns).collect(),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+
+    if !consistent {
+        std::process::exit(1);
+    }
+}
+
+/// Ergebnisse je Record-Typ, jeweils genau ein Eintrag pro Resolver in
+/// dessen Reihenfolge; fehlt ein Ergebnis (abgebrochene Abfrage), steht dort
+/// ein Fehler, damit Matrix und Befunde beim richtigen Resolver bleiben
+fn result_columns(
+    resolvers: &[Resolver],
+    column_count: usize,
+    mut outcomes: BTreeMap<(usize, usize), Result<Option<DnsResponse>, String>>,
+) -> Vec<Vec<ServerResult>> {
+    let mut columns = Vec::with_capacity(column_count);
+    for column in 0..column_count {
+        let mut results = Vec::with_capacity(resolvers.len());
+        for (index, resolver) in resolvers.iter().enumerate() {
+            let outcome = outcomes.remove(&(index, column))
+                .unwrap_or_else(|| Err("Abfrage abgebrochen".to_string()));
+            results.push(ServerResult::new(resolver.address, outcome));
+        }
+        columns.push(results);
+    }
+    columns
+}
+
+/// Abfrage mit EDNS; lehnt der Server EDNS ab (FORMERR/NOTIMP ohne OPT)
+/// oder bleibt die Antwort aus, wird einmal ohne EDNS wiederholt. Manche
+/// Resolver und Middleboxen verwerfen EDNS/DO-Anfragen kommentarlos - kommt
+/// die einfache Anfrage durch, fehlt der OPT-Record und EDNS gilt als nicht
+/// unterstützt.
+async fn query_with_fallback(server: SocketAddr, name: &Name, record_type: RecordType, timeout: Duration) -> Result<Option<DnsResponse>, String> {
+    let response = query(server, name, record_type, timeout, true).await?;
+    match response {
+        Some(response)
+            if matches!(response.rcode, ResponseCode::FormErr | ResponseCode::NotImp) && response.edns_payload.is_none() =>
+        {
+            query(server, name, record_type, timeout, false).await
+        }
+        None => query(server, name, record_type, timeout, false).await,
+        other => Ok(other),
+    }
+}
+
+/// Zelle der Matrix: Daten der Antwort, sonst Antwortcode oder Timeout
+fn cell(result: &ServerResult) -> String {
+    match result.status {
+        ServerStatus::Ok if result.answers.is_empty() => match result.rcode.as_deref() {
+            Some("NOERROR") => "(leer)".to_string(),
+            rcode => rcode.unwrap_or("-").to_string(),
+        },
+        ServerStatus::Ok => {
+            let data: Vec<&str> = result.answers.iter().map(|answer| answer.data.as_str()).collect();
+            data.join(", ")
+        }
+        ServerStatus::Timeout => "Timeout".to_string(),
+        ServerStatus::Error => "Fehler".to_string(),
+    }
+}
+
+fn print_matrix(record_types: &[RecordType], infos: &[ResolverInfo], columns: &[Vec<ServerResult>]) {
+    let servers: Vec<String> = infos.iter().map(|info| info.server.to_string()).collect();
+    let sources: Vec<String> = infos.iter().map(|info| info.sources.join(", ")).collect();
+    let cells: Vec<Vec<String>> = columns.iter()
+        .map(|column| column.iter().map(|result| {
+            if result.agrees { cell(result) } else { format!("{} ⚠", cell(result)) }
+        }).collect())
+        .collect();
+
+    let width = |values: &[String], title: &str| {
+        values.iter().map(|value| value.chars().count()).max().unwrap_or(0).max(title.chars().count())
+    };
+    let server_width = width(&servers, "Resolver");
+    let source_width = width(&sources, "Quelle");
+    let widths: Vec<usize> = record_types.iter().zip(&cells)
+        .map(|(record_type, column)| width(column, &record_type.to_string()))
+        .collect();
+
+    let mut header = format!("   {:<server_width$}  {:<source_width$}", "Resolver", "Quelle");
+    for (record_type, width) in record_types.iter().zip(&widths) {
+        header.push_str(&format!("  {:<width$}", record_type.to_string(), width = width));
+    }
+    println!("{}  {:>9}  {:<7}  EDNS", header, "Zeit", "DNSSEC");
+
+    for (index, info) in infos.iter().enumerate() {
+        let results: Vec<&ServerResult> = columns.iter().map(|column| &column[index]).collect();
+        let icon = if results.iter().any(|result| result.status != ServerStatus::Ok) {
+            "🔴"
+        } else if results.iter().any(|result| !result.agrees) {
+            "🟡"
+        } else {
+            "🟢"
+        };
+        let mut line = format!("{} {:<server_width$}  {:<source_width$}", icon, servers[index], sources[index]);
+        for (column, width) in cells.iter().zip(&widths) {
+            line.push_str(&format!("  {:<width$}", column[index], width = width));
+        }
+        let time = results.iter().filter_map(|result| result.time_ms).reduce(f64::max)
+            .map(|ms| format!("{:.2} ms", ms))
+            .unwrap_or_else(|| "-".to_string());
+        let dnssec = match info.dnssec_validated {
+            Some(true) => "✅ AD",
+            Some(false) => "nein",
+            None => "-",
+        };
+        let edns = match (info.edns, info.edns_payload) {
+            (Some(true), Some(payload)) => format!("ja ({} B)", payload),
+            (Some(false), _) => "nein".to_string(),
+            _ => "-".to_string(),
+        };
+        println!("{}  {:>9}  {:<7}  {}", line, time, dnssec, edns);
+    }
+    println!("   Zeit: langsamste Abfrage je Resolver, ⚠ = weicht von der Mehrheit ab");
+}
+
+fn print_findings(record_types: &[RecordType], infos: &[ResolverInfo], columns: &[Vec<ServerResult>], variants: &[usize]) {
+    println!();
+    let mut findings = false;
+    for ((record_type, column), variants) in record_types.iter().zip(columns).zip(variants) {
+        if *variants <= 1 {
+            continue;
+        }
+        findings = true;
+        let differing: Vec<String> = column.iter()
+            .filter(|result| !result.agrees)
+            .map(|result| result.server.to_string())
+            .collect();
+        println!("⚠️  {}: {} verschiedene Antworten, abweichend: {}", record_type, variants, differing.join(", "));
+        // Ein Teil kennt den Namen, ein anderer nicht: typisch für Split-Horizon
+        let unknown = column.iter().any(|result| result.rcode.as_deref() == Some("NXDOMAIN"));
+        let known = column.iter().any(|result| !result.answers.is_empty());
+        if unknown && known {
+            println!("💡 Tipp: Nur ein Teil der Resolver kennt den Namen (Split-Horizon). Typisch für VPN-Resolver, die nur interne");
+            println!("   Namen auflösen, oder öffentliche Resolver ohne interne Zonen. Resolver-Reihenfolge bzw. Routing-Domains prüfen.");
+        }
+    }
+
+    let timeouts: Vec<String> = infos.iter().enumerate()
+        .filter(|(index, _)| columns.iter().any(|column| column[*index].status != ServerStatus::Ok))
+        .map(|(_, info)| info.server.to_string())
+        .collect();
+    if !timeouts.is_empty() {
+        findings = true;
+        println!("⏱️  Ohne (vollständige) Antwort: {}", timeouts.join(", "));
+    }
+
+    let validating: Vec<String> = infos.iter()
+        .filter(|info| info.dnssec_validated == Some(true))
+        .map(|info| info.server.to_string())
+        .collect();
+    if !validating.is_empty() {
+        println!("🔐 DNSSEC validiert (AD-Bit) von: {}", validating.join(", "));
+    } else if infos.iter().any(|info| info.dnssec_validated.is_some()) {
+        println!("🔓 Kein Resolver hat die Antwort per DNSSEC bestätigt (Name unsigniert oder Resolver validiert nicht)");
+    }
+    let without_edns: Vec<String> = infos.iter()
+        .filter(|info| info.edns == Some(false))
+        .map(|info| info.server.to_string())
+        .collect();
+    if !without_edns.is_empty() {
+        findings = true;
+        println!("⚠️  Ohne EDNS-Unterstützung: {} (große Antworten nur per TCP, kein DNSSEC)", without_edns.join(", "));
+    }
+
+    if !findings && infos.len() > 1 {
+        println!("✅ Alle {} Resolver liefern dieselben Antworten", infos.len());
+    } else if !findings {
+        println!("✅ Nur ein Resolver gefunden, er hat vollständig geantwortet");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::dns::test_server::{reply, reply_a, spawn};
+    use hickory_proto::op::Message;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn name() -> Name {
+        Name::from_ascii("test.example.").unwrap()
+    }
+
+    #[test]
+    fn missing_outcomes_stay_with_their_resolver() {
+        let resolvers: Vec<Resolver> = ["192.0.2.1:53", "192.0.2.2:53", "192.0.2.3:53"].iter()
+            .map(|address| Resolver { address: address.parse().unwrap(), sources: vec!["--server".to_string()] })
+            .collect();
+        // Die erste Abfrage von Resolver 1 fehlt, Resolver 2 meldet einen Fehler
+        let outcomes = BTreeMap::from([
+            ((0, 0), Ok(None)),
+            ((2, 0), Err("Verbindung abgelehnt".to_string())),
+            ((0, 1), Ok(None)),
+            ((1, 1), Ok(None)),
+            ((2, 1), Ok(None)),
+        ]);
+
+        let columns = result_columns(&resolvers, 2, outcomes);
+        assert!(columns.iter().all(|column| column.len() == resolvers.len()));
+        for column in &columns {
+            let servers: Vec<SocketAddr> = column.iter().map(|result| result.server).collect();
+            let expected: Vec<SocketAddr> = resolvers.iter().map(|resolver| resolver.address).collect();
+            assert_eq!(servers, expected);
+        }
+        assert!(columns[0][0].status == ServerStatus::Timeout);
+        assert!(columns[0][1].status == ServerStatus::Error);
+        assert_eq!(columns[0][1].error.as_deref(), Some("Abfrage abgebrochen"));
+        assert_eq!(columns[0][2].error.as_deref(), Some("Verbindung abgelehnt"));
+    }
+
+    #[tokio::test]
+    async fn silently_dropped_edns_counts_as_unsupported() {
+        // Middlebox verwirft alles mit OPT-Record
+        let server = spawn(Arc::new(|request: &Message, _| {
+            request.extensions().is_none().then(|| reply_a(request, &[Ipv4Addr::new(192, 0, 2, 7)]))
+        })).await;
+        let response = query_with_fallback(server, &name(), RecordType::A, Duration::from_millis(200)).await
+            .unwrap().expect("Antwort ohne EDNS erwartet");
+        assert_eq!(response.edns_payload, None);
+        assert_eq!(response.answers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn formerr_is_retried_without_edns() {
+        let server = spawn(Arc::new(|request: &Message, _| {
+            if request.extensions().is_some() {
+                let mut response = reply(request);
+                *response.extensions_mut() = None;
+                response.set_response_code(ResponseCode::FormErr);
+                Some(response)
+            } else {
+                Some(reply_a(request, &[Ipv4Addr::new(192, 0, 2, 7)]))
+            }
+        })).await;
+        let response = query_with_fallback(server, &name(), RecordType::A, Duration::from_secs(2)).await
+            .unwrap().expect("Antwort erwartet");
+        assert_eq!(response.rcode, ResponseCode::NoError);
+        assert_eq!(response.edns_payload, None);
+    }
+
+    #[tokio::test]
+    async fn edns_answer_is_kept_and_dead_server_times_out() {
+        let server = spawn(Arc::new(|request: &Message, _| Some(reply_a(request, &[Ipv4Addr::new(192, 0, 2, 7)])))).await;
+        let response = query_with_fallback(server, &name(), RecordType::A, Duration::from_secs(2)).await
+            .unwrap().expect("Antwort erwartet");
+        assert_eq!(response.edns_payload, Some(1232));
+
+        let server = spawn(Arc::new(|_: &Message, _| None)).await;
+        let response = query_with_fallback(server, &name(), RecordType::A, Duration::from_millis(100)).await.unwrap();
+        assert!(response.is_none());
+    }
+}
@@ -0,0 +1,435 @@
+mod compare;
+mod query;
+/// Kleiner DNS-Server auf 127.0.0.1 als Gegenstelle für die Tests
+#[cfg(test)]
+mod test_server;
+
+use hickory_proto::rr::{Name, RecordType};
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::commands::ping::{resolve_target, OutputFormat};
+use compare::{collect_resolvers, compare_resolvers};
+use query::{query, rcode_name, DnsAnswer, DnsResponse};
+
+/// Standard-Port für DNS
+const DNS_PORT: u16 = 53;
+
+/// Abfragbare Record-Typen
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum RecordKind {
+    A,
+    Aaaa,
+    Mx,
+    Txt,
+    Cname,
+    Ns,
+    Soa,
+    Ptr,
+}
+
+impl RecordKind {
+    fn record_type(self) -> RecordType {
+        match self {
+            RecordKind::A => RecordType::A,
+            RecordKind::Aaaa => RecordType::AAAA,
+            RecordKind::Mx => RecordType::MX,
+            RecordKind::Txt => RecordType::TXT,
+            RecordKind::Cname => RecordType::CNAME,
+            RecordKind::Ns => RecordType::NS,
+            RecordKind::Soa => RecordType::SOA,
+            RecordKind::Ptr => RecordType::PTR,
+        }
+    }
+}
+
+/// Einstellungen für `dns`
+pub struct DnsOptions {
+    /// Record-Typ; ohne Angabe A bzw. PTR für IP-Adressen
+    pub record_type: Option<RecordKind>,
+    /// Zu prüfende Server; leer = DNS-Server des Standard-Interfaces
+    pub servers: Vec<String>,
+    /// Wartezeit auf eine Antwort
+    pub timeout: Duration,
+    pub format: OutputFormat,
+    /// Alle bekannten Resolver (Interfaces, resolv.conf, systemd-resolved) als Matrix vergleichen
+    pub compare: bool,
+}
+
+/// Antwortcode und Records (Typ, Daten) einer Antwort, ohne TTL
+type AnswerKey = (String, Vec<(String, String)>);
+
+/// Ausgang einer Abfrage
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ServerStatus {
+    Ok,
+    Timeout,
+    Error,
+}
+
+/// Ergebnis eines Servers
+#[derive(Serialize)]
+struct ServerResult {
+    server: SocketAddr,
+    status: ServerStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rcode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_ms: Option<f64>,
+    /// Antwort war per UDP abgeschnitten und kam per TCP
+    tcp: bool,
+    answers: Vec<DnsAnswer>,
+    /// Antwort stimmt mit der Mehrheit der Server überein
+    agrees: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ServerResult {
+    fn new(server: SocketAddr, outcome: Result<Option<DnsResponse>, String>) -> Self {
+        let mut result = ServerResult {
+            server,
+            status: ServerStatus::Ok,
+            rcode: None,
+            time_ms: None,
+            tcp: false,
+            answers: Vec::new(),
+            agrees: true,
+            error: None,
+        };
+        match outcome {
+            Ok(Some(response)) => {
+                result.rcode = Some(rcode_name(response.rcode));
+                result.time_ms = Some(response.rtt.as_micros() as f64 / 1000.0);
+                result.tcp = response.over_tcp;
+                result.answers = response.answers;
+            }
+            Ok(None) => result.status = ServerStatus::Timeout,
+            Err(e) => {
+                result.status = ServerStatus::Error;
+                result.error = Some(e);
+            }
+        }
+        result
+    }
+
+    /// Vergleichsschlüssel: Antwortcode und Daten ohne TTL und Reihenfolge
+    fn answer_key(&self) -> Option<AnswerKey> {
+        let rcode = self.rcode.clone()?;
+        let mut records: Vec<(String, String)> = self.answers.iter()
+            .map(|answer| (answer.record_type.clone(), answer.data.to_lowercase()))
+            .collect();
+        records.sort();
+        Some((rcode, records))
+    }
+}
+
+/// Ergebnis für `--format json`
+#[derive(Serialize)]
+struct DnsReport {
+    name: String,
+    record_type: String,
+    started_at: String,
+    /// Alle Server haben geantwortet und stimmen überein
+    consistent: bool,
+    servers: Vec<ServerResult>,
+}
+
+pub async fn handle_dns_command(name: &str, options: DnsOptions) {
+    let (query_name, record_type) = match query_name(name, options.record_type) {
+        Ok(query) => query,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let text = options.format == OutputFormat::Text;
+    if options.compare {
+        // --server ergänzt hier die gefundenen Resolver
+        let extra = if options.servers.is_empty() {
+            Vec::new()
+        } else {
+            match collect_servers(&options.servers).await {
+                Ok(servers) => servers,
+                Err(e) => {
+                    println!("❌ {}", e);
+                    std::process::exit(2);
+                }
+            }
+        };
+        let resolvers = collect_resolvers(&extra);
+        if resolvers.is_empty() {
+            println!("❌ Keine Resolver gefunden, bitte --server angeben.");
+            std::process::exit(2);
+        }
+        // Ohne --type beide Adresstypen vergleichen
+        let record_types = if options.record_type.is_none() && record_type == RecordType::A {
+            vec![RecordType::A, RecordType::AAAA]
+        } else {
+            vec![record_type]
+        };
+        compare_resolvers(&query_name, &record_types, resolvers, options.timeout, text).await;
+        return;
+    }
+
+    let servers = match collect_servers(&options.servers).await {
+        Ok(servers) => servers,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if text {
+        println!("🔎 DNS-Abfrage {} ({}) an {} Server\n", query_name, record_type, servers.len());
+    }
+
+    let started_at = chrono::Local::now();
+    let mut tasks = JoinSet::new();
+    for (index, server) in servers.iter().copied().enumerate() {
+        let (query_name, timeout) = (query_name.clone(), options.timeout);
+        tasks.spawn(async move { (index, ServerResult::new(server, query(server, &query_name, record_type, timeout, false).await)) });
+    }
+    let mut results: Vec<Option<ServerResult>> = (0..servers.len()).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+    let mut results: Vec<ServerResult> = results.into_iter().flatten().collect();
+
+    let variants = mark_disagreement(&mut results);
+    let failed = results.iter().filter(|result| result.status != ServerStatus::Ok).count();
+    let consistent = failed == 0 && variants <= 1;
+
+    if text {
+        for result in &results {
+            print_server(result, options.timeout);
+        }
+        print_verdict(&results, variants);
+    } else {
+        let report = DnsReport {
+            name: query_name.to_string(),
+            record_type: record_type.to_string(),
+            started_at: started_at.to_rfc3339(),
+            consistent,
+            servers: results,
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+
+    // Für Skripte: Timeouts, Fehler oder abweichende Antworten
+    if !consistent {
+        std::process::exit(1);
+    }
+}
+
+/// Abgefragter Name und Typ; IP-Adressen werden ohne `--type` (oder mit
+/// PTR) in den Reverse-Namen umgewandelt
+fn query_name(name: &str, kind: Option<RecordKind>) -> Result<(Name, RecordType), String> {
+    let ip = name.parse::<IpAddr>().ok();
+    let record_type = kind.map(RecordKind::record_type)
+        .unwrap_or(if ip.is_some() { RecordType::PTR } else { RecordType::A });
+    let mut query_name = match ip {
+        Some(ip) if record_type == RecordType::PTR => Name::from(ip),
+        _ => Name::from_utf8(name).map_err(|e| format!("Ungültiger Name '{}': {}", name, e))?,
+    };
+    query_name.set_fqdn(true);
+    Ok((query_name, record_type))
+}
+
+/// Server aus `--server` (IP, IP:Port oder Hostname) oder die DNS-Server
+/// des Standard-Interfaces
+async fn collect_servers(servers: &[String]) -> Result<Vec<SocketAddr>, String> {
+    if servers.is_empty() {
+        let interface = netdev::get_default_interface()
+            .map_err(|e| format!("Standard-Interface konnte nicht ermittelt werden: {}", e))?;
+        if interface.dns_servers.is_empty() {
+            return Err(format!("Interface {} hat keine DNS-Server, bitte --server angeben.", interface.name));
+        }
+        return Ok(interface.dns_servers.iter().map(|ip| SocketAddr::new(*ip, DNS_PORT)).collect());
+    }
+
+    let mut addresses = Vec::new();
+    for server in servers {
+        let address = match (server.parse::<SocketAddr>(), server.parse::<IpAddr>()) {
+            (Ok(address), _) => address,
+            (_, Ok(ip)) => SocketAddr::new(ip, DNS_PORT),
+            _ => {
+                let ip = resolve_target(server).await
+                    .map_err(|e| format!("Server '{}' konnte nicht aufgelöst werden: {}", server, e))?;
+                SocketAddr::new(ip, DNS_PORT)
+            }
+        };
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    Ok(addresses)
+}
+
+/// Markiert Server, deren Antwort von der häufigsten abweicht, und
+/// liefert die Anzahl unterschiedlicher Antworten
+fn mark_disagreement(results: &mut [ServerResult]) -> usize {
+    let mut variants: Vec<(AnswerKey, usize)> = Vec::new();
+    for key in results.iter().filter_map(ServerResult::answer_key) {
+        match variants.iter_mut().find(|(known, _)| *known == key) {
+            Some((_, count)) => *count += 1,
+            None => variants.push((key, 1)),
+        }
+    }
+    // Bei Gleichstand zählt eine Antwort mit Einträgen mehr als NXDOMAIN
+    // oder eine leere Antwort, danach die zuerst gesehene
+    let weight = |(key, count): &(AnswerKey, usize)| (*count, !key.1.is_empty());
+    let majority = variants.iter()
+        .fold(None, |best: Option<&(AnswerKey, usize)>, variant| match best {
+            Some(best) if weight(best) >= weight(variant) => Some(best),
+            _ => Some(variant),
+        })
+        .map(|(key, _)| key.clone());
+    for result in results.iter_mut() {
+        result.agrees = result.answer_key().is_none() || result.answer_key() == majority;
+    }
+    variants.len()
+}
+
+fn print_server(result: &ServerResult, timeout: Duration) {
+    match result.status {
+        ServerStatus::Ok => {
+            let icon = if !result.agrees {
+                "⚠️ "
+            } else if result.rcode.as_deref() == Some("NOERROR") {
+                "✅"
+            } else {
+                "🟡"
+            };
+            let tcp = if result.tcp { ", per TCP wiederholt" } else { "" };
+            println!("{} {}  {} in {:.2} ms{}{}",
+                     icon, result.server, result.rcode.as_deref().unwrap_or("-"), result.time_ms.unwrap_or_default(), tcp,
+                     if result.agrees { "" } else { "  ← weicht ab" });
+            if result.answers.is_empty() {
+                println!("     (keine Einträge)");
+            }
+            for answer in &result.answers {
+                println!("     {}  {}  {}  {}", answer.name, answer.ttl, answer.record_type, answer.data);
+            }
+        }
+        ServerStatus::Timeout => println!("⏱️  {}  Timeout, keine Antwort nach {} ms", result.server, timeout.as_millis()),
+        ServerStatus::Error => println!("❌ {}  Fehler: {}", result.server, result.error.as_deref().unwrap_or("unbekannt")),
+    }
+}
+
+fn print_verdict(results: &[ServerResult], variants: usize) {
+    let answered = results.iter().filter(|result| result.status == ServerStatus::Ok).count();
+    let timeouts: Vec<String> = results.iter()
+        .filter(|result| result.status == ServerStatus::Timeout)
+        .map(|result| result.server.to_string())
+        .collect();
+
+    let mut lines = Vec::new();
+    match variants {
+        0 => lines.push("❌ Kein Server hat geantwortet".to_string()),
+        1 if answered > 1 => lines.push(format!("✅ Alle {} antwortenden Server liefern dieselbe Antwort", answered)),
+        1 => {}
+        _ => {
+            let differing: Vec<String> = results.iter()
+                .filter(|result| !result.agrees)
+                .map(|result| result.server.to_string())
+                .collect();
+            lines.push(format!("⚠️  Server antworten unterschiedlich ({} Varianten), abweichend: {}", variants, differing.join(", ")));
+        }
+    }
+    if !timeouts.is_empty() {
+        lines.push(format!("⏱️  Ohne Antwort: {}", timeouts.join(", ")));
+        lines.push("💡 Tipp: Erreichbarkeit mit 'shc-tool ping --udp 53 <Server>' prüfen.".to_string());
+    }
+    if !lines.is_empty() {
+        println!("\n{}", lines.join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_proto::op::ResponseCode;
+
+    fn answered(port: u16, rcode: ResponseCode, records: &[(&str, u32)]) -> ServerResult {
+        let answers = records.iter()
+            .map(|(data, ttl)| DnsAnswer {
+                name: "test.example.".to_string(),
+                record_type: "A".to_string(),
+                ttl: *ttl,
+                data: data.to_string(),
+            })
+            .collect();
+        let response = DnsResponse {
+            rcode,
+            answers,
+            rtt: Duration::from_millis(5),
+            over_tcp: false,
+            authentic_data: false,
+            edns_payload: None,
+        };
+        ServerResult::new(server(port), Ok(Some(response)))
+    }
+
+    fn server(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::from([127, 0, 0, 1]), port)
+    }
+
+    #[test]
+    fn majority_wins() {
+        let mut results = vec![
+            answered(1, ResponseCode::NoError, &[("192.0.2.1", 300)]),
+            answered(2, ResponseCode::NoError, &[("192.0.2.9", 300)]),
+            answered(3, ResponseCode::NoError, &[("192.0.2.1", 300)]),
+        ];
+        assert_eq!(mark_disagreement(&mut results), 2);
+        let agrees: Vec<bool> = results.iter().map(|result| result.agrees).collect();
+        assert_eq!(agrees, [true, false, true]);
+    }
+
+    #[test]
+    fn ttl_and_order_are_ignored() {
+        let mut results = vec![
+            answered(1, ResponseCode::NoError, &[("192.0.2.1", 300), ("192.0.2.2", 300)]),
+            answered(2, ResponseCode::NoError, &[("192.0.2.2", 17), ("192.0.2.1", 17)]),
+        ];
+        assert_eq!(mark_disagreement(&mut results), 1);
+        assert!(results.iter().all(|result| result.agrees));
+    }
+
+    #[test]
+    fn tie_prefers_answer_with_records() {
+        let mut results = vec![
+            answered(1, ResponseCode::NXDomain, &[]),
+            answered(2, ResponseCode::NoError, &[("10.0.0.5", 60)]),
+        ];
+        assert_eq!(mark_disagreement(&mut results), 2);
+        assert!(!results[0].agrees);
+        assert!(results[1].agrees);
+    }
+
+    #[test]
+    fn timeouts_and_errors_do_not_vote() {
+        let mut results = vec![
+            ServerResult::new(server(1), Ok(None)),
+            answered(2, ResponseCode::NoError, &[("192.0.2.1", 300)]),
+            ServerResult::new(server(3), Err("Verbindung abgelehnt".to_string())),
+        ];
+        assert_eq!(mark_disagreement(&mut results), 1);
+        assert!(results.iter().all(|result| result.agrees));
+        assert!(results[0].status == ServerStatus::Timeout);
+        assert!(results[2].status == ServerStatus::Error);
+
+        let mut silent = vec![ServerResult::new(server(1), Ok(None))];
+        assert_eq!(mark_disagreement(&mut silent), 0);
+    }
+}
@@ -0,0 +1,221 @@
+use hickory_proto::op::{Edns, Message, MessageType, OpCode, Query, ResponseCode};
+use hickory_proto::rr::{Name, RecordType};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+
+/// Ein Eintrag aus dem Answer-Abschnitt
+#[derive(Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DnsAnswer {
+    pub name: String,
+    pub record_type: String,
+    pub ttl: u32,
+    pub data: String,
+}
+
+/// Antwort eines DNS-Servers
+pub struct DnsResponse {
+    pub rcode: ResponseCode,
+    pub answers: Vec<DnsAnswer>,
+    pub rtt: Duration,
+    /// Antwort war per UDP abgeschnitten und kam erneut per TCP
+    pub over_tcp: bool,
+    /// AD-Bit: der Resolver hat die Antwort per DNSSEC validiert
+    pub authentic_data: bool,
+    /// Vom Server angebotene UDP-Größe, wenn er mit EDNS geantwortet hat
+    pub edns_payload: Option<u16>,
+}
+
+/// UDP-Größe für EDNS-Anfragen (DNS Flag Day 2020)
+const EDNS_PAYLOAD: u16 = 1232;
+
+/// Fragt einen Server per UDP und wiederholt die Abfrage per TCP, wenn die
+/// Antwort abgeschnitten ist (TC-Bit). Mit `edns` geht die Anfrage mit
+/// EDNS und DO-Bit raus, damit validierende Resolver das AD-Bit setzen.
+/// `None` bei Timeout.
+pub async fn query(
+    server: SocketAddr,
+    name: &Name,
+    record_type: RecordType,
+    timeout: Duration,
+    edns: bool,
+) -> Result<Option<DnsResponse>, String> {
+    let id = query_id();
+    let mut message = Message::new();
+    message.set_id(id)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name.clone(), record_type));
+    if edns {
+        let mut extension = Edns::new();
+        extension.set_max_payload(EDNS_PAYLOAD).set_dnssec_ok(true);
+        message.set_authentic_data(true).set_edns(extension);
+    }
+    let request = message.to_vec().map_err(|e| format!("Anfrage konnte nicht erzeugt werden: {}", e))?;
+
+    let started = Instant::now();
+    let exchange = async {
+        let mut response = exchange_udp(server, &request, id).await?;
+        let over_tcp = response.truncated();
+        if over_tcp {
+            response = exchange_tcp(server, &request, id).await?;
+        }
+        Ok::<(Message, bool), String>((response, over_tcp))
+    };
+    let (response, over_tcp) = match tokio::time::timeout(timeout, exchange).await {
+        Ok(result) => result?,
+        Err(_) => return Ok(None),
+    };
+
+    // Signaturen unterscheiden sich je Resolver und sind für den Vergleich unerheblich
+    let answers = response.answers().iter()
+        .filter(|record| record.record_type() != RecordType::RRSIG)
+        .map(|record| DnsAnswer {
+            name: record.name().to_string(),
+            record_type: record.record_type().to_string(),
+            ttl: record.ttl(),
+            data: record.data().map(|data| data.to_string()).unwrap_or_default(),
+        })
+        .collect();
+    Ok(Some(DnsResponse {
+        rcode: response.response_code(),
+        answers,
+        rtt: started.elapsed(),
+        over_tcp,
+        authentic_data: response.authentic_data(),
+        edns_payload: response.extensions().as_ref().map(Edns::max_payload),
+    }))
+}
+
+async fn exchange_udp(server: SocketAddr, request: &[u8], id: u16) -> Result<Message, String> {
+    let bind: SocketAddr = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().expect("gültige Adresse");
+    let socket = UdpSocket::bind(bind).await.map_err(|e| e.to_string())?;
+    socket.connect(server).await.map_err(|e| e.to_string())?;
+    socket.send(request).await.map_err(|e| e.to_string())?;
+
+    let mut buffer = vec![0u8; 4096];
+    loop {
+        let length = socket.recv(&mut buffer).await.map_err(|e| e.to_string())?;
+        // Verspätete oder fremde Antworten ignorieren
+        match Message::from_vec(&buffer[..length]) {
+            Ok(response) if response.id() == id => return Ok(response),
+            _ => continue,
+        }
+    }
+}
+
+async fn exchange_tcp(server: SocketAddr, request: &[u8], id: u16) -> Result<Message, String> {
+    let mut stream = TcpStream::connect(server).await.map_err(|e| format!("TCP: {}", e))?;
+    // Bei TCP steht die Länge als 16-Bit-Wert vor der Nachricht
+    let mut framed = (request.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(request);
+    stream.write_all(&framed).await.map_err(|e| format!("TCP: {}", e))?;
+
+    let length = stream.read_u16().await.map_err(|e| format!("TCP: {}", e))?;
+    let mut buffer = vec![0u8; usize::from(length)];
+    stream.read_exact(&mut buffer).await.map_err(|e| format!("TCP: {}", e))?;
+    let response = Message::from_vec(&buffer).map_err(|e| format!("TCP: ungültige Antwort: {}", e))?;
+    if response.id() != id {
+        return Err("TCP: Antwort mit falscher ID".to_string());
+    }
+    Ok(response)
+}
+
+/// Schwer vorhersagbare Abfrage-ID aus Uhrzeit und Prozess-ID
+fn query_id() -> u16 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default();
+    (nanos ^ std::process::id().rotate_left(16)) as u16
+}
+
+/// Kurzname des Antwortcodes wie bei dig (NOERROR, NXDOMAIN, ...)
+pub fn rcode_name(rcode: ResponseCode) -> String {
+    match rcode {
+        ResponseCode::NoError => "NOERROR".to_string(),
+        ResponseCode::FormErr => "FORMERR".to_string(),
+        ResponseCode::ServFail => "SERVFAIL".to_string(),
+        ResponseCode::NXDomain => "NXDOMAIN".to_string(),
+        ResponseCode::NotImp => "NOTIMP".to_string(),
+        ResponseCode::Refused => "REFUSED".to_string(),
+        other => format!("RCODE{}", u16::from(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::dns::test_server::{reply, reply_a, spawn};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    fn name() -> Name {
+        Name::from_ascii("test.example.").unwrap()
+    }
+
+    #[tokio::test]
+    async fn answer_and_rcode() {
+        let server = spawn(Arc::new(|request: &Message, _| Some(reply_a(request, &[Ipv4Addr::new(192, 0, 2, 7)])))).await;
+        let response = query(server, &name(), RecordType::A, Duration::from_secs(2), false).await
+            .unwrap().expect("Antwort erwartet");
+        assert_eq!(response.rcode, ResponseCode::NoError);
+        assert!(!response.over_tcp);
+        assert_eq!(response.edns_payload, None);
+        assert_eq!(response.answers.len(), 1);
+        assert_eq!(response.answers[0].data, "192.0.2.7");
+        assert_eq!(response.answers[0].ttl, 300);
+
+        let server = spawn(Arc::new(|request: &Message, _| {
+            let mut response = reply(request);
+            response.set_response_code(ResponseCode::NXDomain);
+            Some(response)
+        })).await;
+        let response = query(server, &name(), RecordType::A, Duration::from_secs(2), false).await
+            .unwrap().expect("Antwort erwartet");
+        assert_eq!(rcode_name(response.rcode), "NXDOMAIN");
+        assert!(response.answers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn truncated_answer_is_repeated_over_tcp() {
+        let server = spawn(Arc::new(|request: &Message, tcp| {
+            if tcp {
+                Some(reply_a(request, &[Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2)]))
+            } else {
+                let mut response = reply(request);
+                response.set_truncated(true);
+                Some(response)
+            }
+        })).await;
+        let response = query(server, &name(), RecordType::A, Duration::from_secs(2), false).await
+            .unwrap().expect("Antwort erwartet");
+        assert!(response.over_tcp);
+        assert_eq!(response.answers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn timeout_without_answer() {
+        let server = spawn(Arc::new(|_: &Message, _| None)).await;
+        let started = Instant::now();
+        let response = query(server, &name(), RecordType::A, Duration::from_millis(200), false).await.unwrap();
+        assert!(response.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn edns_and_authentic_data() {
+        let server = spawn(Arc::new(|request: &Message, _| {
+            let mut response = reply_a(request, &[Ipv4Addr::new(192, 0, 2, 7)]);
+            response.set_authentic_data(request.extensions().is_some());
+            Some(response)
+        })).await;
+        let response = query(server, &name(), RecordType::A, Duration::from_secs(2), true).await
+            .unwrap().expect("Antwort erwartet");
+        assert_eq!(response.edns_payload, Some(1232));
+        assert!(response.authentic_data);
+    }
+}
@@ -0,0 +1,83 @@
+use hickory_proto::op::{Edns, Message, MessageType};
+use hickory_proto::rr::rdata::A;
+use hickory_proto::rr::{RData, Record};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, UdpSocket};
+
+/// Entscheidet je Anfrage über die Antwort; `tcp` gibt den Transportweg an.
+/// `None` verwirft die Anfrage (Timeout beim Client).
+pub type Responder = Arc<dyn Fn(&Message, bool) -> Option<Message> + Send + Sync>;
+
+/// Startet UDP- und TCP-Server auf demselben freien Port
+pub async fn spawn(responder: Responder) -> SocketAddr {
+    let (udp, tcp) = loop {
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        // Der TCP-Port kann zufällig belegt sein, dann neu versuchen
+        if let Ok(tcp) = TcpListener::bind(udp.local_addr().unwrap()).await {
+            break (udp, tcp);
+        }
+    };
+    let address = udp.local_addr().unwrap();
+
+    let udp_responder = responder.clone();
+    tokio::spawn(async move {
+        let mut buffer = vec![0u8; 4096];
+        while let Ok((length, peer)) = udp.recv_from(&mut buffer).await {
+            let Ok(request) = Message::from_vec(&buffer[..length]) else { continue };
+            if let Some(response) = udp_responder(&request, false) {
+                let _ = udp.send_to(&response.to_vec().unwrap(), peer).await;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = tcp.accept().await {
+            let responder = responder.clone();
+            tokio::spawn(async move {
+                let Ok(length) = stream.read_u16().await else { return };
+                let mut buffer = vec![0u8; usize::from(length)];
+                if stream.read_exact(&mut buffer).await.is_err() {
+                    return;
+                }
+                let Ok(request) = Message::from_vec(&buffer) else { return };
+                if let Some(response) = responder(&request, true) {
+                    let bytes = response.to_vec().unwrap();
+                    let mut framed = (bytes.len() as u16).to_be_bytes().to_vec();
+                    framed.extend_from_slice(&bytes);
+                    let _ = stream.write_all(&framed).await;
+                }
+            });
+        }
+    });
+
+    address
+}
+
+/// Antwort auf `request` ohne Einträge; mit OPT-Record, wenn die Anfrage EDNS nutzt
+pub fn reply(request: &Message) -> Message {
+    let mut response = Message::new();
+    response.set_id(request.id())
+        .set_message_type(MessageType::Response)
+        .set_op_code(request.op_code())
+        .set_recursion_desired(request.recursion_desired())
+        .set_recursion_available(true)
+        .add_queries(request.queries().to_vec());
+    if request.extensions().is_some() {
+        let mut extension = Edns::new();
+        extension.set_max_payload(1232);
+        response.set_edns(extension);
+    }
+    response
+}
+
+/// Antwort mit A-Records für den angefragten Namen
+pub fn reply_a(request: &Message, addresses: &[Ipv4Addr]) -> Message {
+    let mut response = reply(request);
+    let name = request.queries()[0].name().clone();
+    for address in addresses {
+        response.add_answer(Record::from_rdata(name.clone(), 300, RData::A(A(*address))));
+    }
+    response
+}
@@ -1,6 +1,136 @@
 use netdev;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Ausgabeformat von `ip`
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum IpFormat {
+    /// Ausführliche Textausgabe
+    Text,
+    /// Eine Zeile pro Interface
+    Table,
+    Json,
+    Yaml,
+}
+
+/// Alle Angaben eines Interfaces, wie sie `ip` ausgibt
+#[derive(Serialize)]
+pub struct InterfaceReport {
+    pub name: String,
+    pub friendly_name: Option<String>,
+    pub description: Option<String>,
+    pub index: u32,
+    pub if_type: String,
+    /// UP, RUNNING, LOOPBACK, ...
+    pub flags: Vec<&'static str>,
+    pub mac_address: Option<String>,
+    pub ipv4: Vec<Ipv4Report>,
+    pub ipv6: Vec<Ipv6Report>,
+    pub gateway: Option<GatewayReport>,
+    pub dns_servers: Vec<IpAddr>,
+    /// Geschwindigkeiten in Bit/s
+    pub transmit_speed: Option<u64>,
+    pub receive_speed: Option<u64>,
+    pub mtu: Option<u32>,
+    /// Standard-Interface (Default-Route)
+    pub default: bool,
+}
+
+#[derive(Serialize)]
+pub struct Ipv4Report {
+    pub address: Ipv4Addr,
+    pub prefix_len: u8,
+    pub netmask: Ipv4Addr,
+    /// Öffentlich routbare Adresse
+    pub global: bool,
+}
+
+#[derive(Serialize)]
+pub struct Ipv6Report {
+    pub address: Ipv6Addr,
+    pub prefix_len: u8,
+    pub scope_id: u32,
+    /// Öffentlich routbare Adresse
+    pub global: bool,
+}
+
+#[derive(Serialize)]
+pub struct GatewayReport {
+    pub mac_address: String,
+    pub ipv4: Vec<Ipv4Addr>,
+    pub ipv6: Vec<Ipv6Addr>,
+}
+
+impl From<&netdev::Interface> for InterfaceReport {
+    fn from(interface: &netdev::Interface) -> Self {
+        let mut flags = Vec::new();
+        if interface.is_up() { flags.push("UP"); }
+        if interface.is_running() { flags.push("RUNNING"); }
+        if interface.is_loopback() { flags.push("LOOPBACK"); }
+        if interface.is_physical() { flags.push("PHYSICAL"); }
+        if interface.is_multicast() { flags.push("MULTICAST"); }
+        if interface.is_broadcast() { flags.push("BROADCAST"); }
+        if interface.is_point_to_point() { flags.push("P2P"); }
+        if interface.is_tun() { flags.push("TUN"); }
+
+        let global_ipv4 = interface.global_ipv4_addrs();
+        let global_ipv6 = interface.global_ipv6_addrs();
+        InterfaceReport {
+            name: interface.name.clone(),
+            friendly_name: interface.friendly_name.clone(),
+            description: interface.description.clone(),
+            index: interface.index,
+            if_type: interface.if_type.name(),
+            flags,
+            mac_address: interface.mac_addr.map(|mac_addr| mac_addr.to_string()),
+            ipv4: interface.ipv4.iter()
+                .map(|ipv4| Ipv4Report {
+                    address: ipv4.addr(),
+                    prefix_len: ipv4.prefix_len(),
+                    netmask: ipv4.netmask(),
+                    global: global_ipv4.contains(&ipv4.addr()),
+                })
+                .collect(),
+            ipv6: interface.ipv6.iter().zip(&interface.ipv6_scope_ids)
+                .map(|(ipv6, scope_id)| Ipv6Report {
+                    address: ipv6.addr(),
+                    prefix_len: ipv6.prefix_len(),
+                    scope_id: *scope_id,
+                    global: global_ipv6.contains(&ipv6.addr()),
+                })
+                .collect(),
+            gateway: interface.gateway.as_ref().map(|gateway| GatewayReport {
+                mac_address: gateway.mac_addr.to_string(),
+                ipv4: gateway.ipv4.clone(),
+                ipv6: gateway.ipv6.clone(),
+            }),
+            dns_servers: interface.dns_servers.clone(),
+            transmit_speed: interface.transmit_speed,
+            receive_speed: interface.receive_speed,
+            mtu: interface.mtu,
+            default: interface.default,
+        }
+    }
+}
+
+pub fn handle_ip_command(format: IpFormat) {
+    if format != IpFormat::Text {
+        let reports: Vec<InterfaceReport> = netdev::get_interfaces().iter().map(InterfaceReport::from).collect();
+        match format {
+            IpFormat::Table => print_table(&reports),
+            IpFormat::Json => match serde_json::to_string_pretty(&reports) {
+                Ok(json) => println!("{}", json),
+                Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+            },
+            IpFormat::Yaml => match serde_norway::to_string(&reports) {
+                Ok(yaml) => print!("{}", yaml),
+                Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+            },
+            IpFormat::Text => {}
+        }
+        return;
+    }
 
-pub fn handle_ip_command() {
     println!("=== Netzwerk Interface Informationen ===\n");
 
     // Zuerst das Standard-Interface anzeigen
@@ -16,7 +146,7 @@ fn show_default_interface() {
     println!("🔹 Standard Network Interface:");
     match netdev::get_default_interface() {
         Ok(interface) => {
-            print_interface_details(&interface, true);
+            print_interface_details(&InterfaceReport::from(&interface), true);
         }
         Err(e) => {
             println!("❌ Fehler beim Abrufen des Standard-Interfaces: {}", e);
@@ -35,11 +165,11 @@ fn show_all_interfaces() {
 
     for (index, interface) in interfaces.iter().enumerate() {
         println!("\n--- Interface {} ---", index + 1);
-        print_interface_details(interface, false);
+        print_interface_details(&InterfaceReport::from(interface), false);
     }
 }
 
-fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
+fn print_interface_details(interface: &InterfaceReport, show_gateway: bool) {
     println!("  Name: {}", interface.name);
 
     if let Some(friendly_name) = &interface.friendly_name {
@@ -51,25 +181,15 @@ fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
     }
 
     println!("  Index: {}", interface.index);
-    println!("  Typ: {}", interface.if_type.name());
+    println!("  Typ: {}", interface.if_type);
 
     // Status-Flags
-    let mut status_flags = Vec::new();
-    if interface.is_up() { status_flags.push("UP"); }
-    if interface.is_running() { status_flags.push("RUNNING"); }
-    if interface.is_loopback() { status_flags.push("LOOPBACK"); }
-    if interface.is_physical() { status_flags.push("PHYSICAL"); }
-    if interface.is_multicast() { status_flags.push("MULTICAST"); }
-    if interface.is_broadcast() { status_flags.push("BROADCAST"); }
-    if interface.is_point_to_point() { status_flags.push("P2P"); }
-    if interface.is_tun() { status_flags.push("TUN"); }
-
-    if !status_flags.is_empty() {
-        println!("  Status: {}", status_flags.join(", "));
+    if !interface.flags.is_empty() {
+        println!("  Status: {}", interface.flags.join(", "));
     }
 
     // MAC-Adresse
-    if let Some(mac_addr) = interface.mac_addr {
+    if let Some(mac_addr) = &interface.mac_address {
         println!("  MAC-Adresse: {}", mac_addr);
     } else {
         println!("  MAC-Adresse: Nicht verfügbar");
@@ -79,15 +199,14 @@ fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
     if !interface.ipv4.is_empty() {
         println!("  IPv4-Adressen:");
         for ipv4 in &interface.ipv4 {
-            println!("    - {} (Netzmaske: {})", ipv4.addr(), ipv4.netmask());
+            println!("    - {} (Netzmaske: {})", ipv4.address, ipv4.netmask);
         }
 
         // Globale IPv4-Adressen hervorheben
-        if interface.has_global_ipv4() {
-            let global_addrs = interface.global_ipv4_addrs();
+        if interface.ipv4.iter().any(|ipv4| ipv4.global) {
             println!("  🌐 Globale IPv4-Adressen:");
-            for ip in global_addrs {
-                println!("    - {}", ip);
+            for ipv4 in interface.ipv4.iter().filter(|ipv4| ipv4.global) {
+                println!("    - {}", ipv4.address);
             }
         }
     } else {
@@ -97,16 +216,15 @@ fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
     // IPv6-Adressen
     if !interface.ipv6.is_empty() {
         println!("  IPv6-Adressen:");
-        for (ipv6, scope_id) in interface.ipv6.iter().zip(&interface.ipv6_scope_ids) {
-            println!("    - {} (Scope ID: {})", ipv6.addr(), scope_id);
+        for ipv6 in &interface.ipv6 {
+            println!("    - {} (Scope ID: {})", ipv6.address, ipv6.scope_id);
         }
 
         // Globale IPv6-Adressen hervorheben
-        if interface.has_global_ipv6() {
-            let global_addrs = interface.global_ipv6_addrs();
+        if interface.ipv6.iter().any(|ipv6| ipv6.global) {
             println!("  🌐 Globale IPv6-Adressen:");
-            for ip in global_addrs {
-                println!("    - {}", ip);
+            for ipv6 in interface.ipv6.iter().filter(|ipv6| ipv6.global) {
+                println!("    - {}", ipv6.address);
             }
         }
     } else {
@@ -117,7 +235,7 @@ fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
     if show_gateway {
         if let Some(gateway) = &interface.gateway {
             println!("  🚪 Standard-Gateway:");
-            println!("    MAC-Adresse: {}", gateway.mac_addr);
+            println!("    MAC-Adresse: {}", gateway.mac_address);
             if !gateway.ipv4.is_empty() {
                 println!("    IPv4: {:?}", gateway.ipv4);
             }
@@ -156,4 +274,54 @@ fn print_interface_details(interface: &netdev::Interface, show_gateway: bool) {
     if interface.default {
         println!("  ⭐ Dies ist das Standard-Interface");
     }
-}
\ No newline at end of file
+}
+
+/// Kompakte Übersicht: eine Zeile pro Interface, Adressen mit Präfixlänge
+fn print_table(reports: &[InterfaceReport]) {
+    let rows: Vec<[String; 7]> = reports.iter()
+        .map(|report| {
+            let join = |values: Vec<String>| if values.is_empty() { "-".to_string() } else { values.join(", ") };
+            let status = if report.flags.contains(&"UP") { "UP" } else { "DOWN" };
+            let gateway = report.gateway.as_ref()
+                .map(|gateway| {
+                    let addresses: Vec<String> = gateway.ipv4.iter().map(ToString::to_string)
+                        .chain(gateway.ipv6.iter().map(ToString::to_string))
+                        .collect();
+                    join(addresses)
+                })
+                .unwrap_or_else(|| "-".to_string());
+            [
+                report.name.clone(),
+                status.to_string(),
+                report.mac_address.clone().unwrap_or_else(|| "-".to_string()),
+                join(report.ipv4.iter().map(|ipv4| format!("{}/{}", ipv4.address, ipv4.prefix_len)).collect()),
+                join(report.ipv6.iter().map(|ipv6| format!("{}/{}", ipv6.address, ipv6.prefix_len)).collect()),
+                report.mtu.map(|mtu| mtu.to_string()).unwrap_or_else(|| "-".to_string()),
+                gateway,
+            ]
+        })
+        .collect();
+
+    let titles = ["Interface", "Status", "MAC", "IPv4", "IPv6", "MTU", "Gateway"];
+    let widths: Vec<usize> = titles.iter().enumerate()
+        .map(|(column, title)| {
+            rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0).max(title.chars().count())
+        })
+        .collect();
+    let line = |cells: &[&str]| {
+        cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("   {}", line(&titles));
+    for (report, row) in reports.iter().zip(&rows) {
+        // Standard-Interface wie in der Textausgabe mit Stern markieren
+        let marker = if report.default { "⭐ " } else { "   " };
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        println!("{}{}", marker, line(&cells));
+    }
+}
@@ -91,7 +91,7 @@ fn get_common_log_paths() -> Vec<String> {
     ];
 
     // Windows Log-Pfade (als Referenz, WER-Dateien sind schwieriger zu lesen)
-    let windows_logs = vec![
+    let windows_logs = [
         "C:\\Windows\\System32\\winevt\\Logs\\System.evtx",
         "C:\\Windows\\System32\\winevt\\Logs\\Application.evtx",
         "C:\\Windows\\System32\\winevt\\Logs\\Security.evtx",
@@ -224,11 +224,7 @@ fn read_log_file(file_path: &str, lines: usize) -> Result<String, Box<dyn std::e
     }
 
     // Nimm die letzten N Zeilen
-    let start_index = if total_lines > lines {
-        total_lines - lines
-    } else {
-        0
-    };
+    let start_index = total_lines.saturating_sub(lines);
 
     let selected_lines = &log_lines[start_index..];
     let result = selected_lines.join("\n");
@@ -1,6 +1,9 @@
 // src/commands/mod.rs
+pub mod dns;
 pub mod ip;
 pub mod ping;
 pub mod logs;
+pub mod mtu;
 pub mod scan;
-pub mod test;
\ No newline at end of file
+pub mod test;
+pub mod trace;
\ No newline at end of file
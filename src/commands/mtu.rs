@@ -0,0 +1,268 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+use crate::commands::ping::resolve_target;
+use crate::icmp::IcmpSocket;
+
+/// Kleinste MTU, die jeder IPv4-Host verarbeiten muss (RFC 791)
+const MIN_MTU_V4: u32 = 68;
+/// Kleinste MTU für IPv6 (RFC 8200)
+const MIN_MTU_V6: u32 = 1280;
+/// Größtes IP-Paket
+const MAX_MTU: u32 = 65535;
+
+/// Einstellungen für `mtu`
+pub struct MtuOptions {
+    /// Obergrenze der Suche statt der Interface-MTU
+    pub max: Option<u32>,
+    /// Wartezeit auf eine Antwort
+    pub timeout: Duration,
+    /// Versuche je Paketgröße, bevor sie als zu groß gilt
+    pub attempts: u32,
+}
+
+/// Ergebnis einer Paketgröße
+enum ProbeResult {
+    Passed,
+    /// Lokal oder von einem Router abgelehnt ("Fragmentation Needed"),
+    /// ggf. mit der gemeldeten MTU
+    TooBig(Option<u32>),
+    /// Keine Antwort: verworfen oder verloren
+    NoReply,
+}
+
+pub async fn handle_mtu_command(target: &str, options: MtuOptions) {
+    let ip = match resolve_target(target).await {
+        Ok(ip) => ip,
+        Err(e) => {
+            println!("❌ Ziel '{}' konnte nicht aufgelöst werden: {}", target, e);
+            std::process::exit(2);
+        }
+    };
+
+    let socket = match IcmpSocket::new(ip).and_then(|socket| socket.set_dont_fragment().map(|_| socket)) {
+        Ok(socket) => socket,
+        Err(e) => {
+            println!("❌ ICMP-Socket konnte nicht geöffnet werden: {}", e);
+            println!("💡 Tipp: Root-Rechte (CAP_NET_RAW) oder net.ipv4.ping_group_range freigeben.");
+            std::process::exit(2);
+        }
+    };
+
+    // Loopback meldet 65536, mehr als ein IP-Paket fassen kann
+    let interface = outgoing_interface(ip).map(|(name, mtu)| (name, mtu.map(|mtu| mtu.min(MAX_MTU))));
+    let local_mtu = interface.as_ref().and_then(|(_, mtu)| *mtu);
+    let (min_mtu, overhead) = if ip.is_ipv4() { (MIN_MTU_V4, 28) } else { (MIN_MTU_V6, 48) };
+    let upper = options.max.or(local_mtu).unwrap_or(1500).clamp(min_mtu, MAX_MTU);
+
+    let name = if target == ip.to_string() { ip.to_string() } else { format!("{} ({})", target, ip) };
+    match &interface {
+        Some((interface, Some(mtu))) => {
+            println!("📏 Ermittle Pfad-MTU zu {} über {} (Interface-MTU {} Bytes)", name, interface, mtu);
+        }
+        _ => println!("📏 Ermittle Pfad-MTU zu {}", name),
+    }
+    println!("   Suche zwischen {} und {} Bytes mit gesetztem Don't-Fragment-Bit\n", min_mtu, upper);
+
+    let mut prober = Prober { socket: &socket, overhead, options: &options, sequence: 0, router_reported: false };
+
+    // Zuerst prüfen, ob das Ziel überhaupt antwortet
+    if !matches!(prober.probe(min_mtu).await, ProbeResult::Passed) {
+        println!("\n❌ {} antwortet nicht auf ICMP Echo – Pfad-MTU nicht ermittelbar.", ip);
+        std::process::exit(1);
+    }
+
+    let mut search = MtuSearch::new(min_mtu, upper);
+    while let Some(size) = search.next_size() {
+        let result = prober.probe(size).await;
+        search.record(size, &result);
+    }
+
+    let path_mtu = search.good;
+    println!("\n📊 Pfad-MTU: {} Bytes (größte ICMP-Nutzlast ohne Fragmentierung: {} Bytes)", path_mtu, path_mtu - overhead);
+    match interface {
+        // Die Obergrenze ging durch: mehr lässt sich über den Pfad nicht sagen
+        Some((interface, Some(mtu))) if path_mtu == upper && path_mtu < mtu => {
+            println!("ℹ️  Suche durch --max begrenzt; MTU von {}: {} Bytes", interface, mtu);
+        }
+        Some((interface, Some(mtu))) if path_mtu < mtu => {
+            let mss = path_mtu - if ip.is_ipv4() { 40 } else { 60 };
+            println!("⚠️  Abweichung: Pfad-MTU ist {} Bytes kleiner als die MTU von {} ({} Bytes)", mtu - path_mtu, interface, mtu);
+            println!("💡 Tipp: Typisch für VPN-, PPPoE- oder Tunnel-Strecken. Interface-MTU auf {} setzen oder TCP-MSS auf {} begrenzen (MSS-Clamping).",
+                     path_mtu, mss);
+            if !prober.router_reported {
+                println!("⚠️  Keine \"Fragmentation Needed\"-Meldung erhalten: große Pakete werden stillschweigend verworfen (PMTU-Blackhole).");
+            }
+        }
+        Some((interface, Some(mtu))) => {
+            println!("✅ Pfad-MTU entspricht der MTU von {} ({} Bytes)", interface, mtu);
+        }
+        _ => println!("ℹ️  MTU des ausgehenden Interfaces unbekannt, kein Vergleich möglich"),
+    }
+}
+
+/// Binäre Suche nach der größten Paketgröße, die den Pfad passiert
+struct MtuSearch {
+    /// Größte durchgelassene Größe
+    good: u32,
+    /// Kleinste Größe, die nicht durchging
+    bad: u32,
+    next: Option<u32>,
+}
+
+impl MtuSearch {
+    /// `good` muss bereits durchgegangen sein. Häufigster Fall: die volle
+    /// Größe geht durch, daher wird `upper` zuerst geprüft.
+    fn new(good: u32, upper: u32) -> Self {
+        Self { good, bad: upper + 1, next: Some(upper) }
+    }
+
+    fn next_size(&mut self) -> Option<u32> {
+        self.next.take()
+    }
+
+    fn record(&mut self, size: u32, result: &ProbeResult) {
+        match result {
+            ProbeResult::Passed => self.good = self.good.max(size),
+            ProbeResult::TooBig(reported) => {
+                // Größer als die gemeldete MTU passt nicht durch diesen Router;
+                // die Meldung selbst als Nächstes prüfen. Eine veraltete oder
+                // falsche Meldung unterhalb bereits durchgelassener Größen
+                // darf die Suche nicht unter `good` drücken.
+                self.bad = reported.map_or(size, |mtu| size.min(mtu + 1)).max(self.good + 1);
+                self.next = reported.filter(|mtu| *mtu > self.good && *mtu < self.bad);
+            }
+            ProbeResult::NoReply => self.bad = size.max(self.good + 1),
+        }
+        if self.next.is_none() && self.bad - self.good > 1 {
+            self.next = Some(self.good + (self.bad - self.good) / 2);
+        }
+    }
+}
+
+struct Prober<'a> {
+    socket: &'a IcmpSocket,
+    /// IP- und ICMP-Header, die zur Nutzlast hinzukommen
+    overhead: u32,
+    options: &'a MtuOptions,
+    sequence: u16,
+    /// Mindestens ein Router hat "Fragmentation Needed" gemeldet
+    router_reported: bool,
+}
+
+impl Prober<'_> {
+    /// Prüft eine Paketgröße (inklusive IP-Header) und gibt eine Zeile aus
+    async fn probe(&mut self, size: u32) -> ProbeResult {
+        let payload = vec![0x53u8; (size - self.overhead) as usize];
+        let mut result = ProbeResult::NoReply;
+        for _ in 0..self.options.attempts.max(1) {
+            self.sequence = self.sequence.wrapping_add(1);
+            match self.socket.echo(self.sequence, &payload, self.options.timeout).await {
+                Ok(Some(_)) => {
+                    result = ProbeResult::Passed;
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) if e.raw_os_error() == Some(libc::EMSGSIZE) => {
+                    let reported = self.socket.path_mtu().filter(|mtu| *mtu < size);
+                    self.router_reported |= reported.is_some();
+                    result = ProbeResult::TooBig(reported);
+                    break;
+                }
+                // Sonstige ICMP-Fehler (z.B. Host Unreachable) wie Verlust behandeln
+                Err(_) => {}
+            }
+        }
+
+        match &result {
+            ProbeResult::Passed => println!("   {:>5} Bytes  ✅ durchgelassen", size),
+            ProbeResult::TooBig(Some(mtu)) => println!("   {:>5} Bytes  ❌ zu groß (Router meldet MTU {})", size, mtu),
+            ProbeResult::TooBig(None) => println!("   {:>5} Bytes  ❌ zu groß", size),
+            ProbeResult::NoReply => println!("   {:>5} Bytes  ❌ keine Antwort", size),
+        }
+        result
+    }
+}
+
+/// Name und MTU des Interfaces, über das das Ziel erreicht wird
+fn outgoing_interface(target: IpAddr) -> Option<(String, Option<u32>)> {
+    let local = local_address(target).ok()?;
+    netdev::get_interfaces().into_iter()
+        .find(|interface| match local {
+            IpAddr::V4(ip) => interface.ipv4.iter().any(|net| net.addr() == ip),
+            IpAddr::V6(ip) => interface.ipv6.iter().any(|net| net.addr() == ip),
+        })
+        .map(|interface| (interface.name, interface.mtu))
+}
+
+/// Quelladresse, die der Kernel für das Ziel wählen würde (ohne Pakete zu senden)
+fn local_address(target: IpAddr) -> io::Result<IpAddr> {
+    let bind: SocketAddr = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().expect("gültige Adresse");
+    let socket = UdpSocket::bind(bind)?;
+    socket.connect(SocketAddr::new(target, 9))?;
+    Ok(socket.local_addr()?.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Führt die Suche gegen einen simulierten Pfad aus und liefert die
+    /// Pfad-MTU sowie die geprüften Größen
+    fn search(min: u32, upper: u32, path: impl Fn(u32) -> ProbeResult) -> (u32, Vec<u32>) {
+        let mut search = MtuSearch::new(min, upper);
+        let mut probed = Vec::new();
+        while let Some(size) = search.next_size() {
+            assert!(probed.len() < 32, "Suche endet nicht: {:?}", probed);
+            probed.push(size);
+            search.record(size, &path(size));
+        }
+        (search.good, probed)
+    }
+
+    /// Pfad mit fester MTU; größere Pakete werden mit `report` abgelehnt
+    fn path(mtu: u32, report: Option<u32>) -> impl Fn(u32) -> ProbeResult {
+        move |size| match (size <= mtu, report) {
+            (true, _) => ProbeResult::Passed,
+            (false, Some(reported)) => ProbeResult::TooBig(Some(reported)),
+            (false, None) => ProbeResult::NoReply,
+        }
+    }
+
+    #[test]
+    fn full_size_needs_one_probe() {
+        assert_eq!(search(68, 1500, path(1500, None)), (1500, vec![1500]));
+    }
+
+    #[test]
+    fn reported_mtu_is_tried_next() {
+        assert_eq!(search(68, 1500, path(1420, Some(1420))), (1420, vec![1500, 1420]));
+        assert_eq!(search(1280, 1500, path(1280, Some(1280))), (1280, vec![1500]));
+    }
+
+    #[test]
+    fn blackhole_is_found_by_bisection() {
+        for mtu in [68, 69, 576, 1280, 1399, 1400, 1499] {
+            assert_eq!(search(68, 1500, path(mtu, None)).0, mtu);
+        }
+        assert_eq!(search(68, 1500, path(1400, Some(1200))).0, 1200);
+    }
+
+    #[test]
+    fn stale_report_below_passed_size_does_not_underflow() {
+        // 784 ging bereits durch, danach meldet ein Router eine kleinere MTU:
+        // die Suche endet bei der größten bestätigten Größe
+        let stale = |size| match size {
+            1500 => ProbeResult::NoReply,
+            ..=1000 => ProbeResult::Passed,
+            _ => ProbeResult::TooBig(Some(576)),
+        };
+        assert_eq!(search(68, 1500, stale), (784, vec![1500, 784, 1142]));
+
+        let mut search = MtuSearch::new(1400, 1500);
+        search.record(1500, &ProbeResult::TooBig(Some(68)));
+        assert_eq!((search.good, search.bad), (1400, 1401));
+        assert_eq!(search.next_size(), None);
+    }
+}
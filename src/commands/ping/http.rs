@@ -0,0 +1,167 @@
+use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
+use reqwest::Url;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_openssl::SslStream;
+
+use super::probe::{timeout_reason, HttpPhases, Outcome, Reply};
+
+/// Mehr wird für die Statuszeile nicht gelesen
+const MAX_STATUS_LINE: usize = 1024;
+
+/// Ziel einer HTTP-Messung. DNS-Auflösung, Verbindungsaufbau und TLS werden
+/// bei jeder Messung neu durchlaufen, damit alle Phasen messbar sind.
+pub struct HttpTarget {
+    url: Url,
+    /// Hostname für DNS und SNI; fehlt bei IP-Adressen in der URL
+    domain: Option<String>,
+    ip: Option<IpAddr>,
+    port: u16,
+    https: bool,
+}
+
+impl HttpTarget {
+    /// Ohne Schema wird `http://` angenommen
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = if input.contains("://") { input.to_string() } else { format!("http://{}", input) };
+        let url = Url::parse(&input).map_err(|e| format!("ungültige URL '{}': {}", input, e))?;
+        let https = match url.scheme() {
+            "http" => false,
+            "https" => true,
+            other => return Err(format!("nicht unterstütztes Schema '{}' (nur http und https)", other)),
+        };
+        let host = url.host_str()
+            .filter(|host| !host.is_empty())
+            .ok_or_else(|| format!("URL '{}' enthält keinen Host", input))?;
+        // IPv6-Adressen stehen in der URL in eckigen Klammern
+        let (domain, ip) = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            Ok(ip) => (None, Some(ip)),
+            Err(_) => (Some(host.to_string()), None),
+        };
+        let port = url.port_or_known_default().unwrap_or(if https { 443 } else { 80 });
+        Ok(Self { url, domain, ip, port, https })
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    pub async fn probe(&self, timeout: Duration) -> Outcome {
+        match tokio::time::timeout(timeout, self.measure()).await {
+            Ok(Ok(reply)) => Outcome::Reply(reply),
+            Ok(Err(reason)) => Outcome::Lost(reason),
+            Err(_) => Outcome::Lost(timeout_reason(timeout)),
+        }
+    }
+
+    async fn measure(&self) -> Result<Reply, String> {
+        let (ip, dns) = match (&self.domain, self.ip) {
+            (_, Some(ip)) => (ip, None),
+            (Some(domain), None) => {
+                let started = Instant::now();
+                let ip = super::resolve_target(domain).await
+                    .map_err(|e| format!("DNS-Auflösung fehlgeschlagen: {}", e))?;
+                (ip, Some(started.elapsed()))
+            }
+            (None, None) => return Err("kein Host".to_string()),
+        };
+
+        let started = Instant::now();
+        let tcp = TcpStream::connect(SocketAddr::new(ip, self.port)).await
+            .map_err(|e| format!("Verbindung fehlgeschlagen: {}", e))?;
+        let connect = started.elapsed();
+
+        let request = self.request();
+        let (tls, ttfb, status) = if self.https {
+            let started = Instant::now();
+            let mut stream = self.tls_handshake(tcp).await?;
+            let tls = started.elapsed();
+            let (ttfb, status) = exchange(&mut stream, &request).await?;
+            (Some(tls), ttfb, status)
+        } else {
+            let mut tcp = tcp;
+            let (ttfb, status) = exchange(&mut tcp, &request).await?;
+            (None, ttfb, status)
+        };
+
+        let rtt = dns.unwrap_or_default() + connect + tls.unwrap_or_default() + ttfb;
+        Ok(Reply {
+            rtt,
+            ip,
+            ttl: None,
+            size: None,
+            status: Some(status),
+            phases: Some(HttpPhases {
+                dns_ms: dns.map(millis),
+                connect_ms: millis(connect),
+                tls_ms: tls.map(millis),
+                ttfb_ms: millis(ttfb),
+            }),
+        })
+    }
+
+    /// TLS ohne Zertifikatsprüfung: gemessen wird die Laufzeit, nicht die
+    /// Gültigkeit (dafür gibt es `scan --tls`)
+    async fn tls_handshake(&self, tcp: TcpStream) -> Result<SslStream<TcpStream>, String> {
+        let mut builder = SslConnector::builder(SslMethod::tls_client()).map_err(|e| e.to_string())?;
+        builder.set_verify(SslVerifyMode::NONE);
+        let connector = builder.build();
+        let mut config = connector.configure().map_err(|e| e.to_string())?;
+        config.set_verify_hostname(false);
+        config.set_use_server_name_indication(self.domain.is_some());
+        let ssl = config.into_ssl(self.domain.as_deref().unwrap_or("localhost")).map_err(|e| e.to_string())?;
+
+        let mut stream = SslStream::new(ssl, tcp).map_err(|e| e.to_string())?;
+        Pin::new(&mut stream).connect().await.map_err(|e| format!("TLS-Handshake fehlgeschlagen: {}", e))?;
+        Ok(stream)
+    }
+
+    fn request(&self) -> Vec<u8> {
+        let path = match self.url.query() {
+            Some(query) => format!("{}?{}", self.url.path(), query),
+            None => self.url.path().to_string(),
+        };
+        let host = match self.url.port() {
+            Some(port) => format!("{}:{}", self.url.host_str().unwrap_or_default(), port),
+            None => self.url.host_str().unwrap_or_default().to_string(),
+        };
+        format!("GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: shc-tool\r\nAccept: */*\r\nConnection: close\r\n\r\n", path, host)
+            .into_bytes()
+    }
+}
+
+/// Sendet die Anfrage und misst die Zeit bis zum ersten Byte der Antwort;
+/// danach wird nur noch die Statuszeile gelesen
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, request: &[u8]) -> Result<(Duration, u16), String> {
+    let started = Instant::now();
+    stream.write_all(request).await.map_err(|e| format!("Senden fehlgeschlagen: {}", e))?;
+
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 512];
+    let mut ttfb = None;
+    while !head.contains(&b'\n') && head.len() < MAX_STATUS_LINE {
+        let length = stream.read(&mut buffer).await.map_err(|e| format!("Lesen fehlgeschlagen: {}", e))?;
+        if length == 0 {
+            break;
+        }
+        ttfb.get_or_insert_with(|| started.elapsed());
+        head.extend_from_slice(&buffer[..length]);
+    }
+
+    let ttfb = ttfb.ok_or_else(|| "Verbindung ohne Antwort geschlossen".to_string())?;
+    let status = String::from_utf8_lossy(&head)
+        .lines()
+        .next()
+        .filter(|line| line.starts_with("HTTP/"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "keine gültige HTTP-Antwort".to_string())?;
+    Ok((ttfb, status))
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
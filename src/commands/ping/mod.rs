@@ -0,0 +1,505 @@
+mod http;
+mod multi;
+mod probe;
+mod watch;
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use crate::icmp::IcmpSocket;
+use crate::stats::{RttSummary, Samples, Summary};
+use http::HttpTarget;
+use multi::{collect_targets, ping_multiple};
+pub use multi::TargetList;
+use probe::{HttpPhases, Outcome, Probe};
+pub use watch::format_duration;
+use watch::{print_outages, OutageRecord, OutageTracker, Timeline};
+
+/// Größte Nutzlast, die in ein IPv4-Paket passt (65535 - 20 IP - 8 ICMP)
+const MAX_PAYLOAD: usize = 65507;
+/// Höchstens so viele Messungen listet der JSON-Bericht einzeln, damit der
+/// Dauerbetrieb nicht unbegrenzt Speicher belegt
+const MAX_REPLY_RECORDS: usize = 10_000;
+
+/// Einstellungen für `ping`
+#[derive(Clone)]
+pub struct PingOptions {
+    /// Messmethode
+    pub mode: PingMode,
+    /// Anzahl der Messungen; 0 misst bis Strg+C
+    pub count: u32,
+    /// Abstand zwischen zwei Messungen
+    pub interval: Duration,
+    /// Größe der Nutzdaten in Bytes (ohne ICMP-Header)
+    pub size: usize,
+    /// TTL bzw. Hop Limit der ausgehenden Pakete (nur ICMP)
+    pub ttl: Option<u8>,
+    /// Wartezeit auf eine Antwort
+    pub timeout: Duration,
+    /// Ausgabeformat
+    pub format: OutputFormat,
+    /// Ab so vielen Verlusten in Folge gilt die Verbindung als ausgefallen
+    pub outage_threshold: u32,
+    /// CSV-Datei, in die jede Messung sofort geschrieben wird
+    pub timeline: Option<String>,
+}
+
+/// Messmethode von `ping`
+#[derive(Clone, Copy, PartialEq)]
+pub enum PingMode {
+    /// ICMP Echo
+    Icmp,
+    /// TCP-Handshake auf dem angegebenen Port
+    Tcp(u16),
+    /// UDP-Anfrage/Antwort auf dem angegebenen Port
+    Udp(u16),
+    /// HTTP(S)-Anfrage; das Ziel ist eine URL
+    Http,
+}
+
+impl PingMode {
+    fn name(&self) -> &'static str {
+        match self {
+            PingMode::Icmp => "icmp",
+            PingMode::Tcp(_) => "tcp",
+            PingMode::Udp(_) => "udp",
+            PingMode::Http => "http",
+        }
+    }
+
+    fn port(&self) -> Option<u16> {
+        match self {
+            PingMode::Tcp(port) | PingMode::Udp(port) => Some(*port),
+            _ => None,
+        }
+    }
+}
+
+/// Ausgabeformat von `ping`
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Lesbare Ausgabe mit einer Zeile pro Antwort
+    Text,
+    /// Ergebnis und Statistik als JSON (z.B. für Skripte)
+    Json,
+}
+
+/// Ergebnis eines Ping-Laufs für `--format json`
+#[derive(Serialize)]
+struct PingReport {
+    target: String,
+    mode: &'static str,
+    /// Bei HTTP wird die Adresse pro Messung neu aufgelöst
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload_size: Option<usize>,
+    /// Die jüngsten Messungen, höchstens `MAX_REPLY_RECORDS`
+    replies: VecDeque<ReplyRecord>,
+    /// Anzahl älterer Messungen, die nicht mehr einzeln enthalten sind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replies_omitted: Option<u64>,
+    statistics: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phases: Option<PhaseStatistics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outages: Option<Vec<OutageRecord>>,
+}
+
+/// Eine Messung; ohne Antwort sind Laufzeit und TTL leer
+#[derive(Serialize)]
+struct ReplyRecord {
+    sequence: u16,
+    rtt_ms: Option<f64>,
+    ttl: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phases: Option<HttpPhases>,
+    /// Grund für eine fehlende Antwort
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Laufzeiten der einzelnen HTTP-Phasen
+#[derive(Default)]
+struct PhaseSamples {
+    dns: Samples,
+    connect: Samples,
+    tls: Samples,
+    ttfb: Samples,
+}
+
+/// Auswertung der HTTP-Phasen; DNS und TLS fehlen, wenn sie nie anfielen
+#[derive(Serialize)]
+struct PhaseStatistics {
+    dns: Option<RttSummary>,
+    connect: Option<RttSummary>,
+    tls: Option<RttSummary>,
+    ttfb: Option<RttSummary>,
+}
+
+impl PhaseSamples {
+    fn add(&mut self, phases: &HttpPhases) {
+        let duration = |ms: f64| Duration::from_micros((ms * 1000.0).round() as u64);
+        if let Some(dns) = phases.dns_ms {
+            self.dns.add_reply(duration(dns));
+        }
+        self.connect.add_reply(duration(phases.connect_ms));
+        if let Some(tls) = phases.tls_ms {
+            self.tls.add_reply(duration(tls));
+        }
+        self.ttfb.add_reply(duration(phases.ttfb_ms));
+    }
+
+    fn statistics(&self) -> PhaseStatistics {
+        PhaseStatistics {
+            dns: self.dns.summary().rtt,
+            connect: self.connect.summary().rtt,
+            tls: self.tls.summary().rtt,
+            ttfb: self.ttfb.summary().rtt,
+        }
+    }
+}
+
+pub async fn handle_ping_command(list: TargetList, options: PingOptions) {
+    if options.size > MAX_PAYLOAD {
+        println!("❌ --size darf höchstens {} Bytes betragen.", MAX_PAYLOAD);
+        std::process::exit(2);
+    }
+
+    let targets = match collect_targets(list) {
+        Ok(targets) => targets,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    match targets.as_slice() {
+        [] => {
+            println!("❌ Keine Ziele angegeben.");
+            std::process::exit(2);
+        }
+        [target] => ping_single(&target.address, options).await,
+        _ => {
+            if options.timeline.is_some() {
+                println!("❌ --csv ist nur mit einem einzelnen Ziel möglich.");
+                std::process::exit(2);
+            }
+            ping_multiple(targets, options).await;
+        }
+    }
+}
+
+/// Ein Ziel mit Antwortzeilen, Statistik und Ausfall-Erkennung
+async fn ping_single(target: &str, options: PingOptions) {
+    let text = options.format == OutputFormat::Text;
+    let watch = options.count == 0;
+    let mut timeline = match options.timeline.as_deref().map(Timeline::create).transpose() {
+        Ok(timeline) => timeline,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    let (probe, ip) = match open_probe(target, &options, text).await {
+        Ok(opened) => opened,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    if text && watch {
+        println!("👀 Dauerbetrieb: Ausfall ab {} Verlusten in Folge, beenden mit Strg+C", options.outage_threshold);
+    }
+    let destination = destination(target, options.mode, ip);
+
+    let started_at = chrono::Local::now();
+    let mut samples = Samples::default();
+    let mut phase_samples = PhaseSamples::default();
+    let mut replies = VecDeque::new();
+    let mut replies_omitted: u64 = 0;
+    let mut outages = OutageTracker::new(options.outage_threshold);
+
+    let run = async {
+        let mut index: u64 = 0;
+        while watch || index < u64::from(options.count) {
+            let sequence = sequence_number(index);
+            let started = Instant::now();
+            let outcome = probe.send(sequence, options.timeout).await;
+            let now = chrono::Local::now();
+            // Im Dauerbetrieb braucht jede Zeile eine Uhrzeit
+            let prefix = if watch { format!("[{}] ", now.format("%H:%M:%S")) } else { String::new() };
+
+            let record = match outcome {
+                Outcome::Reply(reply) => {
+                    samples.add_reply(reply.rtt);
+                    if let Some(phases) = &reply.phases {
+                        phase_samples.add(phases);
+                    }
+                    let rtt_ms = reply.rtt.as_micros() as f64 / 1000.0;
+                    if text {
+                        println!("{}{}", prefix, format_reply(options.mode, sequence, rtt_ms, &reply));
+                    }
+                    if let Some(outage) = outages.reply(now) {
+                        if text {
+                            println!("{}🟢 Verbindung wieder da nach {} ({} Verluste)",
+                                     prefix, format_duration(outage.duration(now)), outage.lost);
+                        }
+                    }
+                    ReplyRecord {
+                        sequence,
+                        rtt_ms: Some(rtt_ms),
+                        ttl: reply.ttl,
+                        status: reply.status,
+                        phases: reply.phases,
+                        error: None,
+                    }
+                }
+                Outcome::Lost(reason) => {
+                    samples.add_loss();
+                    if text {
+                        println!("{}❌ Keine Antwort von {}: seq={} ({})", prefix, destination, sequence, reason);
+                    }
+                    if let Some(start) = outages.loss(now) {
+                        if text {
+                            println!("{}🔴 Ausfall seit {} ({} Verluste in Folge)",
+                                     prefix, start.format("%H:%M:%S"), options.outage_threshold);
+                        }
+                    }
+                    ReplyRecord {
+                        sequence,
+                        rtt_ms: None,
+                        ttl: None,
+                        status: None,
+                        phases: None,
+                        error: Some(reason),
+                    }
+                }
+            };
+
+            if let Some(writer) = timeline.as_mut() {
+                if let Err(e) = writer.record(now, sequence, record.rtt_ms, record.ttl, record.status, record.error.as_deref()) {
+                    println!("⚠️  Zeitreihe konnte nicht geschrieben werden, sie wird nicht fortgeführt: {}", e);
+                    timeline = None;
+                }
+            }
+            // Einzelne Messungen braucht nur der JSON-Bericht
+            if !text {
+                if replies.len() == MAX_REPLY_RECORDS {
+                    replies.pop_front();
+                    replies_omitted += 1;
+                }
+                replies.push_back(record);
+            }
+
+            index += 1;
+            if watch || index < u64::from(options.count) {
+                tokio::time::sleep(options.interval.saturating_sub(started.elapsed())).await;
+            }
+        }
+    };
+    // Strg+C beendet die Messung, die Auswertung folgt trotzdem
+    tokio::select! {
+        _ = run => {}
+        _ = tokio::signal::ctrl_c() => {
+            if text {
+                println!("\n⏹️  Abgebrochen");
+            }
+        }
+    }
+
+    let finished_at = chrono::Local::now();
+    let summary = samples.summary();
+    let received = summary.received;
+    let phases = (options.mode == PingMode::Http).then(|| phase_samples.statistics());
+    // Die Ausfall-Tabelle gehört zum Dauerbetrieb, erscheint aber auch sonst bei Ausfällen
+    let show_outages = watch || !outages.outages().is_empty();
+    if text {
+        println!("\n📊 Ping-Statistik für {}:", destination);
+        print_summary(&summary);
+        if let Some(phases) = &phases {
+            print_phases(phases);
+        }
+        if show_outages {
+            println!("🕒 Laufzeit: {}", format_duration(finished_at - started_at));
+            print_outages(&outages, finished_at);
+        }
+        if let Some(path) = &options.timeline {
+            println!("💾 Zeitreihe gespeichert: {}", path);
+        }
+    } else {
+        let report = PingReport {
+            target: target.to_string(),
+            mode: options.mode.name(),
+            ip,
+            port: options.mode.port(),
+            started_at: started_at.to_rfc3339(),
+            payload_size: matches!(options.mode, PingMode::Icmp | PingMode::Udp(_)).then_some(options.size),
+            replies,
+            replies_omitted: (replies_omitted > 0).then_some(replies_omitted),
+            statistics: summary,
+            phases,
+            outages: show_outages.then(|| {
+                outages.outages().iter().map(|outage| outage.record(finished_at)).collect()
+            }),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+
+    if received == 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Öffnet die Messmethode und gibt bei Textausgabe die Kopfzeile aus. Die
+/// Adresse fehlt nur bei HTTP, wo sie pro Messung neu aufgelöst wird.
+async fn open_probe(target: &str, options: &PingOptions, text: bool) -> Result<(Probe, Option<IpAddr>), String> {
+    if options.mode == PingMode::Http {
+        let http = HttpTarget::parse(target)?;
+        if text {
+            println!("🏓 HTTP-Ping {} (Phasen: DNS, Verbindung, TLS, TTFB)", http.url());
+        }
+        return Ok((Probe::Http(http), None));
+    }
+
+    let ip = resolve_target(target).await
+        .map_err(|e| format!("Ziel '{}' konnte nicht aufgelöst werden: {}", target, e))?;
+    let name = if target == ip.to_string() { ip.to_string() } else { format!("{} ({})", target, ip) };
+
+    let probe = match options.mode {
+        PingMode::Tcp(port) => {
+            if text {
+                println!("🏓 TCP-Ping {} Port {} (Dauer des Verbindungsaufbaus)", name, port);
+            }
+            Probe::Tcp(SocketAddr::new(ip, port))
+        }
+        PingMode::Udp(port) => {
+            if text {
+                if port == 53 {
+                    println!("🏓 UDP-Ping {} Port {} (DNS-Anfrage)", name, port);
+                } else {
+                    println!("🏓 UDP-Ping {} Port {} mit {} Bytes Nutzdaten", name, port, options.size);
+                }
+            }
+            Probe::Udp { addr: SocketAddr::new(ip, port), size: options.size }
+        }
+        PingMode::Icmp | PingMode::Http => {
+            let socket = IcmpSocket::new(ip).map_err(|e| format!(
+                "ICMP-Socket konnte nicht geöffnet werden: {}\n💡 Tipp: Root-Rechte (CAP_NET_RAW) oder net.ipv4.ping_group_range freigeben.", e
+            ))?;
+            if let Some(ttl) = options.ttl {
+                socket.set_ttl(ttl).map_err(|e| format!("TTL {} konnte nicht gesetzt werden: {}", ttl, e))?;
+            }
+            if text {
+                let socket_kind = if socket.is_raw() { "Raw-Socket" } else { "ICMP-Datagram-Socket" };
+                println!("🏓 Pinge {} mit {} Bytes Nutzdaten ({})", name, options.size, socket_kind);
+            }
+            let payload = b"SHC-Tool".iter().copied().cycle().take(options.size).collect();
+            Probe::Icmp { socket, payload }
+        }
+    };
+    Ok((probe, Some(ip)))
+}
+
+/// Bezeichnung des Ziels in Antwortzeilen und Statistik
+fn destination(target: &str, mode: PingMode, ip: Option<IpAddr>) -> String {
+    match (mode, ip) {
+        (PingMode::Http, _) | (_, None) => target.to_string(),
+        (PingMode::Icmp, Some(ip)) => ip.to_string(),
+        (_, Some(ip)) => SocketAddr::new(ip, mode.port().unwrap_or_default()).to_string(),
+    }
+}
+
+/// Sequenznummer der Messung `index`; im Dauerbetrieb läuft sie über und
+/// beginnt wieder bei 1
+fn sequence_number(index: u64) -> u16 {
+    (index % u64::from(u16::MAX)) as u16 + 1
+}
+
+/// Antwortzeile der Textausgabe, je nach Messmethode
+fn format_reply(mode: PingMode, sequence: u16, rtt_ms: f64, reply: &probe::Reply) -> String {
+    let size = reply.size.unwrap_or_default();
+    match mode {
+        PingMode::Icmp => {
+            let ttl = reply.ttl.map(|ttl| format!(" ttl={}", ttl)).unwrap_or_default();
+            format!("✅ {} Bytes von {}: seq={}{} Zeit={:.2} ms", size, reply.ip, sequence, ttl, rtt_ms)
+        }
+        PingMode::Tcp(port) => {
+            format!("✅ Verbunden mit {}: seq={} Zeit={:.2} ms", SocketAddr::new(reply.ip, port), sequence, rtt_ms)
+        }
+        PingMode::Udp(port) => {
+            format!("✅ {} Bytes von {}: seq={} Zeit={:.2} ms", size, SocketAddr::new(reply.ip, port), sequence, rtt_ms)
+        }
+        PingMode::Http => {
+            let phases = reply.phases.as_ref().map(describe_phases).unwrap_or_default();
+            format!("✅ HTTP {} von {}: seq={} Zeit={:.2} ms ({})",
+                    reply.status.unwrap_or_default(), reply.ip, sequence, rtt_ms, phases)
+        }
+    }
+}
+
+fn describe_phases(phases: &HttpPhases) -> String {
+    [
+        ("DNS", phases.dns_ms),
+        ("Verbindung", Some(phases.connect_ms)),
+        ("TLS", phases.tls_ms),
+        ("TTFB", Some(phases.ttfb_ms)),
+    ]
+    .iter()
+    .filter_map(|(name, value)| value.map(|ms| format!("{} {:.2}", name, ms)))
+    .collect::<Vec<_>>()
+    .join(" | ")
+}
+
+/// Statistik-Block der Textausgabe
+fn print_summary(summary: &Summary) {
+    println!("📤 Gesendet: {}, 📥 Empfangen: {}, 📉 Verlust: {:.1} %",
+             summary.sent, summary.received, summary.loss_percent);
+    if let Some(rtt) = &summary.rtt {
+        println!("⏱️  RTT min/avg/max/stddev: {:.2}/{:.2}/{:.2}/{:.2} ms",
+                 rtt.min_ms, rtt.avg_ms, rtt.max_ms, rtt.stddev_ms);
+        println!("〰️  Jitter: {:.2} ms", rtt.jitter_ms);
+        println!("📈 Perzentile p50/p95/p99: {:.2}/{:.2}/{:.2} ms", rtt.p50_ms, rtt.p95_ms, rtt.p99_ms);
+    }
+}
+
+/// Mittelwert und p95 je HTTP-Phase
+fn print_phases(phases: &PhaseStatistics) {
+    let parts: Vec<String> = [
+        ("DNS", &phases.dns),
+        ("Verbindung", &phases.connect),
+        ("TLS", &phases.tls),
+        ("TTFB", &phases.ttfb),
+    ]
+    .iter()
+    .filter_map(|(name, rtt)| rtt.as_ref().map(|rtt| format!("{} {:.2}/{:.2}", name, rtt.avg_ms, rtt.p95_ms)))
+    .collect();
+    if !parts.is_empty() {
+        println!("🧩 Phasen avg/p95 (ms): {}", parts.join(" | "));
+    }
+}
+
+/// IP-Adresse direkt übernehmen, Hostnamen per DNS auflösen (IPv4 bevorzugt)
+pub async fn resolve_target(target: &str) -> Result<IpAddr, String> {
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return Ok(ip);
+    }
+
+    let addresses: Vec<IpAddr> = tokio::net::lookup_host((target, 0)).await
+        .map_err(|e| e.to_string())?
+        .map(|addr| addr.ip())
+        .collect();
+    addresses.iter().find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.first())
+        .copied()
+        .ok_or_else(|| "keine Adresse gefunden".to_string())
+}
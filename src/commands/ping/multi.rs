@@ -0,0 +1,273 @@
+use serde::Serialize;
+use std::net::IpAddr;
+use std::time::Instant;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use super::probe::Outcome;
+use super::{destination, format_reply, open_probe, sequence_number, OutputFormat, PingMode, PingOptions};
+use crate::stats::{Samples, Summary};
+
+/// Herkunft der Ziele von `ping`
+pub struct TargetList {
+    /// Auf der Kommandozeile angegebene Ziele
+    pub targets: Vec<String>,
+    /// Datei mit einem Ziel pro Zeile
+    pub from_file: Option<String>,
+    /// Gateway des Standard-Interfaces anpingen
+    pub gateway: bool,
+    /// DNS-Server des Standard-Interfaces anpingen
+    pub dns: bool,
+}
+
+/// Ein Ziel, ggf. mit seiner Rolle aus dem Standard-Interface
+pub struct PingTarget {
+    pub address: String,
+    pub role: Option<&'static str>,
+}
+
+/// Ergebnis eines Ziels im Vergleich
+#[derive(Serialize)]
+struct TargetResult {
+    target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip: Option<IpAddr>,
+    statistics: Summary,
+    /// Ziel konnte nicht aufgelöst oder geöffnet werden
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Vergleich mehrerer Ziele für `--format json`
+#[derive(Serialize)]
+struct MultiPingReport {
+    mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    started_at: String,
+    targets: Vec<TargetResult>,
+}
+
+/// Sammelt Gateway, DNS-Server, Kommandozeilen-Ziele und Datei-Ziele in
+/// dieser Reihenfolge; doppelte Einträge werden nur einmal gepingt
+pub fn collect_targets(list: TargetList) -> Result<Vec<PingTarget>, String> {
+    let mut targets: Vec<PingTarget> = Vec::new();
+
+    if list.gateway || list.dns {
+        let interface = netdev::get_default_interface()
+            .map_err(|e| format!("Standard-Interface konnte nicht ermittelt werden: {}", e))?;
+        if list.gateway {
+            let gateway = interface.gateway.as_ref()
+                .and_then(|gateway| {
+                    gateway.ipv4.first().map(|ip| IpAddr::V4(*ip))
+                        .or_else(|| gateway.ipv6.first().map(|ip| IpAddr::V6(*ip)))
+                })
+                .ok_or_else(|| format!("Interface {} hat kein Gateway.", interface.name))?;
+            targets.push(PingTarget { address: gateway.to_string(), role: Some("Gateway") });
+        }
+        if list.dns {
+            if interface.dns_servers.is_empty() {
+                return Err(format!("Interface {} hat keine DNS-Server.", interface.name));
+            }
+            for server in &interface.dns_servers {
+                targets.push(PingTarget { address: server.to_string(), role: Some("DNS") });
+            }
+        }
+    }
+
+    for target in list.targets {
+        targets.push(PingTarget { address: target, role: None });
+    }
+
+    if let Some(path) = &list.from_file {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Zieldatei '{}' konnte nicht gelesen werden: {}", path, e))?;
+        for line in content.lines() {
+            // Kommentare und Leerzeilen überspringen
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                targets.push(PingTarget { address: line.to_string(), role: None });
+            }
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    targets.retain(|target| seen.insert(target.address.clone()));
+    Ok(targets)
+}
+
+/// Pingt alle Ziele gleichzeitig und vergleicht sie anschließend
+pub async fn ping_multiple(targets: Vec<PingTarget>, options: PingOptions) {
+    let text = options.format == OutputFormat::Text;
+    let watch = options.count == 0;
+    if text {
+        let mode = match options.mode {
+            PingMode::Tcp(port) => format!("TCP-Port {}", port),
+            PingMode::Udp(port) => format!("UDP-Port {}", port),
+            PingMode::Icmp | PingMode::Http => "ICMP".to_string(),
+        };
+        println!("🏓 Pinge {} Ziele gleichzeitig ({})", targets.len(), mode);
+        if watch {
+            println!("👀 Dauerbetrieb, beenden mit Strg+C");
+        }
+    }
+
+    let started_at = chrono::Local::now();
+    let (stop, stopped) = watch::channel(false);
+    let mut tasks = JoinSet::new();
+    let count = targets.len();
+    for (index, target) in targets.into_iter().enumerate() {
+        let options = options.clone();
+        let stopped = stopped.clone();
+        tasks.spawn(async move { (index, run_target(target, options, stopped).await) });
+    }
+
+    let mut results: Vec<Option<TargetResult>> = (0..count).map(|_| None).collect();
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut interrupted = false;
+    loop {
+        tokio::select! {
+            finished = tasks.join_next() => match finished {
+                Some(Ok((index, result))) => results[index] = Some(result),
+                Some(Err(_)) => {}
+                None => break,
+            },
+            // Strg+C beendet alle Messungen, die Auswertung folgt trotzdem
+            _ = &mut ctrl_c, if !interrupted => {
+                interrupted = true;
+                let _ = stop.send(true);
+                if text {
+                    println!("\n⏹️  Abgebrochen");
+                }
+            }
+        }
+    }
+
+    let results: Vec<TargetResult> = results.into_iter().flatten().collect();
+    let unreachable = results.iter().any(|result| result.statistics.received == 0);
+    if text {
+        print_comparison(&results);
+    } else {
+        let report = MultiPingReport {
+            mode: options.mode.name(),
+            port: options.mode.port(),
+            started_at: started_at.to_rfc3339(),
+            targets: results,
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+
+    // Für Skripte: mindestens ein Ziel hat gar nicht geantwortet
+    if unreachable {
+        std::process::exit(1);
+    }
+}
+
+async fn run_target(target: PingTarget, options: PingOptions, mut stopped: watch::Receiver<bool>) -> TargetResult {
+    let text = options.format == OutputFormat::Text;
+    let watch = options.count == 0;
+    let mut samples = Samples::default();
+
+    let (probe, ip) = match open_probe(&target.address, &options, false).await {
+        Ok(opened) => opened,
+        // Fehler erscheinen gesammelt unter der Vergleichstabelle
+        Err(e) => {
+            return TargetResult {
+                target: target.address,
+                role: target.role,
+                ip: None,
+                statistics: samples.summary(),
+                error: Some(e),
+            };
+        }
+    };
+    let destination = destination(&target.address, options.mode, ip);
+
+    let run = async {
+        let mut index: u64 = 0;
+        while watch || index < u64::from(options.count) {
+            let sequence = sequence_number(index);
+            let started = Instant::now();
+            let outcome = probe.send(sequence, options.timeout).await;
+            let prefix = if watch { format!("[{}] ", chrono::Local::now().format("%H:%M:%S")) } else { String::new() };
+            match outcome {
+                Outcome::Reply(reply) => {
+                    samples.add_reply(reply.rtt);
+                    if text {
+                        let rtt_ms = reply.rtt.as_micros() as f64 / 1000.0;
+                        println!("{}{}", prefix, format_reply(options.mode, sequence, rtt_ms, &reply));
+                    }
+                }
+                Outcome::Lost(reason) => {
+                    samples.add_loss();
+                    if text {
+                        println!("{}❌ Keine Antwort von {}: seq={} ({})", prefix, destination, sequence, reason);
+                    }
+                }
+            }
+
+            index += 1;
+            if watch || index < u64::from(options.count) {
+                tokio::time::sleep(options.interval.saturating_sub(started.elapsed())).await;
+            }
+        }
+    };
+    tokio::select! {
+        _ = run => {}
+        _ = stopped.wait_for(|stop| *stop) => {}
+    }
+
+    TargetResult {
+        target: target.address,
+        role: target.role,
+        ip,
+        statistics: samples.summary(),
+        error: None,
+    }
+}
+
+/// Vergleichstabelle: Verlust und Laufzeiten je Ziel nebeneinander
+fn print_comparison(results: &[TargetResult]) {
+    let names: Vec<String> = results.iter()
+        .map(|result| match result.ip {
+            Some(ip) if ip.to_string() != result.target => format!("{} ({})", result.target, ip),
+            _ => result.target.clone(),
+        })
+        .collect();
+    let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max(4);
+
+    println!("\n📊 Vergleich ({} Ziele):", results.len());
+    println!("   {:<width$}  {:<7}  {:>5}  {:>5}  {:>7}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}",
+             "Ziel", "Rolle", "Ges.", "Empf.", "Verlust", "min", "avg", "max", "p95", "Jitter", width = width);
+    for (result, name) in results.iter().zip(&names) {
+        let summary = &result.statistics;
+        let icon = if result.error.is_some() || summary.received == 0 {
+            "🔴"
+        } else if summary.received < summary.sent {
+            "🟡"
+        } else {
+            "🟢"
+        };
+        let latency = |value: Option<f64>| value.map(|ms| format!("{:.2}", ms)).unwrap_or_else(|| "-".to_string());
+        let rtt = summary.rtt.as_ref();
+        let loss = if result.error.is_some() { "-".to_string() } else { format!("{:.1}%", summary.loss_percent) };
+        println!("{} {:<width$}  {:<7}  {:>5}  {:>5}  {:>7}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}",
+                 icon, name, result.role.unwrap_or("-"), summary.sent, summary.received, loss,
+                 latency(rtt.map(|rtt| rtt.min_ms)), latency(rtt.map(|rtt| rtt.avg_ms)),
+                 latency(rtt.map(|rtt| rtt.max_ms)), latency(rtt.map(|rtt| rtt.p95_ms)),
+                 latency(rtt.map(|rtt| rtt.jitter_ms)), width = width);
+    }
+    println!("   Laufzeiten in ms");
+
+    for (result, name) in results.iter().zip(&names) {
+        if let Some(error) = &result.error {
+            println!("❌ {}: {}", name, error);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+use tokio::io::Interest;
+use tokio::net::{TcpStream, UdpSocket};
+
+use super::http::HttpTarget;
+use crate::icmp::IcmpSocket;
+
+/// Eine Messmethode von `ping`
+pub enum Probe {
+    /// ICMP Echo Request / Echo Reply
+    Icmp { socket: IcmpSocket, payload: Vec<u8> },
+    /// Dauer des TCP-Handshakes (SYN bis SYN/ACK)
+    Tcp(SocketAddr),
+    /// Anfrage/Antwort per UDP; auf Port 53 als DNS-Anfrage
+    Udp { addr: SocketAddr, size: usize },
+    /// HTTP(S)-Anfrage, aufgeteilt in einzelne Phasen
+    Http(HttpTarget),
+}
+
+/// Ergebnis einer einzelnen Messung
+pub enum Outcome {
+    Reply(Reply),
+    /// Keine gültige Antwort, mit Begründung für die Ausgabe
+    Lost(String),
+}
+
+/// Eine erfolgreiche Messung
+pub struct Reply {
+    /// Gesamtdauer; bei HTTP die Summe aller Phasen
+    pub rtt: Duration,
+    /// Absender der Antwort
+    pub ip: IpAddr,
+    pub ttl: Option<u8>,
+    /// Größe der Antwort in Bytes (ICMP und UDP)
+    pub size: Option<usize>,
+    /// HTTP-Statuscode
+    pub status: Option<u16>,
+    pub phases: Option<HttpPhases>,
+}
+
+/// Dauer der Phasen einer HTTP-Messung in Millisekunden
+#[derive(Clone, Serialize)]
+pub struct HttpPhases {
+    /// Fehlt, wenn die URL eine IP-Adresse enthält
+    pub dns_ms: Option<f64>,
+    pub connect_ms: f64,
+    /// Fehlt bei unverschlüsseltem HTTP
+    pub tls_ms: Option<f64>,
+    /// Zeit vom Absenden der Anfrage bis zum ersten Byte der Antwort
+    pub ttfb_ms: f64,
+}
+
+impl Probe {
+    pub async fn send(&self, sequence: u16, timeout: Duration) -> Outcome {
+        match self {
+            Probe::Icmp { socket, payload } => match socket.echo(sequence, payload, timeout).await {
+                Ok(Some(reply)) => Outcome::Reply(Reply {
+                    rtt: reply.rtt,
+                    ip: socket.target(),
+                    ttl: reply.ttl,
+                    size: Some(reply.size),
+                    status: None,
+                    phases: None,
+                }),
+                Ok(None) => Outcome::Lost(timeout_reason(timeout)),
+                Err(e) => Outcome::Lost(format!("Senden fehlgeschlagen: {}", e)),
+            },
+            Probe::Tcp(addr) => tcp_handshake(*addr, timeout).await,
+            Probe::Udp { addr, size } => udp_exchange(*addr, sequence, *size, timeout).await,
+            Probe::Http(target) => target.probe(timeout).await,
+        }
+    }
+}
+
+pub fn timeout_reason(timeout: Duration) -> String {
+    format!("Timeout nach {} ms", timeout.as_millis())
+}
+
+async fn tcp_handshake(addr: SocketAddr, timeout: Duration) -> Outcome {
+    let started = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(_stream)) => Outcome::Reply(Reply {
+            rtt: started.elapsed(),
+            ip: addr.ip(),
+            ttl: None,
+            size: None,
+            status: None,
+            phases: None,
+        }),
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => Outcome::Lost(format!(
+            "Port {} geschlossen, RST nach {:.2} ms", addr.port(), started.elapsed().as_micros() as f64 / 1000.0
+        )),
+        Ok(Err(e)) => Outcome::Lost(e.to_string()),
+        Err(_) => Outcome::Lost(timeout_reason(timeout)),
+    }
+}
+
+/// Sendet ein Datagramm und wartet auf die erste passende Antwort. Jede
+/// Messung nutzt einen eigenen Socket, damit verspätete Antworten nicht
+/// der nächsten Sequenz zugeordnet werden.
+async fn udp_exchange(addr: SocketAddr, sequence: u16, size: usize, timeout: Duration) -> Outcome {
+    let bind: SocketAddr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().expect("gültige Adresse");
+    let socket = match UdpSocket::bind(bind).await {
+        Ok(socket) => socket,
+        Err(e) => return Outcome::Lost(format!("Socket-Fehler: {}", e)),
+    };
+    if let Err(e) = socket.connect(addr).await {
+        return Outcome::Lost(e.to_string());
+    }
+
+    let dns = addr.port() == 53;
+    let request = if dns {
+        dns_query(sequence)
+    } else {
+        b"SHC-Tool".iter().copied().cycle().take(size).collect()
+    };
+
+    let started = Instant::now();
+    if let Err(e) = socket.send(&request).await {
+        return Outcome::Lost(format!("Senden fehlgeschlagen: {}", e));
+    }
+
+    let mut buffer = vec![0u8; 65535];
+    let fd = socket.as_raw_fd();
+    let receive = async {
+        loop {
+            // ICMP Port Unreachable kommt als Socket-Fehler an, den Tokio nicht
+            // als Lesebereitschaft meldet; deshalb auch auf Fehler warten
+            let length = socket.async_io(Interest::READABLE | Interest::ERROR, || receive(fd, &mut buffer)).await?;
+            // Bei DNS nur die Antwort mit passender Transaktions-ID werten
+            if !dns || (length >= 2 && buffer[..2] == sequence.to_be_bytes()) {
+                return Ok::<usize, std::io::Error>(length);
+            }
+        }
+    };
+    match tokio::time::timeout(timeout, receive).await {
+        Ok(Ok(length)) => Outcome::Reply(Reply {
+            rtt: started.elapsed(),
+            ip: addr.ip(),
+            ttl: None,
+            size: Some(length),
+            status: None,
+            phases: None,
+        }),
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => {
+            Outcome::Lost(format!("Port {} nicht erreichbar (ICMP Port Unreachable)", addr.port()))
+        }
+        Ok(Err(e)) => Outcome::Lost(e.to_string()),
+        Err(_) => Outcome::Lost(timeout_reason(timeout)),
+    }
+}
+
+fn receive(fd: RawFd, buffer: &mut [u8]) -> std::io::Result<usize> {
+    // SAFETY: Zeiger und Länge beschreiben den gültigen Puffer
+    let length = unsafe { libc::recv(fd, buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+    if length < 0 { Err(std::io::Error::last_os_error()) } else { Ok(length as usize) }
+}
+
+/// DNS-Anfrage nach den NS-Einträgen der Root-Zone; die Sequenznummer dient
+/// als Transaktions-ID
+fn dns_query(sequence: u16) -> Vec<u8> {
+    let mut packet = sequence.to_be_bytes().to_vec();
+    packet.extend_from_slice(&[
+        0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x02, 0x00, 0x01,
+    ]);
+    packet
+}
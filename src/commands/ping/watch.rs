@@ -0,0 +1,240 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::csv::escape_csv;
+
+/// Ein Ausfall: mindestens `threshold` Verluste in Folge
+pub struct Outage {
+    /// Zeitpunkt der ersten verlorenen Messung
+    pub start: DateTime<Local>,
+    /// Zeitpunkt der ersten Antwort danach; fehlt, solange der Ausfall andauert
+    pub end: Option<DateTime<Local>>,
+    /// Verlorene Messungen während des Ausfalls
+    pub lost: u32,
+}
+
+/// Ausfall für `--format json`
+#[derive(Serialize)]
+pub struct OutageRecord {
+    started_at: String,
+    ended_at: Option<String>,
+    duration_s: f64,
+    lost: u32,
+}
+
+impl Outage {
+    /// Dauer bis zum Ende bzw. bis `now`, wenn der Ausfall noch andauert
+    pub fn duration(&self, now: DateTime<Local>) -> chrono::Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+
+    pub fn record(&self, now: DateTime<Local>) -> OutageRecord {
+        OutageRecord {
+            started_at: self.start.to_rfc3339(),
+            ended_at: self.end.map(|end| end.to_rfc3339()),
+            duration_s: self.duration(now).num_milliseconds() as f64 / 1000.0,
+            lost: self.lost,
+        }
+    }
+}
+
+/// Erkennt Ausfälle anhand aufeinanderfolgender Verluste
+pub struct OutageTracker {
+    threshold: u32,
+    consecutive: u32,
+    streak_start: Option<DateTime<Local>>,
+    outages: Vec<Outage>,
+}
+
+impl OutageTracker {
+    pub fn new(threshold: u32) -> Self {
+        Self { threshold: threshold.max(1), consecutive: 0, streak_start: None, outages: Vec::new() }
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Zählt einen Verlust; liefert den Beginn, sobald daraus ein Ausfall wird
+    pub fn loss(&mut self, at: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.consecutive += 1;
+        let start = *self.streak_start.get_or_insert(at);
+        if self.consecutive == self.threshold {
+            self.outages.push(Outage { start, end: None, lost: self.consecutive });
+            return Some(start);
+        }
+        if self.consecutive > self.threshold {
+            if let Some(outage) = self.outages.last_mut() {
+                outage.lost += 1;
+            }
+        }
+        None
+    }
+
+    /// Zählt eine Antwort; liefert den gerade beendeten Ausfall
+    pub fn reply(&mut self, at: DateTime<Local>) -> Option<&Outage> {
+        let ended = self.consecutive >= self.threshold;
+        self.consecutive = 0;
+        self.streak_start = None;
+        if !ended {
+            return None;
+        }
+        let outage = self.outages.last_mut()?;
+        outage.end = Some(at);
+        Some(outage)
+    }
+
+    pub fn outages(&self) -> &[Outage] {
+        &self.outages
+    }
+}
+
+/// Schreibt jede Messung sofort als CSV-Zeile, damit auch bei einem
+/// Abbruch nach Stunden nichts verloren geht
+pub struct Timeline {
+    writer: BufWriter<File>,
+}
+
+impl Timeline {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Zeitreihe '{}' konnte nicht angelegt werden: {}", path, e))?;
+        let mut timeline = Self { writer: BufWriter::new(file) };
+        timeline.write_line("timestamp,sequence,status,rtt_ms,ttl,http_status,error")
+            .map_err(|e| format!("Zeitreihe '{}' konnte nicht geschrieben werden: {}", path, e))?;
+        Ok(timeline)
+    }
+
+    pub fn record(
+        &mut self,
+        at: DateTime<Local>,
+        sequence: u16,
+        rtt_ms: Option<f64>,
+        ttl: Option<u8>,
+        status: Option<u16>,
+        error: Option<&str>,
+    ) -> std::io::Result<()> {
+        let line = [
+            at.to_rfc3339(),
+            sequence.to_string(),
+            if rtt_ms.is_some() { "ok" } else { "lost" }.to_string(),
+            rtt_ms.map(|rtt| format!("{:.3}", rtt)).unwrap_or_default(),
+            ttl.map(|ttl| ttl.to_string()).unwrap_or_default(),
+            status.map(|status| status.to_string()).unwrap_or_default(),
+            escape_csv(error.unwrap_or_default()),
+        ]
+        .join(",");
+        self.write_line(&line)
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+}
+
+/// Ausfall-Tabelle am Ende der Textausgabe
+pub fn print_outages(tracker: &OutageTracker, now: DateTime<Local>) {
+    let outages = tracker.outages();
+    if outages.is_empty() {
+        println!("\n✅ Keine Ausfälle erkannt (Schwelle: {} Verluste in Folge)", tracker.threshold());
+        return;
+    }
+
+    println!("\n🚨 Ausfälle (ab {} Verlusten in Folge): {}", tracker.threshold(), outages.len());
+    println!("   {:<19}  {:<19}  {:>10}  {:>8}", "Beginn", "Ende", "Dauer", "Verluste");
+    for outage in outages {
+        let end = outage.end
+            .map(|end| end.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "andauernd".to_string());
+        println!("   {:<19}  {:<19}  {:>10}  {:>8}",
+                 outage.start.format("%Y-%m-%d %H:%M:%S"), end,
+                 format_duration(outage.duration(now)), outage.lost);
+    }
+    let total: i64 = outages.iter().map(|outage| outage.duration(now).num_milliseconds()).sum();
+    println!("   Gesamt: {}", format_duration(chrono::Duration::milliseconds(total)));
+}
+
+/// Dauer als "12.3 s", "4m 05s" oder "2h 03m 10s"
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let millis = duration.num_milliseconds().max(0);
+    let seconds = millis / 1000;
+    match seconds {
+        0..=59 => format!("{:.1} s", millis as f64 / 1000.0),
+        60..=3599 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m {:02}s", seconds / 3600, seconds % 3600 / 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Zeitpunkt `second` Sekunden nach einem festen Startzeitpunkt
+    fn at(second: i64) -> DateTime<Local> {
+        Local.timestamp_opt(1_700_000_000 + second, 0).unwrap()
+    }
+
+    #[test]
+    fn short_loss_streak_is_no_outage() {
+        let mut tracker = OutageTracker::new(3);
+        assert_eq!(tracker.loss(at(0)), None);
+        assert_eq!(tracker.loss(at(1)), None);
+        assert!(tracker.reply(at(2)).is_none());
+        // Die Zählung beginnt nach einer Antwort von vorn
+        assert_eq!(tracker.loss(at(3)), None);
+        assert_eq!(tracker.loss(at(4)), None);
+        assert!(tracker.reply(at(5)).is_none());
+        assert!(tracker.outages().is_empty());
+    }
+
+    #[test]
+    fn outage_starts_at_first_loss_and_ends_with_reply() {
+        let mut tracker = OutageTracker::new(3);
+        assert!(tracker.reply(at(0)).is_none());
+        assert_eq!(tracker.loss(at(1)), None);
+        assert_eq!(tracker.loss(at(2)), None);
+        assert_eq!(tracker.loss(at(3)), Some(at(1)));
+        assert_eq!(tracker.loss(at(4)), None);
+
+        let ongoing = &tracker.outages()[0];
+        assert_eq!((ongoing.end, ongoing.lost), (None, 4));
+        assert_eq!(ongoing.duration(at(10)).num_seconds(), 9);
+
+        let ended = tracker.reply(at(5)).unwrap();
+        assert_eq!((ended.start, ended.end, ended.lost), (at(1), Some(at(5)), 4));
+        assert_eq!(ended.duration(at(10)).num_seconds(), 4);
+
+        // Weitere Antworten beenden den Ausfall nicht erneut
+        assert!(tracker.reply(at(6)).is_none());
+        assert_eq!(tracker.outages()[0].end, Some(at(5)));
+    }
+
+    #[test]
+    fn each_streak_is_a_separate_outage() {
+        let mut tracker = OutageTracker::new(0);
+        assert_eq!(tracker.threshold(), 1);
+        assert_eq!(tracker.loss(at(0)), Some(at(0)));
+        assert!(tracker.reply(at(1)).is_some());
+        assert_eq!(tracker.loss(at(2)), Some(at(2)));
+
+        let outages = tracker.outages();
+        assert_eq!(outages.len(), 2);
+        assert_eq!(outages[0].end, Some(at(1)));
+        assert_eq!(outages[1].end, None);
+
+        let record = outages[1].record(at(5));
+        assert_eq!((record.ended_at, record.duration_s, record.lost), (None, 3.0, 1));
+    }
+
+    #[test]
+    fn durations_are_formatted_by_magnitude() {
+        let format = |millis| format_duration(chrono::Duration::milliseconds(millis));
+        assert_eq!(format(-5), "0.0 s");
+        assert_eq!(format(12_345), "12.3 s");
+        assert_eq!(format(245_000), "4m 05s");
+        assert_eq!(format(7_390_000), "2h 03m 10s");
+    }
+}
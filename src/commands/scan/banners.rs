@@ -0,0 +1,518 @@
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::{HostResult, PortState, Protocol};
+
+// Maximale Größe einer gelesenen Antwort
+const MAX_RESPONSE: usize = 4096;
+
+// Nach dem ersten Datenblock nur noch kurz auf weitere Daten warten
+const FOLLOW_UP_WAIT: Duration = Duration::from_millis(150);
+
+/// Per Banner oder Sonde erkannter Dienst
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    /// Erkannter Dienst, z.B. "SSH"
+    pub service: String,
+    /// Produkt, z.B. "OpenSSH"
+    pub product: Option<String>,
+    /// Version des Produkts, z.B. "8.9p1"
+    pub version: Option<String>,
+    /// Zusatzinformationen, z.B. "STARTTLS" bei SMTP
+    pub extra_info: Option<String>,
+    /// Erste Zeile der Antwort (nur druckbare Zeichen)
+    pub banner: Option<String>,
+}
+
+impl ServiceInfo {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            product: None,
+            version: None,
+            extra_info: None,
+            banner: None,
+        }
+    }
+
+    /// Kurzbeschreibung, z.B. "SSH, OpenSSH 8.9p1"
+    pub fn describe(&self) -> String {
+        let mut text = self.service.clone();
+        if let Some(product) = &self.product {
+            if *product != self.service {
+                text.push_str(", ");
+                text.push_str(product);
+            }
+            if let Some(version) = &self.version {
+                text.push(' ');
+                text.push_str(version);
+            }
+        }
+        if let Some(extra) = &self.extra_info {
+            text.push_str(&format!(" [{}]", extra));
+        }
+        text
+    }
+}
+
+/// Liest Banner aller offenen Ports und trägt die erkannten Dienste ein
+pub async fn grab_banners(results: &mut [HostResult], concurrency: usize, timeout: Duration) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (host_index, result) in results.iter().enumerate() {
+        for (port_index, port) in result.ports.iter().enumerate() {
+            if port.protocol != Protocol::Tcp || port.state != PortState::Open {
+                continue;
+            }
+            let addr = SocketAddr::new(result.ip, port.port);
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                (host_index, port_index, grab_banner(addr, timeout).await)
+            });
+        }
+    }
+
+    while let Some(finished) = tasks.join_next().await {
+        if let Ok((host_index, port_index, service)) = finished {
+            results[host_index].ports[port_index].service = service;
+        }
+    }
+}
+
+async fn grab_banner(addr: SocketAddr, timeout: Duration) -> Option<ServiceInfo> {
+    let mut stream = tokio::time::timeout(timeout, TcpStream::connect(addr)).await.ok()?.ok()?;
+    let port = addr.port();
+
+    // Viele Dienste (SSH, FTP, SMTP, ...) melden sich von selbst
+    let mut response = if client_speaks_first(port) {
+        Vec::new()
+    } else {
+        read_response(&mut stream, timeout).await
+    };
+
+    // Sonst eine zum Port passende Sonde schicken
+    let mut probe = Probe::Http;
+    if response.is_empty() {
+        probe = probe_for_port(port);
+        stream.write_all(&probe.payload(addr)).await.ok()?;
+        response = read_response(&mut stream, timeout).await;
+    }
+
+    if response.is_empty() {
+        return None;
+    }
+
+    let mut info = identify(&response, port, probe);
+
+    // Nachgelagerte Abfragen für genauere Informationen
+    match info.service.as_str() {
+        "SMTP" => smtp_ehlo(&mut stream, &mut info, timeout).await,
+        "Redis" if response.starts_with(b"+PONG") => redis_version(&mut stream, &mut info, timeout).await,
+        _ => {}
+    }
+
+    Some(info)
+}
+
+/// Protokolle, bei denen der Client zuerst sendet
+fn client_speaks_first(port: u16) -> bool {
+    matches!(
+        port,
+        80 | 81 | 82 | 443 | 3000 | 3389 | 5000 | 5432 | 6379 | 8000 | 8008
+            | 8080 | 8081 | 8088 | 8443 | 8888 | 9000 | 9090 | 9200
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Probe {
+    Http,
+    Redis,
+    PostgresSsl,
+    RdpConnect,
+}
+
+impl Probe {
+    fn payload(self, addr: SocketAddr) -> Vec<u8> {
+        match self {
+            Probe::Http => format!(
+                "HEAD / HTTP/1.0\r\nHost: {}\r\nUser-Agent: shc-tool\r\n\r\n",
+                addr.ip()
+            ).into_bytes(),
+            Probe::Redis => b"PING\r\n".to_vec(),
+            // SSLRequest: Länge 8, Code 80877103
+            Probe::PostgresSsl => vec![0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f],
+            // X.224 Connection Request mit RDP Negotiation Request
+            Probe::RdpConnect => vec![
+                0x03, 0x00, 0x00, 0x13, 0x0e, 0xe0, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x01, 0x00, 0x08, 0x00, 0x03, 0x00, 0x00, 0x00,
+            ],
+        }
+    }
+}
+
+fn probe_for_port(port: u16) -> Probe {
+    match port {
+        6379 => Probe::Redis,
+        5432 => Probe::PostgresSsl,
+        3389 => Probe::RdpConnect,
+        _ => Probe::Http,
+    }
+}
+
+async fn read_response(stream: &mut TcpStream, timeout: Duration) -> Vec<u8> {
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 1024];
+    let mut wait = timeout;
+
+    while response.len() < MAX_RESPONSE {
+        match tokio::time::timeout(wait, stream.read(&mut buffer)).await {
+            Ok(Ok(n)) if n > 0 => {
+                response.extend_from_slice(&buffer[..n]);
+                wait = FOLLOW_UP_WAIT;
+            }
+            _ => break,
+        }
+    }
+
+    response
+}
+
+fn identify(response: &[u8], port: u16, probe: Probe) -> ServiceInfo {
+    let text = String::from_utf8_lossy(response);
+    let first_line = printable_first_line(&text);
+
+    let mut info = if response.starts_with(b"SSH-") {
+        identify_ssh(&first_line)
+    } else if response.starts_with(b"HTTP/") {
+        identify_http(&text)
+    } else if response.starts_with(b"RFB ") {
+        let mut info = ServiceInfo::new("VNC");
+        info.version = first_line.get(4..).map(|v| v.trim_start_matches('0').replace(".00", "."));
+        info
+    } else if response.starts_with(b"+PONG") || response.starts_with(b"-NOAUTH")
+        || (probe == Probe::Redis && response.starts_with(b"-"))
+    {
+        let mut info = ServiceInfo::new("Redis");
+        if response.starts_with(b"-NOAUTH") {
+            info.extra_info = Some("Authentifizierung erforderlich".to_string());
+        }
+        info
+    } else if response.starts_with(b"+OK") {
+        identify_mail("POP3", &first_line)
+    } else if response.starts_with(b"* OK") {
+        identify_mail("IMAP", &first_line)
+    } else if response.starts_with(b"220") {
+        identify_220(&first_line, port)
+    } else if let Some(info) = identify_mysql(response) {
+        info
+    } else if probe == Probe::PostgresSsl && (response == b"S" || response == b"N") {
+        let mut info = ServiceInfo::new("PostgreSQL");
+        info.extra_info = Some(if response == b"S" { "SSL unterstützt" } else { "ohne SSL" }.to_string());
+        info
+    } else if probe == Probe::RdpConnect && response.starts_with(&[0x03, 0x00]) {
+        ServiceInfo::new("RDP")
+    } else if response[0] == 0xff {
+        // IAC-Aushandlung
+        ServiceInfo::new("Telnet")
+    } else if response.len() > 2 && response[0] == 0x15 && response[1] == 0x03 {
+        // TLS-Alert als Antwort auf Klartext
+        ServiceInfo::new("TLS/SSL")
+    } else {
+        ServiceInfo::new(super::get_service_name(port, Protocol::Tcp))
+    };
+
+    if !first_line.is_empty() {
+        info.banner = Some(first_line);
+    }
+    info
+}
+
+fn identify_ssh(line: &str) -> ServiceInfo {
+    let mut info = ServiceInfo::new("SSH");
+    // SSH-2.0-OpenSSH_8.9p1 Ubuntu-3ubuntu0.1
+    let mut parts = line.splitn(3, '-');
+    let protocol = parts.nth(1).unwrap_or_default();
+    let software = parts.next().unwrap_or_default();
+    let software = software.split_whitespace().next().unwrap_or_default();
+
+    // Die Version steht hinter dem letzten Unterstrich (OpenSSH_for_Windows_8.1)
+    match software.split_once('_') {
+        Some((product, rest)) => {
+            info.product = Some(product.to_string());
+            info.version = rest.rsplit('_').next().map(str::to_string);
+        }
+        None if !software.is_empty() => info.product = Some(software.to_string()),
+        None => {}
+    }
+    info.extra_info = Some(format!("Protokoll {}", protocol));
+    info
+}
+
+fn identify_http(text: &str) -> ServiceInfo {
+    let mut info = ServiceInfo::new("HTTP");
+
+    let server = text.lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("server").then(|| value.trim().to_string())
+        });
+
+    if let Some(server) = server {
+        // Apache/2.4.41 (Ubuntu)
+        let token = server.split_whitespace().next().unwrap_or_default();
+        match token.split_once('/') {
+            Some((product, version)) => {
+                info.product = Some(product.to_string());
+                info.version = Some(version.to_string());
+            }
+            None => info.product = Some(server.clone()),
+        }
+    }
+    info
+}
+
+fn identify_mail(service: &str, line: &str) -> ServiceInfo {
+    let mut info = ServiceInfo::new(service);
+    for product in ["Dovecot", "Courier", "Cyrus", "Microsoft Exchange", "Zimbra"] {
+        if contains_ignore_case(line, product) {
+            info.product = Some(product.to_string());
+            info.version = version_after(line, product);
+            break;
+        }
+    }
+    info
+}
+
+/// "220"-Begrüßungen verwenden sowohl FTP als auch SMTP
+fn identify_220(line: &str, port: u16) -> ServiceInfo {
+    let is_smtp = contains_ignore_case(line, "SMTP")
+        || (!contains_ignore_case(line, "FTP") && matches!(port, 25 | 465 | 587 | 2525));
+
+    let (service, products): (&str, &[&str]) = if is_smtp {
+        ("SMTP", &["Postfix", "Exim", "Sendmail", "Microsoft ESMTP", "OpenSMTPD", "qmail"])
+    } else {
+        ("FTP", &["vsFTPd", "ProFTPD", "Pure-FTPd", "FileZilla Server", "Microsoft FTP Service"])
+    };
+
+    let mut info = ServiceInfo::new(service);
+    for product in products {
+        if contains_ignore_case(line, product) {
+            info.product = Some(product.to_string());
+            info.version = version_after(line, product);
+            break;
+        }
+    }
+    info
+}
+
+/// MySQL/MariaDB senden ein binäres Handshake-Paket (Protokoll 10)
+fn identify_mysql(response: &[u8]) -> Option<ServiceInfo> {
+    if response.len() < 6 {
+        return None;
+    }
+
+    match response[4] {
+        0x0a => {
+            let end = response[5..].iter().position(|&b| b == 0)? + 5;
+            let version = String::from_utf8_lossy(&response[5..end]).to_string();
+            let mut info = ServiceInfo::new("MySQL");
+            // MariaDB meldet sich als "5.5.5-10.6.12-MariaDB"
+            if let Some(rest) = version.strip_prefix("5.5.5-").filter(|v| v.contains("MariaDB")) {
+                info.product = Some("MariaDB".to_string());
+                info.version = rest.split('-').next().map(str::to_string);
+            } else {
+                info.product = Some(if version.contains("MariaDB") { "MariaDB" } else { "MySQL" }.to_string());
+                info.version = version.split('-').next().map(str::to_string);
+            }
+            Some(info)
+        }
+        0xff => {
+            let text = String::from_utf8_lossy(&response[5..]);
+            (text.contains("MySQL") || text.contains("MariaDB") || text.contains("is not allowed to connect"))
+                .then(|| ServiceInfo::new("MySQL"))
+        }
+        _ => None,
+    }
+}
+
+async fn smtp_ehlo(stream: &mut TcpStream, info: &mut ServiceInfo, timeout: Duration) {
+    if stream.write_all(b"EHLO shc-tool\r\n").await.is_err() {
+        return;
+    }
+    let response = read_response(stream, timeout).await;
+    if response.is_empty() {
+        return;
+    }
+
+    let text = String::from_utf8_lossy(&response);
+    let starttls = text.lines().any(|line| contains_ignore_case(line, "STARTTLS"));
+    info.extra_info = Some(if starttls { "STARTTLS" } else { "ohne STARTTLS" }.to_string());
+    let _ = stream.write_all(b"QUIT\r\n").await;
+}
+
+async fn redis_version(stream: &mut TcpStream, info: &mut ServiceInfo, timeout: Duration) {
+    info.extra_info = Some("ohne Authentifizierung".to_string());
+    if stream.write_all(b"INFO server\r\n").await.is_err() {
+        return;
+    }
+    let response = read_response(stream, timeout).await;
+    let text = String::from_utf8_lossy(&response);
+    if let Some(version) = text.lines().find_map(|line| line.strip_prefix("redis_version:")) {
+        info.product = Some("Redis".to_string());
+        info.version = Some(version.trim().to_string());
+    }
+}
+
+/// Erste Zeile ohne Steuerzeichen, auf eine lesbare Länge gekürzt
+fn printable_first_line(text: &str) -> String {
+    text.lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(120)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Sucht die Versionsnummer direkt hinter einem Produktnamen, z.B.
+/// "ProFTPD 1.3.5 Server" -> "1.3.5" oder "(vsFTPd 3.0.3)" -> "3.0.3"
+fn version_after(text: &str, product: &str) -> Option<String> {
+    let start = text.to_lowercase().find(&product.to_lowercase())? + product.len();
+    let rest = text.get(start..)?
+        .trim_start_matches([' ', '/', '_', 'v', 'V']);
+
+    let version: String = rest.chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-')
+        .collect();
+
+    version.starts_with(|c: char| c.is_ascii_digit()).then_some(version)
+}
+
+/// Vergleicht eine Versionsnummer wie "8.9p1" mit einer Mindestversion
+pub fn version_below(version: &str, minimum: &[u32]) -> bool {
+    let parts: Vec<u32> = version
+        .split(|c: char| !c.is_ascii_digit())
+        .take_while(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+
+    if parts.is_empty() {
+        return false;
+    }
+    parts.as_slice() < minimum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dienst, Produkt und Version zu einer Antwort auf `port`
+    fn detect(response: &str, port: u16) -> (String, Option<String>, Option<String>) {
+        let info = identify(response.as_bytes(), port, probe_for_port(port));
+        (info.service, info.product, info.version)
+    }
+
+    fn service(service: &str, product: Option<&str>, version: Option<&str>) -> (String, Option<String>, Option<String>) {
+        (service.to_string(), product.map(str::to_string), version.map(str::to_string))
+    }
+
+    #[test]
+    fn identifies_real_banners() {
+        let cases = [
+            ("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13.5\r\n", 22, service("SSH", Some("OpenSSH"), Some("9.6p1"))),
+            ("SSH-1.99-OpenSSH_3.9p1\r\n", 2222, service("SSH", Some("OpenSSH"), Some("3.9p1"))),
+            ("SSH-2.0-dropbear_2022.83\r\n", 22, service("SSH", Some("dropbear"), Some("2022.83"))),
+            ("SSH-2.0-OpenSSH_for_Windows_8.1\r\n", 22, service("SSH", Some("OpenSSH"), Some("8.1"))),
+            ("SSH-2.0-Cisco-1.25\r\n", 22, service("SSH", Some("Cisco-1.25"), None)),
+            ("HTTP/1.1 200 OK\r\nServer: Apache/2.4.41 (Ubuntu)\r\n\r\n", 80, service("HTTP", Some("Apache"), Some("2.4.41"))),
+            ("HTTP/1.1 301 Moved\r\nserver: nginx\r\n\r\n", 8080, service("HTTP", Some("nginx"), None)),
+            ("HTTP/1.1 200 OK\r\nServer: Microsoft-IIS/10.0\r\n\r\n", 80, service("HTTP", Some("Microsoft-IIS"), Some("10.0"))),
+            ("HTTP/1.0 404 Not Found\r\n\r\n", 80, service("HTTP", None, None)),
+            ("220 ProFTPD 1.3.5 Server (Debian) [::ffff:10.0.0.5]\r\n", 21, service("FTP", Some("ProFTPD"), Some("1.3.5"))),
+            ("220 ProFTPD 1.3.5e Server ready.\r\n", 21, service("FTP", Some("ProFTPD"), Some("1.3.5e"))),
+            ("220 (vsFTPd 3.0.3)\r\n", 21, service("FTP", Some("vsFTPd"), Some("3.0.3"))),
+            ("220---------- Welcome to Pure-FTPd [privsep] [TLS] ----------\r\n", 21, service("FTP", Some("Pure-FTPd"), None)),
+            ("220 Microsoft FTP Service\r\n", 2121, service("FTP", Some("Microsoft FTP Service"), None)),
+            ("220 mail.example.com ESMTP Postfix (Ubuntu)\r\n", 25, service("SMTP", Some("Postfix"), None)),
+            ("220 mx.example.com ESMTP Exim 4.96 Mon, 01 Jan 2024 10:00:00 +0000\r\n", 25, service("SMTP", Some("Exim"), Some("4.96"))),
+            ("220 host.example.com ESMTP Sendmail 8.15.2/8.15.2; Mon, 1 Jan 2024\r\n", 587, service("SMTP", Some("Sendmail"), Some("8.15.2"))),
+            ("220 relay ready\r\n", 25, service("SMTP", None, None)),
+            ("220 FTP relay ready\r\n", 25, service("FTP", None, None)),
+            ("+OK Dovecot (Ubuntu) ready.\r\n", 110, service("POP3", Some("Dovecot"), None)),
+            ("* OK [CAPABILITY IMAP4rev1 LITERAL+] Dovecot ready.\r\n", 143, service("IMAP", Some("Dovecot"), None)),
+            ("* OK The Microsoft Exchange IMAP4 service is ready.\r\n", 143, service("IMAP", Some("Microsoft Exchange"), None)),
+            ("RFB 003.008\n", 5900, service("VNC", None, Some("3.8"))),
+            ("-NOAUTH Authentication required.\r\n", 6379, service("Redis", None, None)),
+        ];
+        for (banner, port, expected) in cases {
+            assert_eq!(detect(banner, port), expected, "{:?}", banner);
+        }
+    }
+
+    #[test]
+    fn keeps_first_line_as_banner() {
+        let info = identify(b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13.5\r\nrest", 22, Probe::Http);
+        assert_eq!(info.banner.as_deref(), Some("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13.5"));
+        assert_eq!(info.extra_info.as_deref(), Some("Protokoll 2.0"));
+    }
+
+    #[test]
+    fn identifies_mysql_handshake() {
+        let handshake = |version: &str| {
+            let mut packet = vec![0x4a, 0x00, 0x00, 0x00, 0x0a];
+            packet.extend_from_slice(version.as_bytes());
+            packet.extend_from_slice(&[0x00, 0x08, 0x00, 0x00, 0x00]);
+            packet
+        };
+        let mysql = identify(&handshake("8.0.36-0ubuntu0.22.04.1"), 3306, Probe::Http);
+        assert_eq!((mysql.product.as_deref(), mysql.version.as_deref()), (Some("MySQL"), Some("8.0.36")));
+        let mariadb = identify(&handshake("5.5.5-10.6.12-MariaDB-0ubuntu0.22.04.1"), 3306, Probe::Http);
+        assert_eq!((mariadb.product.as_deref(), mariadb.version.as_deref()), (Some("MariaDB"), Some("10.6.12")));
+    }
+
+    #[test]
+    fn compares_versions_with_suffixes() {
+        let cases: [(&str, &[u32], bool); 14] = [
+            ("7.4", &[8, 0], true),
+            ("8.0", &[8, 0], false),
+            ("9.6p1", &[9, 8], true),
+            ("9.8p1", &[9, 8], false),
+            ("8.5p1", &[8, 5], false),
+            ("8.4p1", &[8, 5], true),
+            ("10.0", &[9, 8], false),
+            ("1.3.5", &[1, 3, 6], true),
+            ("1.3.5e", &[1, 3, 6], true),
+            ("1.3.6", &[1, 3, 6], false),
+            ("2.4.41", &[2, 4], false),
+            ("2.2.34", &[2, 4], true),
+            ("", &[8, 0], false),
+            ("unbekannt", &[8, 0], false),
+        ];
+        for (version, minimum, below) in cases {
+            assert_eq!(version_below(version, minimum), below, "{} < {:?}", version, minimum);
+        }
+    }
+
+    #[test]
+    fn finds_version_after_product() {
+        assert_eq!(version_after("(vsFTPd 3.0.3)", "vsFTPd").as_deref(), Some("3.0.3"));
+        assert_eq!(version_after("Exim/4.96", "exim").as_deref(), Some("4.96"));
+        assert_eq!(version_after("Dovecot v2.3.16 ready", "Dovecot").as_deref(), Some("2.3.16"));
+        assert_eq!(version_after("Postfix (Ubuntu)", "Postfix"), None);
+        assert_eq!(version_after("ESMTP", "Postfix"), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use super::ports::parse_port_spec;
+use super::report::ScanReport;
+use super::{HostResult, PortResult, Protocol};
+
+/// Eine Abweichung zwischen Baseline und aktuellem Scan
+pub enum Drift {
+    NewHost(IpAddr),
+    HostGone(IpAddr),
+    PortOpened { ip: IpAddr, port: u16, protocol: Protocol, service: String },
+    PortClosed { ip: IpAddr, port: u16, protocol: Protocol, service: String },
+    BannerChanged { ip: IpAddr, port: u16, protocol: Protocol, old: String, new: String },
+}
+
+impl Drift {
+    pub fn describe(&self) -> String {
+        match self {
+            Drift::NewHost(ip) => format!("🆕 Neuer Host: {}", ip),
+            Drift::HostGone(ip) => format!("👻 Host nicht mehr aktiv: {}", ip),
+            Drift::PortOpened { ip, port, protocol, service } => {
+                format!("🔓 Neu geöffnet: {} Port {} ({})", ip, port_label(*port, *protocol), service)
+            }
+            Drift::PortClosed { ip, port, protocol, service } => {
+                format!("🔒 Nicht mehr offen: {} Port {} ({})", ip, port_label(*port, *protocol), service)
+            }
+            Drift::BannerChanged { ip, port, protocol, old, new } => {
+                format!("🔄 Banner geändert: {} Port {}: '{}' → '{}'", ip, port_label(*port, *protocol), old, new)
+            }
+        }
+    }
+}
+
+fn port_label(port: u16, protocol: Protocol) -> String {
+    match protocol {
+        Protocol::Tcp => port.to_string(),
+        Protocol::Udp => format!("{}/udp", port),
+    }
+}
+
+pub fn save_baseline(report: &ScanReport, path: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Bericht konnte nicht serialisiert werden: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Fehler beim Schreiben von '{}': {}", path, e))
+}
+
+pub fn load_baseline(path: &str) -> Result<ScanReport, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Fehler beim Lesen von '{}': {}", path, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("'{}' ist keine gültige Baseline (JSON-Bericht von --save-baseline): {}", path, e))
+}
+
+/// Vergleicht den aktuellen Scan mit einer Baseline.
+///
+/// Ports, die nur in einem der beiden Scans geprüft wurden, werden ignoriert,
+/// damit ein kleinerer Port-Bereich nicht als "geschlossen" gemeldet wird.
+pub fn compare_reports(baseline: &ScanReport, current: &ScanReport, scanned_ports: &[u16]) -> Vec<Drift> {
+    let baseline_ports: HashSet<u16> = parse_port_spec(&baseline.ports, baseline.protocol)
+        .map(|ports| ports.into_iter().collect())
+        .unwrap_or_default();
+    let compared = |port: &PortResult| {
+        port.protocol == current.protocol
+            && baseline_ports.contains(&port.port)
+            && scanned_ports.binary_search(&port.port).is_ok()
+    };
+
+    let mut drifts = Vec::new();
+
+    for host in current.hosts.iter().filter(|host| host.is_active()) {
+        let Some(old_host) = baseline.hosts.iter().find(|old| old.ip == host.ip && old.is_active()) else {
+            drifts.push(Drift::NewHost(host.ip));
+            continue;
+        };
+
+        for port in host.open_ports().filter(|port| compared(port)) {
+            match find_open_port(old_host, port) {
+                None => drifts.push(Drift::PortOpened {
+                    ip: host.ip,
+                    port: port.port,
+                    protocol: port.protocol,
+                    service: port.service_name().to_string(),
+                }),
+                Some(old_port) => {
+                    let old_banner = banner_of(old_port);
+                    let new_banner = banner_of(port);
+                    // Nur vergleichen, wenn beide Scans Banner gelesen haben
+                    if let (Some(old), Some(new)) = (old_banner, new_banner) {
+                        if old != new {
+                            drifts.push(Drift::BannerChanged {
+                                ip: host.ip,
+                                port: port.port,
+                                protocol: port.protocol,
+                                old,
+                                new,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        for old_port in old_host.open_ports().filter(|port| compared(port)) {
+            if find_open_port(host, old_port).is_none() {
+                drifts.push(Drift::PortClosed {
+                    ip: host.ip,
+                    port: old_port.port,
+                    protocol: old_port.protocol,
+                    service: old_port.service_name().to_string(),
+                });
+            }
+        }
+    }
+
+    for old_host in baseline.hosts.iter().filter(|host| host.is_active()) {
+        let still_active = current.hosts.iter().any(|host| host.ip == old_host.ip && host.is_active());
+        if !still_active {
+            drifts.push(Drift::HostGone(old_host.ip));
+        }
+    }
+
+    drifts
+}
+
+fn find_open_port<'a>(host: &'a HostResult, port: &PortResult) -> Option<&'a PortResult> {
+    host.open_ports().find(|candidate| candidate.port == port.port && candidate.protocol == port.protocol)
+}
+
+/// Banner bzw. Produkt und Version eines Ports, falls erkannt
+fn banner_of(port: &PortResult) -> Option<String> {
+    let info = port.service.as_ref()?;
+    info.banner.clone().or_else(|| {
+        let description = info.describe();
+        (description != info.service).then_some(description)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Host mit den angegebenen offenen Ports und optionalem Banner
+    fn host(ip: &str, open: &[(u16, Option<&str>)]) -> Value {
+        let ports: Vec<Value> = open.iter()
+            .map(|(port, banner)| json!({
+                "port": port,
+                "protocol": "tcp",
+                "state": "open",
+                "service": banner.map(|banner| json!({ "service": "SSH", "banner": banner })),
+                "warnings": [],
+            }))
+            .collect();
+        json!({
+            "ip": ip,
+            "ports": ports,
+            "counts": { "open": open.len(), "open_filtered": 0, "closed": 0, "filtered": 0, "unreachable": 0 },
+        })
+    }
+
+    fn report(ports: &str, hosts: Vec<Value>) -> ScanReport {
+        serde_json::from_value(json!({
+            "target": "192.0.2.0/24",
+            "protocol": "tcp",
+            "started_at": "2026-01-01T00:00:00Z",
+            "duration_seconds": 1.0,
+            "ports": ports,
+            "port_count": 0,
+            "hosts_total": 254,
+            "discover_only": false,
+            "hosts": hosts,
+        }))
+        .unwrap()
+    }
+
+    fn describe(drifts: Vec<Drift>) -> Vec<String> {
+        drifts.iter().map(Drift::describe).collect()
+    }
+
+    #[test]
+    fn identical_scans_have_no_drift() {
+        let scan = || report("1-1024", vec![host("192.0.2.1", &[(22, Some("SSH-2.0-OpenSSH_9.6")), (80, None)])]);
+        assert!(compare_reports(&scan(), &scan(), &[22, 80, 443]).is_empty());
+    }
+
+    #[test]
+    fn reports_new_and_gone_hosts() {
+        let baseline = report("22", vec![host("192.0.2.1", &[(22, None)]), host("192.0.2.2", &[(22, None)])]);
+        let current = report("22", vec![host("192.0.2.2", &[(22, None)]), host("192.0.2.3", &[(22, None)])]);
+        assert_eq!(describe(compare_reports(&baseline, &current, &[22])), vec![
+            "🆕 Neuer Host: 192.0.2.3",
+            "👻 Host nicht mehr aktiv: 192.0.2.1",
+        ]);
+    }
+
+    #[test]
+    fn inactive_hosts_count_as_missing() {
+        let baseline = report("22", vec![host("192.0.2.1", &[])]);
+        let current = report("22", vec![host("192.0.2.1", &[(22, None)])]);
+        assert_eq!(describe(compare_reports(&baseline, &current, &[22])), vec!["🆕 Neuer Host: 192.0.2.1"]);
+        assert_eq!(describe(compare_reports(&current, &baseline, &[22])), vec!["👻 Host nicht mehr aktiv: 192.0.2.1"]);
+    }
+
+    #[test]
+    fn reports_opened_closed_and_changed_ports() {
+        let baseline = report("1-1024", vec![host("192.0.2.1", &[(22, Some("SSH-2.0-OpenSSH_8.9")), (23, None)])]);
+        let current = report("1-1024", vec![host("192.0.2.1", &[(22, Some("SSH-2.0-OpenSSH_9.6")), (443, None)])]);
+        assert_eq!(describe(compare_reports(&baseline, &current, &[22, 23, 443])), vec![
+            "🔄 Banner geändert: 192.0.2.1 Port 22: 'SSH-2.0-OpenSSH_8.9' → 'SSH-2.0-OpenSSH_9.6'",
+            "🔓 Neu geöffnet: 192.0.2.1 Port 443 (HTTPS)",
+            "🔒 Nicht mehr offen: 192.0.2.1 Port 23 (Telnet)",
+        ]);
+    }
+
+    #[test]
+    fn missing_banner_is_no_change() {
+        let baseline = report("22", vec![host("192.0.2.1", &[(22, Some("SSH-2.0-OpenSSH_8.9"))])]);
+        let current = report("22", vec![host("192.0.2.1", &[(22, None)])]);
+        assert!(compare_reports(&baseline, &current, &[22]).is_empty());
+    }
+
+    #[test]
+    fn ignores_ports_scanned_only_once() {
+        // Baseline kannte nur 1-100, der aktuelle Scan nur 22 und 8080
+        let baseline = report("1-100", vec![host("192.0.2.1", &[(22, None), (80, None)])]);
+        let current = report("22,8080", vec![host("192.0.2.1", &[(22, None), (8080, None)])]);
+        assert!(compare_reports(&baseline, &current, &[22, 8080]).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::targets::ScanTarget;
+use crate::icmp::IcmpSocket;
+
+/// Ports, auf denen bei der Host-Erkennung ein Verbindungsaufbau versucht wird
+const DISCOVERY_PORTS: [u16; 6] = [80, 443, 22, 445, 3389, 8080];
+
+// ATF_COM: ARP-Eintrag ist vollständig (Antwort erhalten)
+const ARP_FLAG_COMPLETE: u32 = 0x2;
+
+/// Ein bei der Host-Erkennung gefundener Host
+pub struct Discovery {
+    /// Woran der Host erkannt wurde, z.B. "ICMP Echo (0.4 ms)" oder "TCP 443"
+    pub method: String,
+    /// MAC-Adresse aus der ARP-Tabelle (nur im lokalen Netz)
+    pub mac: Option<String>,
+}
+
+/// Prüft, welche Hosts erreichbar sind.
+///
+/// Nacheinander werden ICMP Echo, TCP-Verbindungen zu typischen Ports und
+/// für lokale Netze die ARP-Tabelle des Kernels ausgewertet. Das Ergebnis hat
+/// dieselbe Reihenfolge wie `hosts`, `None` steht für keinen Hinweis auf
+/// einen aktiven Host.
+pub async fn discover_hosts(hosts: &[ScanTarget], concurrency: usize, timeout: Duration) -> Vec<Option<Discovery>> {
+    let icmp_available = hosts.first().is_some_and(|host| IcmpSocket::new(host.ip).is_ok());
+    if !icmp_available {
+        // stderr, damit JSON/CSV/XML auf stdout parsebar bleibt
+        eprintln!("ℹ️  ICMP nicht verfügbar (fehlende Berechtigung) - nutze nur TCP und ARP");
+    }
+
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    // Jeder Host belegt einen Slot pro gleichzeitig geprüftem Port
+    let permits_per_host = DISCOVERY_PORTS.len().min(concurrency) as u32;
+    let mut tasks = JoinSet::new();
+
+    for (index, host) in hosts.iter().enumerate() {
+        let ip = host.ip;
+        let permit = semaphore.clone().acquire_many_owned(permits_per_host).await
+            .expect("Semaphore wird nie geschlossen");
+        tasks.spawn(async move {
+            let method = probe_host(ip, icmp_available, timeout).await;
+            drop(permit);
+            (index, method)
+        });
+    }
+
+    let mut discoveries: Vec<Option<Discovery>> = hosts.iter().map(|_| None).collect();
+    while let Some(finished) = tasks.join_next().await {
+        if let Ok((index, Some(method))) = finished {
+            discoveries[index] = Some(Discovery { method, mac: None });
+        }
+    }
+
+    // Die Verbindungsversuche haben im lokalen Netz ARP-Anfragen ausgelöst.
+    // Ein vollständiger Eintrag beweist, dass der Host aktiv ist - auch wenn
+    // er ICMP und TCP komplett verwirft.
+    let neighbours = read_arp_table();
+    let interfaces = netdev::get_interfaces();
+    for (host, discovery) in hosts.iter().zip(discoveries.iter_mut()) {
+        let local_mac = interfaces.iter()
+            .find(|interface| interface.ip_addrs().contains(&host.ip))
+            .and_then(|interface| interface.mac_addr)
+            .filter(|mac| *mac != netdev::MacAddr::zero())
+            .map(|mac| mac.address());
+        let mac = match host.ip {
+            IpAddr::V4(ip) => neighbours.get(&ip).cloned(),
+            IpAddr::V6(_) => None,
+        }.or(local_mac);
+
+        match discovery {
+            Some(found) => found.mac = mac,
+            None if mac.is_some() && is_local_network(&interfaces, host.ip) => {
+                *discovery = Some(Discovery { method: "ARP".to_string(), mac });
+            }
+            None => {}
+        }
+    }
+
+    discoveries
+}
+
+/// Liefert die Erkennungsmethode, falls der Host geantwortet hat
+async fn probe_host(ip: IpAddr, icmp_available: bool, timeout: Duration) -> Option<String> {
+    if icmp_available {
+        if let Ok(socket) = IcmpSocket::new(ip) {
+            if let Ok(Some(reply)) = socket.echo(1, b"SHC-Tool discovery", timeout).await {
+                return Some(format!("ICMP Echo ({:.1} ms)", reply.rtt.as_secs_f64() * 1000.0));
+            }
+        }
+    }
+
+    let mut connects = JoinSet::new();
+    for port in DISCOVERY_PORTS {
+        connects.spawn(async move {
+            let addr = SocketAddr::new(ip, port);
+            match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+                Ok(Ok(_)) => Some(format!("TCP {}", port)),
+                // Auch ein RST zeigt, dass der Host aktiv ist
+                Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => Some(format!("TCP {} (RST)", port)),
+                _ => None,
+            }
+        });
+    }
+
+    while let Some(finished) = connects.join_next().await {
+        if let Ok(Some(method)) = finished {
+            return Some(method);
+        }
+    }
+    None
+}
+
+/// Liest die IPv4-Nachbartabelle des Kernels (`/proc/net/arp`).
+///
+/// Nur unter Linux verfügbar; netdev liefert keine Nachbartabelle, sondern
+/// nur die MAC-Adresse des Gateways.
+#[cfg(target_os = "linux")]
+fn read_arp_table() -> HashMap<Ipv4Addr, String> {
+    let Ok(content) = std::fs::read_to_string("/proc/net/arp") else {
+        return HashMap::new();
+    };
+
+    content.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let ip = fields.first()?.parse().ok()?;
+            let flags = u32::from_str_radix(fields.get(2)?.trim_start_matches("0x"), 16).ok()?;
+            if flags & ARP_FLAG_COMPLETE == 0 {
+                return None;
+            }
+            Some((ip, fields.get(3)?.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Ohne `/proc/net/arp` entfällt die ARP-Erkennung
+#[cfg(not(target_os = "linux"))]
+fn read_arp_table() -> HashMap<Ipv4Addr, String> {
+    HashMap::new()
+}
+
+/// Ob die Adresse in einem direkt angeschlossenen Netz liegt
+fn is_local_network(interfaces: &[netdev::Interface], ip: IpAddr) -> bool {
+    interfaces.iter().any(|interface| match ip {
+        IpAddr::V4(ip) => interface.ipv4.iter().any(|net| net.contains(&ip)),
+        IpAddr::V6(ip) => interface.ipv6.iter().any(|net| net.contains(&ip)),
+    })
+}
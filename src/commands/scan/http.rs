@@ -0,0 +1,367 @@
+use reqwest::header::{HeaderMap, LOCATION, SERVER, WWW_AUTHENTICATE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::{HostResult, PortResult, PortState, Protocol};
+
+/// Ports, die immer als Web-Ports geprüft werden
+const HTTP_PORTS: [u16; 4] = [80, 443, 8080, 8443];
+/// Ports, auf denen ohne TLS-Prüfung HTTPS angenommen wird
+const HTTPS_PORTS: [u16; 2] = [443, 8443];
+/// Maximal verfolgte Weiterleitungen
+const MAX_REDIRECTS: usize = 5;
+/// Mehr wird vom Seiteninhalt nicht gelesen (Titel und Erkennung)
+const MAX_BODY: usize = 64 * 1024;
+
+/// Merkmale einer bekannten Admin-Oberfläche
+struct AdminSignature {
+    name: &'static str,
+    /// Gesucht in `<title>` und im Realm von `WWW-Authenticate`
+    titles: &'static [&'static str],
+    /// Produktspezifische Spuren im Seiteninhalt (Pfade, Formularfelder),
+    /// keine bloßen Herstellernamen
+    markers: &'static [&'static str],
+}
+
+/// Bekannte Admin-Oberflächen. Herstellernamen zählen nur in Titel oder
+/// Realm, damit Blog- oder Shop-Seiten, die das Produkt nur erwähnen, keinen
+/// Befund auslösen.
+const ADMIN_SIGNATURES: &[AdminSignature] = &[
+    AdminSignature { name: "Tomcat Manager", titles: &["Tomcat Manager", "Tomcat Web Application Manager"], markers: &["/manager/html/upload"] },
+    AdminSignature { name: "phpMyAdmin", titles: &["phpMyAdmin"], markers: &["name=\"pma_username\""] },
+    AdminSignature { name: "FRITZ!Box", titles: &["FRITZ!Box"], markers: &[] },
+    AdminSignature { name: "TP-Link Router", titles: &["TP-LINK", "TP-Link"], markers: &[] },
+    AdminSignature { name: "MikroTik RouterOS", titles: &["RouterOS"], markers: &[] },
+    AdminSignature { name: "OpenWrt LuCI", titles: &["LuCI"], markers: &["/luci-static/"] },
+    AdminSignature { name: "DD-WRT", titles: &["DD-WRT"], markers: &[] },
+    AdminSignature { name: "Speedport", titles: &["Speedport"], markers: &[] },
+    AdminSignature { name: "NETGEAR Router", titles: &["NETGEAR"], markers: &[] },
+    AdminSignature { name: "Zyxel", titles: &["ZyXEL", "Zyxel"], markers: &[] },
+    AdminSignature { name: "pfSense", titles: &["pfSense"], markers: &[] },
+    AdminSignature { name: "OPNsense", titles: &["OPNsense"], markers: &[] },
+    AdminSignature { name: "Synology DSM", titles: &["Synology", "DiskStation"], markers: &["/webman/login.cgi"] },
+    AdminSignature { name: "UniFi Controller", titles: &["UniFi"], markers: &[] },
+    AdminSignature { name: "Webmin", titles: &["Webmin"], markers: &[] },
+];
+
+/// Zusätzlich abgefragte Pfade typischer Admin-Oberflächen
+const ADMIN_PATHS: [&str; 3] = ["/manager/html", "/phpmyadmin/", "/phpMyAdmin/"];
+
+/// Ergebnis der HTTP-Prüfung eines Web-Ports
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HttpInfo {
+    /// Abgefragte Start-URL
+    pub url: String,
+    /// Statuscode der letzten Antwort (nach Weiterleitungen)
+    pub status: u16,
+    pub server: Option<String>,
+    pub powered_by: Option<String>,
+    pub title: Option<String>,
+    /// Weiterleitungsziele in Reihenfolge; ein Ziel auf anderem Host oder
+    /// Port steht am Ende und wurde nicht mehr abgefragt
+    pub redirects: Vec<String>,
+    /// Fehlende Sicherheits-Header (HSTS nur bei HTTPS)
+    pub missing_headers: Vec<String>,
+    /// Gefundene Admin-Oberflächen, z.B. "Tomcat Manager (/manager/html)"
+    pub admin_panels: Vec<String>,
+}
+
+/// Eine gelesene Antwort
+struct Page {
+    status: u16,
+    headers: HeaderMap,
+    body: String,
+}
+
+/// Fragt offene Web-Ports per HTTP(S) ab
+pub async fn inspect_http(results: &mut [HostResult], concurrency: usize, timeout: Duration) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (host_index, result) in results.iter().enumerate() {
+        for (port_index, port) in result.ports.iter().enumerate() {
+            if !is_web_port(port) {
+                continue;
+            }
+            let addr = SocketAddr::new(result.ip, port.port);
+            let hostname = result.hostname.clone();
+            let https = port.tls.is_some() || HTTPS_PORTS.contains(&port.port) || port.service_name().starts_with("HTTPS");
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let info = inspect_endpoint(addr, hostname.as_deref(), https, timeout).await;
+                (host_index, port_index, info)
+            });
+        }
+    }
+
+    while let Some(finished) = tasks.join_next().await {
+        if let Ok((host_index, port_index, info)) = finished {
+            results[host_index].ports[port_index].http = info;
+        }
+    }
+}
+
+fn is_web_port(port: &PortResult) -> bool {
+    port.protocol == Protocol::Tcp
+        && port.state == PortState::Open
+        && (HTTP_PORTS.contains(&port.port) || port.service_name().starts_with("HTTP"))
+}
+
+async fn inspect_endpoint(addr: SocketAddr, hostname: Option<&str>, https: bool, timeout: Duration) -> Option<HttpInfo> {
+    let scheme = if https { "https" } else { "http" };
+    let mut builder = Client::builder()
+        .redirect(Policy::none())
+        .danger_accept_invalid_certs(true)
+        .connect_timeout(timeout)
+        .timeout(timeout * 3)
+        .user_agent("shc-tool");
+
+    // Mit Hostname abfragen (virtuelle Hosts), aber auf die gescannte Adresse festlegen
+    let url = match hostname {
+        Some(name) => {
+            builder = builder.resolve(name, addr);
+            format!("{}://{}:{}/", scheme, name, addr.port())
+        }
+        None => format!("{}://{}/", scheme, addr),
+    };
+    let client = builder.build().ok()?;
+    let start = Url::parse(&url).ok()?;
+
+    let mut current = start.clone();
+    let mut page = fetch(&client, &current).await?;
+    let mut redirects: Vec<String> = Vec::new();
+    while (300..400).contains(&page.status) && redirects.len() < MAX_REDIRECTS {
+        let Some(next) = page.headers.get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| current.join(location).ok())
+        else {
+            break;
+        };
+        if next == current || redirects.contains(&next.to_string()) {
+            break;
+        }
+        redirects.push(next.to_string());
+        // Nur innerhalb des gescannten Dienstes folgen; fremde Ziele werden
+        // lediglich vermerkt, nicht abgefragt
+        if !same_endpoint(&start, &next) {
+            break;
+        }
+        current = next;
+        match fetch(&client, &current).await {
+            Some(next_page) => page = next_page,
+            None => break,
+        }
+    }
+
+    let mut admin_panels: Vec<String> = detect_admin_panel(&page)
+        .map(|name| format!("{} ({})", name, current.path()))
+        .into_iter()
+        .collect();
+    for path in ADMIN_PATHS {
+        let Ok(url) = start.join(path) else { continue };
+        let Some(candidate) = fetch(&client, &url).await else { continue };
+        if !matches!(candidate.status, 200 | 401 | 403) {
+            continue;
+        }
+        if let Some(name) = detect_admin_panel(&candidate) {
+            let panel = format!("{} ({})", name, path);
+            if !admin_panels.iter().any(|known| known.starts_with(name)) {
+                admin_panels.push(panel);
+            }
+        }
+    }
+
+    let final_https = current.scheme() == "https";
+    let mut missing_headers = Vec::new();
+    for (header, needed) in [
+        ("Strict-Transport-Security", final_https),
+        ("Content-Security-Policy", true),
+        ("X-Frame-Options", true),
+    ] {
+        if needed && !page.headers.contains_key(header) {
+            missing_headers.push(header.to_string());
+        }
+    }
+
+    Some(HttpInfo {
+        url,
+        status: page.status,
+        server: header_value(&page.headers, SERVER.as_str()),
+        powered_by: header_value(&page.headers, "x-powered-by"),
+        title: extract_title(&page.body),
+        redirects,
+        missing_headers,
+        admin_panels,
+    })
+}
+
+/// Gleicher Host und Port, unabhängig von Schema und Pfad
+fn same_endpoint(start: &Url, next: &Url) -> bool {
+    start.host_str().map(str::to_ascii_lowercase) == next.host_str().map(str::to_ascii_lowercase)
+        && start.port_or_known_default() == next.port_or_known_default()
+}
+
+async fn fetch(client: &Client, url: &Url) -> Option<Page> {
+    let mut response = client.get(url.clone()).send().await.ok()?;
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+
+    let mut body = Vec::new();
+    while body.len() < MAX_BODY {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    body.truncate(MAX_BODY);
+
+    Some(Page { status, headers, body: String::from_utf8_lossy(&body).into_owned() })
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Sucht Merkmale bekannter Admin-Oberflächen in Titel, Auth-Realm und
+/// produktspezifischen Spuren im Inhalt
+fn detect_admin_panel(page: &Page) -> Option<&'static str> {
+    let realm = header_value(&page.headers, WWW_AUTHENTICATE.as_str()).unwrap_or_default();
+    let title = extract_title(&page.body).unwrap_or_default();
+    ADMIN_SIGNATURES.iter()
+        .find(|signature| {
+            signature.titles.iter().any(|pattern| realm.contains(pattern) || title.contains(pattern))
+                || signature.markers.iter().any(|marker| page.body.contains(marker))
+        })
+        .map(|signature| signature.name)
+}
+
+/// Inhalt des `<title>`-Elements, Leerraum zusammengefasst
+fn extract_title(body: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+
+    let title = body[start..end]
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'");
+    let title: String = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title: String = title.chars().take(120).collect();
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn page(body: &str, realm: Option<&str>) -> Page {
+        let mut headers = HeaderMap::new();
+        if let Some(realm) = realm {
+            headers.insert(WWW_AUTHENTICATE, HeaderValue::from_str(realm).unwrap());
+        }
+        Page { status: 200, headers, body: body.to_string() }
+    }
+
+    #[test]
+    fn vendor_name_in_body_is_no_admin_panel() {
+        let blog = page("<html><title>Mein Blog</title><p>Mein NETGEAR-Router und die Synology im Test, dazu UniFi.</p></html>", None);
+        assert_eq!(detect_admin_panel(&blog), None);
+    }
+
+    #[test]
+    fn detects_title_realm_and_markers() {
+        let title = page("<title>pfSense - Login</title>", None);
+        assert_eq!(detect_admin_panel(&title), Some("pfSense"));
+
+        let realm = page("", Some("Basic realm=\"NETGEAR R7000\""));
+        assert_eq!(detect_admin_panel(&realm), Some("NETGEAR Router"));
+
+        let luci = page("<title>router</title><link href=\"/luci-static/bootstrap/cascade.css\">", None);
+        assert_eq!(detect_admin_panel(&luci), Some("OpenWrt LuCI"));
+    }
+
+    #[test]
+    fn compares_host_and_port_only() {
+        let start = Url::parse("http://nas.local/").unwrap();
+        let same = |url: &str| same_endpoint(&start, &Url::parse(url).unwrap());
+        assert!(same("http://nas.local/login"));
+        assert!(same("http://NAS.local:80/admin"));
+        assert!(!same("https://nas.local/"));
+        assert!(!same("http://nas.local:8080/"));
+        assert!(!same("http://sso.example.com/"));
+
+        let start = Url::parse("http://192.0.2.10:8080/").unwrap();
+        assert!(same_endpoint(&start, &Url::parse("https://192.0.2.10:8080/").unwrap()));
+    }
+
+    /// Beantwortet jede Anfrage mit `response` und zählt die Verbindungen
+    async fn serve(response: String) -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (addr, hits)
+    }
+
+    fn redirect_to(location: &str) -> String {
+        format!("HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", location)
+    }
+
+    #[tokio::test]
+    async fn records_foreign_redirect_without_fetching() {
+        let (other, other_hits) = serve("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()).await;
+        let (addr, _) = serve(redirect_to(&format!("http://{}/login", other))).await;
+
+        let info = inspect_endpoint(addr, None, false, Duration::from_secs(2)).await.unwrap();
+        assert_eq!(info.status, 302);
+        assert_eq!(info.redirects, [format!("http://{}/login", other)]);
+        assert_eq!(other_hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn follows_redirect_on_same_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).await.unwrap_or(0);
+                let response = if request[..n].starts_with(b"GET / ") {
+                    redirect_to("/login")
+                } else {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 20\r\nConnection: close\r\n\r\n<title>Login</title>".to_string()
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let info = inspect_endpoint(addr, None, false, Duration::from_secs(2)).await.unwrap();
+        assert_eq!(info.status, 200);
+        assert_eq!(info.title.as_deref(), Some("Login"));
+        assert_eq!(info.redirects, [format!("http://{}/login", addr)]);
+    }
+}
@@ -0,0 +1,587 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+mod banners;
+mod baseline;
+mod discovery;
+mod http;
+mod policy;
+mod ports;
+mod report;
+mod scanner;
+mod services;
+mod smb;
+mod targets;
+mod text;
+mod tls;
+mod udp;
+mod vendors;
+mod xml;
+
+use banners::{grab_banners, ServiceInfo};
+use baseline::{compare_reports, load_baseline, save_baseline};
+use discovery::discover_hosts;
+use http::{inspect_http, HttpInfo};
+use policy::{Finding, Policy};
+use ports::{exclude_ports, parse_port_spec};
+pub use report::OutputFormat;
+use report::{compact_ports, ScanReport};
+use scanner::scan_ports;
+use services::get_service_name;
+use smb::{inspect_smb, SmbInfo};
+pub use targets::{reverse_lookup, AddressFamily};
+use targets::{parse_target, ScanTarget};
+use tls::{inspect_tls, TlsInfo};
+use vendors::get_vendor;
+
+/// Einstellungen für den Port-Scan
+pub struct ScanOptions {
+    /// Maximale Anzahl gleichzeitig offener Verbindungsversuche
+    pub concurrency: usize,
+    /// Timeout pro Verbindungsversuch
+    pub timeout: Duration,
+    /// Optionale Obergrenze für Verbindungsversuche pro Sekunde
+    pub rate: Option<u32>,
+    /// Port-Spezifikation, z.B. `1-1024,8080,top100` (Standard: gängige Ports)
+    pub ports: Option<String>,
+    /// Ports, die trotz `ports` nicht gescannt werden sollen
+    pub exclude_ports: Option<String>,
+    /// Welche Adressen eines Hostnamens gescannt werden
+    pub family: AddressFamily,
+    /// Gefundene Hosts per Reverse-DNS (PTR) benennen
+    pub reverse_dns: bool,
+    /// Banner offener Ports lesen und Dienste/Versionen erkennen
+    pub banners: bool,
+    /// TLS-Handshake mit offenen Ports versuchen und Zertifikate auslesen
+    pub tls: bool,
+    /// Web-Ports per HTTP(S) abfragen (Titel, Header, Weiterleitungen, Admin-Oberflächen)
+    pub http: bool,
+    /// SMB-Ports nach Dialekt, Signing, NetBIOS-Namen und Betriebssystem fragen
+    pub smb: bool,
+    /// UDP- statt TCP-Ports scannen
+    pub udp: bool,
+    /// Auch geschlossene, gefilterte und unerreichbare Ports auflisten
+    pub show_closed: bool,
+    /// Nur aktive Hosts ermitteln, keinen Port-Scan durchführen
+    pub discover_only: bool,
+    /// Host-Erkennung überspringen und alle Adressen scannen
+    pub no_discovery: bool,
+    /// Format des Berichts
+    pub format: OutputFormat,
+    /// Bericht in diese Datei schreiben statt auf die Konsole
+    pub output: Option<String>,
+    /// Ergebnis als Baseline (JSON) für spätere Vergleiche speichern
+    pub save_baseline: Option<String>,
+    /// Ergebnis mit dieser Baseline vergleichen
+    pub compare: Option<String>,
+    /// Kundenspezifische Sicherheitsrichtlinie (TOML) statt bzw. zusätzlich zur Standard-Richtlinie
+    pub policy: Option<String>,
+}
+
+impl ScanOptions {
+    fn protocol(&self) -> Protocol {
+        if self.udp { Protocol::Udp } else { Protocol::Tcp }
+    }
+}
+
+/// Transportprotokoll eines gescannten Ports
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    /// Kurzname wie in der Dienst-Tabelle, z.B. "tcp"
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        }
+    }
+}
+
+/// Zustand eines Ports nach dem Scan
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    Open,
+    /// Keine Antwort - bei UDP nicht von einem Paketfilter zu unterscheiden
+    #[serde(rename = "open|filtered")]
+    OpenFiltered,
+    /// Verbindung abgewiesen (TCP RST bzw. ICMP Port Unreachable)
+    Closed,
+    /// Keine Antwort innerhalb des Timeouts, vermutlich ein Paketfilter
+    Filtered,
+    /// ICMP Host/Network Unreachable oder keine Route zum Ziel
+    Unreachable,
+}
+
+impl PortState {
+    /// Bezeichnung wie bei nmap, z.B. "open|filtered"
+    fn as_str(self) -> &'static str {
+        match self {
+            PortState::Open => "open",
+            PortState::OpenFiltered => "open|filtered",
+            PortState::Closed => "closed",
+            PortState::Filtered => "filtered",
+            PortState::Unreachable => "unreachable",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PortState::Open => "OFFEN",
+            PortState::OpenFiltered => "OFFEN|GEFILTERT",
+            PortState::Closed => "GESCHLOSSEN",
+            PortState::Filtered => "GEFILTERT",
+            PortState::Unreachable => "UNERREICHBAR",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            PortState::Open => "✅",
+            PortState::OpenFiltered => "❔",
+            PortState::Closed => "🚫",
+            PortState::Filtered => "🧱",
+            PortState::Unreachable => "📵",
+        }
+    }
+}
+
+/// Anzahl der Ports je Zustand, unabhängig von `--show-closed`
+#[derive(Default, Serialize, Deserialize)]
+pub struct PortCounts {
+    open: usize,
+    open_filtered: usize,
+    closed: usize,
+    filtered: usize,
+    unreachable: usize,
+}
+
+impl PortCounts {
+    fn add(&mut self, state: PortState) {
+        match state {
+            PortState::Open => self.open += 1,
+            PortState::OpenFiltered => self.open_filtered += 1,
+            PortState::Closed => self.closed += 1,
+            PortState::Filtered => self.filtered += 1,
+            PortState::Unreachable => self.unreachable += 1,
+        }
+    }
+}
+
+/// Ergebnis des Port-Scans für einen einzelnen Host
+#[derive(Serialize, Deserialize)]
+pub struct HostResult {
+    ip: IpAddr,
+    /// Hostname aus dem Scan-Ziel
+    hostname: Option<String>,
+    /// MAC-Adresse aus der Host-Erkennung (nur im lokalen Netz)
+    mac: Option<String>,
+    /// Hersteller laut MAC-Präfix
+    vendor: Option<String>,
+    /// Woran die Host-Erkennung den Host erkannt hat
+    discovered_by: Option<String>,
+    /// Name aus dem PTR-Eintrag (nur mit `--reverse-dns`)
+    reverse_dns: Option<String>,
+    /// Offene Ports, mit `--show-closed` auch alle übrigen
+    ports: Vec<PortResult>,
+    counts: PortCounts,
+}
+
+/// Ein gefundener Port mit optional erkanntem Dienst
+#[derive(Serialize, Deserialize)]
+pub struct PortResult {
+    port: u16,
+    protocol: Protocol,
+    state: PortState,
+    /// Per Banner bzw. UDP-Antwort erkannter Dienst
+    service: Option<ServiceInfo>,
+    /// Zertifikat und Protokoll, falls der Port TLS spricht (nur mit `--tls`)
+    tls: Option<TlsInfo>,
+    /// Antwort eines Web-Ports (nur mit `--http`)
+    http: Option<HttpInfo>,
+    /// Dialekt, Signing und Namen eines SMB-Ports (nur mit `--smb`)
+    smb: Option<SmbInfo>,
+    /// Befunde der Sicherheitsrichtlinie (nur für offene Ports)
+    warnings: Vec<Finding>,
+}
+
+impl PortResult {
+    /// Erkannter Dienst oder der Name aus der Dienst-Tabelle
+    fn service_name(&self) -> &str {
+        match &self.service {
+            Some(info) => &info.service,
+            None => get_service_name(self.port, self.protocol),
+        }
+    }
+}
+
+impl HostResult {
+    fn new(target: &ScanTarget) -> Self {
+        HostResult {
+            ip: target.ip,
+            hostname: target.hostname.clone(),
+            mac: target.mac.clone(),
+            vendor: target.mac.as_deref().and_then(get_vendor).map(str::to_string),
+            discovered_by: target.discovered_by.clone(),
+            reverse_dns: None,
+            ports: Vec::new(),
+            counts: PortCounts::default(),
+        }
+    }
+
+    fn open_ports(&self) -> impl Iterator<Item = &PortResult> {
+        self.ports.iter().filter(|port| port.state == PortState::Open)
+    }
+
+    fn ports_scanned(&self) -> usize {
+        let counts = &self.counts;
+        counts.open + counts.open_filtered + counts.closed + counts.filtered + counts.unreachable
+    }
+
+    /// Ob der Host geantwortet hat (für den Baseline-Vergleich)
+    fn is_active(&self) -> bool {
+        self.discovered_by.is_some() || self.counts.open + self.counts.closed > 0
+    }
+
+    /// Ob der Host bei Netzwerk-Scans einzeln aufgelistet wird
+    fn is_listed(&self, show_closed: bool) -> bool {
+        self.counts.open + self.counts.open_filtered > 0
+            // Geschlossene Ports zeigen immerhin, dass der Host antwortet
+            || (show_closed && self.counts.closed > 0)
+    }
+
+    /// Adresse mit bekanntem Namen, z.B. `192.168.1.1 (router.local)`
+    fn label(&self) -> String {
+        match self.hostname.as_ref().or(self.reverse_dns.as_ref()) {
+            Some(name) => format!("{} ({})", self.ip, name),
+            None => self.ip.to_string(),
+        }
+    }
+}
+
+pub async fn handle_scan_command(target: &str, options: ScanOptions) {
+    // Fortschrittsmeldungen nur, wenn sie keine maschinenlesbare Ausgabe stören
+    let progress = options.format == OutputFormat::Text || options.output.is_some();
+    if progress {
+        println!("🔍 Starte Netzwerk-Scan für: {}", target);
+    }
+
+    if options.concurrency == 0 {
+        println!("❌ --concurrency muss mindestens 1 sein.");
+        std::process::exit(2);
+    }
+    if options.rate == Some(0) {
+        println!("❌ --rate muss größer als 0 sein.");
+        std::process::exit(2);
+    }
+
+    // Versuche das Ziel in eine Host-Liste aufzulösen
+    let hosts = match parse_target(target, options.family).await {
+        Ok(hosts) => hosts,
+        Err(e) => {
+            println!("❌ Fehler beim Parsen des Ziels '{}': {}", target, e);
+            std::process::exit(2);
+        }
+    };
+
+    if hosts.is_empty() {
+        println!("❌ Das Ziel '{}' enthält keine scanbaren Hosts.", target);
+        std::process::exit(2);
+    }
+
+    let ports = match resolve_ports(&options) {
+        Ok(ports) => ports,
+        Err(e) => {
+            println!("❌ Ungültige Port-Angabe: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if ports.is_empty() {
+        println!("❌ Nach Anwendung von --exclude-ports bleiben keine Ports übrig.");
+        std::process::exit(2);
+    }
+
+    let policy = match Policy::load(options.policy.as_deref()) {
+        Ok(policy) => policy,
+        Err(e) => {
+            println!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // Baseline vor dem Scan prüfen, damit ein Tippfehler im Pfad nicht erst
+    // nach einem langen Scan auffällt
+    let baseline = match options.compare.as_deref().map(load_baseline).transpose() {
+        Ok(Some(baseline)) if baseline.protocol != options.protocol() => {
+            eprintln!("❌ Die Baseline '{}' wurde mit einem anderen Protokoll erstellt ({}).",
+                      options.compare.as_deref().unwrap_or_default(), baseline.protocol.as_str());
+            std::process::exit(2);
+        }
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if progress {
+        print_resolved_hostnames(&hosts);
+    }
+
+    let started_at = chrono::Local::now();
+    let started = Instant::now();
+    let hosts_total = hosts.len();
+
+    // Bei mehreren Hosts zuerst die aktiven ermitteln, statt tote Adressen
+    // komplett durchzuscannen
+    let hosts = if options.discover_only || (hosts.len() > 1 && !options.no_discovery) {
+        if progress {
+            println!("🛰️  Host-Erkennung für {} Hosts (ICMP, TCP, ARP)...", hosts.len());
+        }
+        let discoveries = discover_hosts(&hosts, options.concurrency, options.timeout).await;
+        let live_hosts: Vec<ScanTarget> = hosts.into_iter()
+            .zip(discoveries)
+            .filter_map(|(mut host, discovery)| {
+                let discovery = discovery?;
+                host.mac = discovery.mac;
+                host.discovered_by = Some(discovery.method);
+                Some(host)
+            })
+            .collect();
+        if progress {
+            println!("✅ {} von {} Hosts sind aktiv ({:.2} s)\n",
+                     live_hosts.len(), hosts_total, started.elapsed().as_secs_f64());
+        }
+
+        if live_hosts.is_empty() && !options.discover_only {
+            let message = "🚫 Keine aktiven Hosts gefunden. Mit --no-discovery trotzdem alle Adressen scannen.";
+            if progress {
+                println!("{}", message);
+            } else {
+                eprintln!("{}", message);
+            }
+            // Mit --compare weitermachen, damit verschwundene Hosts als Abweichung gemeldet werden
+            if options.compare.is_none() {
+                return;
+            }
+        }
+        live_hosts
+    } else {
+        hosts
+    };
+
+    let mut results = if options.discover_only || hosts.is_empty() {
+        let mut results: Vec<HostResult> = hosts.iter().map(HostResult::new).collect();
+        if options.reverse_dns {
+            resolve_reverse_dns(&mut results, options.concurrency, true).await;
+        }
+        results
+    } else {
+        if progress {
+            if hosts.len() == 1 {
+                println!("📡 Scanne IP-Adresse: {}", hosts[0].ip);
+            } else {
+                println!("📡 Scanne {} Hosts", hosts.len());
+            }
+            let protocol_label = if options.udp { "UDP-" } else { "" };
+            print!("⏳ Führe {}Port-Scan über {} Ports durch (Parallelität: {}, Timeout: {} ms",
+                   protocol_label, ports.len(), options.concurrency, options.timeout.as_millis());
+            if let Some(rate) = options.rate {
+                print!(", max. {} Pakete/s", rate);
+            }
+            println!(")...\n");
+        }
+
+        let mut results = scan_ports(&hosts, &ports, &options).await;
+
+        if options.banners && !options.udp {
+            if progress {
+                println!("🏷️  Lese Banner der offenen Ports...\n");
+            }
+            grab_banners(&mut results, options.concurrency, options.timeout).await;
+        }
+
+        if options.tls && !options.udp {
+            if progress {
+                println!("🔐 Prüfe offene Ports auf TLS...\n");
+            }
+            inspect_tls(&mut results, options.concurrency, options.timeout).await;
+        }
+
+        if options.http && !options.udp {
+            if progress {
+                println!("🌐 Frage Web-Ports per HTTP ab...\n");
+            }
+            inspect_http(&mut results, options.concurrency, options.timeout).await;
+        }
+
+        if options.smb && !options.udp {
+            if progress {
+                println!("🗂️  Frage SMB-Ports ab...\n");
+            }
+            inspect_smb(&mut results, options.concurrency, options.timeout).await;
+        }
+
+        if options.reverse_dns {
+            resolve_reverse_dns(&mut results, options.concurrency, options.show_closed).await;
+        }
+        results
+    };
+
+    for result in &mut results {
+        let ip = result.ip;
+        for port in &mut result.ports {
+            if port.state == PortState::Open {
+                port.warnings = policy.evaluate(ip, port);
+            }
+        }
+    }
+
+    let report = ScanReport {
+        target: target.to_string(),
+        protocol: options.protocol(),
+        started_at: started_at.to_rfc3339(),
+        duration_seconds: started.elapsed().as_secs_f64(),
+        ports: if options.discover_only { String::new() } else { compact_ports(&ports) },
+        port_count: if options.discover_only { 0 } else { ports.len() },
+        hosts_total,
+        discover_only: options.discover_only,
+        hosts: results,
+    };
+
+    let mut failed = false;
+    let rendered = report.render(options.format, options.show_closed);
+    match &options.output {
+        Some(path) => match std::fs::write(path, rendered) {
+            Ok(()) => println!("💾 Bericht gespeichert: {}", path),
+            Err(e) => {
+                println!("❌ Fehler beim Schreiben von '{}': {}", path, e);
+                failed = true;
+            }
+        },
+        None => print!("{}", rendered),
+    }
+
+    let mut drift_found = false;
+    if let (Some(baseline), Some(path)) = (&baseline, &options.compare) {
+        let summary = describe_drift(baseline, &report, &ports, path);
+        drift_found = summary.is_some();
+        let text = summary.unwrap_or_else(|| "✅ Keine Abweichungen zur Baseline.\n".to_string());
+        // Bei JSON/CSV/XML auf stdout den Vergleich nach stderr schreiben
+        if progress {
+            print!("\n{}", text);
+        } else {
+            eprint!("{}", text);
+        }
+    }
+
+    if let Some(path) = &options.save_baseline {
+        match save_baseline(&report, path) {
+            Ok(()) if progress => println!("📐 Baseline gespeichert: {}", path),
+            Ok(()) => {}
+            Err(e) => {
+                println!("❌ {}", e);
+                failed = true;
+            }
+        }
+    }
+
+    // Für Cron-Jobs: Fehler und Abweichungen über den Exit-Code signalisieren
+    if failed {
+        std::process::exit(2);
+    }
+    if drift_found {
+        std::process::exit(1);
+    }
+}
+
+/// Beschreibt die Abweichungen zur Baseline, `None` wenn es keine gibt
+fn describe_drift(baseline: &ScanReport, current: &ScanReport, ports: &[u16], path: &str) -> Option<String> {
+    let drifts = compare_reports(baseline, current, ports);
+    if drifts.is_empty() {
+        return None;
+    }
+
+    let mut text = format!("📐 Vergleich mit Baseline {} (vom {}):\n", path, baseline.started_at);
+    for drift in &drifts {
+        text.push_str(&format!("  {}\n", drift.describe()));
+    }
+    text.push_str(&format!("⚠️  {} Abweichung(en) zur Baseline gefunden.\n", drifts.len()));
+    Some(text)
+}
+
+/// Zeigt, zu welchen Adressen die angegebenen Hostnamen aufgelöst wurden
+fn print_resolved_hostnames(hosts: &[ScanTarget]) {
+    let mut mappings: Vec<(&str, Vec<IpAddr>)> = Vec::new();
+    for host in hosts {
+        if let Some(hostname) = &host.hostname {
+            match mappings.iter_mut().find(|(name, _)| name == hostname) {
+                Some((_, ips)) => ips.push(host.ip),
+                None => mappings.push((hostname, vec![host.ip])),
+            }
+        }
+    }
+
+    for (hostname, ips) in mappings {
+        let ips: Vec<String> = ips.iter().map(IpAddr::to_string).collect();
+        println!("🔗 {} → {}", hostname, ips.join(", "));
+    }
+}
+
+/// Benennt gefundene Hosts über PTR-Einträge
+async fn resolve_reverse_dns(results: &mut [HostResult], concurrency: usize, show_closed: bool) {
+    let single_host = results.len() == 1;
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut lookups = JoinSet::new();
+
+    for (index, result) in results.iter().enumerate() {
+        // Bei Netzwerk-Scans nur Hosts auflösen, auf denen etwas gefunden wurde
+        if !single_host && !result.is_listed(show_closed) {
+            continue;
+        }
+        let ip = result.ip;
+        let semaphore = semaphore.clone();
+        lookups.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, reverse_lookup(ip).await)
+        });
+    }
+
+    while let Some(finished) = lookups.join_next().await {
+        if let Ok((index, name)) = finished {
+            results[index].reverse_dns = name;
+        }
+    }
+}
+
+/// Ermittelt die zu scannenden Ports aus `--ports` und `--exclude-ports`
+fn resolve_ports(options: &ScanOptions) -> Result<Vec<u16>, String> {
+    let protocol = options.protocol();
+    let mut ports = match &options.ports {
+        Some(spec) => parse_port_spec(spec, protocol)?,
+        None => parse_port_spec("common", protocol)?,
+    };
+
+    if let Some(spec) = &options.exclude_ports {
+        ports = exclude_ports(ports, spec, protocol)?;
+    }
+
+    Ok(ports)
+}
+
+//fn is_well_known_port(port: u16) -> bool {
+  //  match port {
+    //    1..=1023 => true,
+       // _ => false,
+    //}
+//}
\ No newline at end of file
@@ -0,0 +1,518 @@
+use netdev::ipnet::IpNet;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+use super::banners::version_below;
+use super::http::HttpInfo;
+use super::smb::SmbInfo;
+use super::tls::TlsInfo;
+use super::{PortResult, Protocol};
+
+/// Eingebettete Standard-Richtlinie (entspricht den bisherigen festen Hinweisen)
+const DEFAULT_POLICY: &str = include_str!("default_policy.toml");
+
+/// Schweregrad eines Befunds
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Low => "NIEDRIG",
+            Severity::Medium => "MITTEL",
+            Severity::High => "HOCH",
+            Severity::Critical => "KRITISCH",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ️ ",
+            Severity::Low => "🔵",
+            Severity::Medium => "🟡",
+            Severity::High => "🟠",
+            Severity::Critical => "🔴",
+        }
+    }
+}
+
+/// Ein Sicherheitshinweis, den eine Regel für einen Port erzeugt hat
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// ID der auslösenden Regel, z.B. "telnet"
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    pub remediation: Option<String>,
+}
+
+/// Aufbau einer Richtlinien-Datei
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    /// Standard-Regeln übernehmen und nur ergänzen/überschreiben
+    #[serde(default = "default_true")]
+    include_default: bool,
+    /// IDs von Regeln, die nicht angewendet werden sollen
+    #[serde(default)]
+    disable: Vec<String>,
+    #[serde(default)]
+    rule: Vec<RuleConfig>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Eine Regel, wie sie in der Richtlinien-Datei steht
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    id: String,
+    severity: Severity,
+    message: String,
+    remediation: Option<String>,
+
+    ports: Option<Vec<u16>>,
+    protocol: Option<Protocol>,
+    service: Option<String>,
+    product: Option<String>,
+    /// Zielnetze in CIDR-Schreibweise, z.B. ["10.0.0.0/8"]
+    network: Option<Vec<String>>,
+
+    version_below: Option<String>,
+    version_at_least: Option<String>,
+    version_regex: Option<String>,
+    banner_regex: Option<String>,
+    extra_regex: Option<String>,
+
+    tls_expired: Option<bool>,
+    tls_expires_within_days: Option<i32>,
+    tls_self_signed: Option<bool>,
+    tls_signature_regex: Option<String>,
+    tls_key_type: Option<String>,
+    tls_key_bits_below: Option<u32>,
+    tls_legacy_protocols: Option<bool>,
+
+    http_admin_panel: Option<bool>,
+    http_missing_headers: Option<bool>,
+    http_server_regex: Option<String>,
+    http_powered_by_regex: Option<String>,
+    http_title_regex: Option<String>,
+
+    smb_v1: Option<bool>,
+    smb_signing_required: Option<bool>,
+}
+
+/// Regel mit vorab kompilierten Regex-Ausdrücken und Netzen
+struct Rule {
+    config: RuleConfig,
+    networks: Vec<IpNet>,
+    version_below: Option<Vec<u32>>,
+    version_at_least: Option<Vec<u32>>,
+    version_regex: Option<Regex>,
+    banner_regex: Option<Regex>,
+    extra_regex: Option<Regex>,
+    tls_signature_regex: Option<Regex>,
+    http_server_regex: Option<Regex>,
+    http_powered_by_regex: Option<Regex>,
+    http_title_regex: Option<Regex>,
+}
+
+/// Geladene Sicherheitsrichtlinie
+pub struct Policy {
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    /// Lädt die Standard-Richtlinie und wendet optional eine kundenspezifische
+    /// Richtlinie an. Regeln mit gleicher ID ersetzen die Standard-Regel.
+    pub fn load(path: Option<&str>) -> Result<Policy, String> {
+        let content = path
+            .map(|path| std::fs::read_to_string(path).map_err(|e| format!("Fehler beim Lesen von '{}': {}", path, e)))
+            .transpose()?;
+        Policy::build(content.as_deref(), path.unwrap_or_default())
+    }
+
+    /// Wie `load`, aber mit dem Inhalt der kundenspezifischen Richtlinie
+    fn build(custom: Option<&str>, source: &str) -> Result<Policy, String> {
+        let mut configs = parse_policy(DEFAULT_POLICY, "Standard-Richtlinie")?.rule;
+
+        if let Some(content) = custom {
+            let custom = parse_policy(content, source)?;
+
+            if !custom.include_default {
+                configs.clear();
+            }
+            configs.retain(|rule| !custom.disable.contains(&rule.id));
+            for rule in custom.rule {
+                match configs.iter_mut().find(|existing| existing.id == rule.id) {
+                    Some(existing) => *existing = rule,
+                    None => configs.push(rule),
+                }
+            }
+        }
+
+        let rules = configs.into_iter().map(Rule::compile).collect::<Result<_, _>>()?;
+        Ok(Policy { rules })
+    }
+
+    /// Wendet alle Regeln auf einen offenen Port an, schwerste Befunde zuerst
+    pub fn evaluate(&self, ip: IpAddr, port: &PortResult) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self.rules.iter()
+            .filter(|rule| rule.matches(ip, port))
+            .map(|rule| Finding {
+                rule: rule.config.id.clone(),
+                severity: rule.config.severity,
+                message: fill_placeholders(&rule.config.message, port),
+                remediation: rule.config.remediation.as_deref().map(|text| fill_placeholders(text, port)),
+            })
+            .collect();
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+        findings
+    }
+}
+
+fn parse_policy(content: &str, source: &str) -> Result<PolicyFile, String> {
+    toml::from_str(content).map_err(|e| format!("Ungültige Richtlinie '{}': {}", source, e))
+}
+
+impl Rule {
+    fn compile(config: RuleConfig) -> Result<Rule, String> {
+        let id = config.id.clone();
+        let regex = |pattern: &Option<String>| -> Result<Option<Regex>, String> {
+            pattern.as_deref()
+                .map(|pattern| Regex::new(pattern).map_err(|e| format!("Regel '{}': ungültiger Regex '{}': {}", id, pattern, e)))
+                .transpose()
+        };
+        let version = |value: &Option<String>| -> Result<Option<Vec<u32>>, String> {
+            value.as_deref()
+                .map(|value| {
+                    value.split('.')
+                        .map(|part| part.trim().parse::<u32>())
+                        .collect::<Result<Vec<u32>, _>>()
+                        .map_err(|_| format!("Regel '{}': ungültige Version '{}'", id, value))
+                })
+                .transpose()
+        };
+
+        let networks = config.network.iter().flatten()
+            .map(|network| {
+                network.parse::<IpNet>()
+                    .or_else(|_| network.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| format!("Regel '{}': ungültiges Netz '{}'", id, network))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Rule {
+            networks,
+            version_below: version(&config.version_below)?,
+            version_at_least: version(&config.version_at_least)?,
+            version_regex: regex(&config.version_regex)?,
+            banner_regex: regex(&config.banner_regex)?,
+            extra_regex: regex(&config.extra_regex)?,
+            tls_signature_regex: regex(&config.tls_signature_regex)?,
+            http_server_regex: regex(&config.http_server_regex)?,
+            http_powered_by_regex: regex(&config.http_powered_by_regex)?,
+            http_title_regex: regex(&config.http_title_regex)?,
+            config,
+        })
+    }
+
+    /// Alle angegebenen Bedingungen müssen zutreffen
+    fn matches(&self, ip: IpAddr, port: &PortResult) -> bool {
+        let config = &self.config;
+        let info = port.service.as_ref();
+        let product = info.and_then(|info| info.product.as_deref());
+        let version = info.and_then(|info| info.version.as_deref());
+        let banner = info.and_then(|info| info.banner.as_deref());
+        let extra = info.and_then(|info| info.extra_info.as_deref());
+
+        if config.ports.as_ref().is_some_and(|ports| !ports.contains(&port.port))
+            || config.protocol.is_some_and(|protocol| protocol != port.protocol)
+            || config.service.as_ref().is_some_and(|service| !service.eq_ignore_ascii_case(port.service_name()))
+            || config.product.as_ref().is_some_and(|expected| product != Some(expected.as_str()))
+            || (!self.networks.is_empty() && !self.networks.iter().any(|network| network.contains(&ip)))
+        {
+            return false;
+        }
+
+        // Versions-Bedingungen greifen nur, wenn eine Version erkannt wurde
+        if let Some(minimum) = &self.version_below {
+            if !version.is_some_and(|version| version_below(version, minimum)) {
+                return false;
+            }
+        }
+        if let Some(minimum) = &self.version_at_least {
+            let known = |version: &str| version.starts_with(|c: char| c.is_ascii_digit());
+            if !version.is_some_and(|version| known(version) && !version_below(version, minimum)) {
+                return false;
+            }
+        }
+        if !regex_matches(&self.version_regex, version)
+            || !regex_matches(&self.banner_regex, banner)
+            || !regex_matches(&self.extra_regex, extra)
+        {
+            return false;
+        }
+
+        if self.has_tls_conditions() {
+            let Some(tls) = &port.tls else {
+                return false;
+            };
+            if !self.matches_tls(tls) {
+                return false;
+            }
+        }
+        if self.has_http_conditions() {
+            let Some(http) = &port.http else {
+                return false;
+            };
+            if !self.matches_http(http) {
+                return false;
+            }
+        }
+        if self.has_smb_conditions() {
+            let Some(smb) = &port.smb else {
+                return false;
+            };
+            return self.matches_smb(smb);
+        }
+        true
+    }
+
+    fn has_tls_conditions(&self) -> bool {
+        let config = &self.config;
+        config.tls_expired.is_some()
+            || config.tls_expires_within_days.is_some()
+            || config.tls_self_signed.is_some()
+            || config.tls_signature_regex.is_some()
+            || config.tls_key_type.is_some()
+            || config.tls_key_bits_below.is_some()
+            || config.tls_legacy_protocols.is_some()
+    }
+
+    fn has_http_conditions(&self) -> bool {
+        let config = &self.config;
+        config.http_admin_panel.is_some()
+            || config.http_missing_headers.is_some()
+            || config.http_server_regex.is_some()
+            || config.http_powered_by_regex.is_some()
+            || config.http_title_regex.is_some()
+    }
+
+    fn matches_http(&self, http: &HttpInfo) -> bool {
+        let config = &self.config;
+        config.http_admin_panel.is_none_or(|expected| http.admin_panels.is_empty() != expected)
+            && config.http_missing_headers.is_none_or(|expected| http.missing_headers.is_empty() != expected)
+            && regex_matches(&self.http_server_regex, http.server.as_deref())
+            && regex_matches(&self.http_powered_by_regex, http.powered_by.as_deref())
+            && regex_matches(&self.http_title_regex, http.title.as_deref())
+    }
+
+    fn has_smb_conditions(&self) -> bool {
+        self.config.smb_v1.is_some() || self.config.smb_signing_required.is_some()
+    }
+
+    fn matches_smb(&self, smb: &SmbInfo) -> bool {
+        let config = &self.config;
+        config.smb_v1.is_none_or(|expected| smb.smb1 == expected)
+            && config.smb_signing_required.is_none_or(|expected| smb.signing_required == expected)
+    }
+
+    fn matches_tls(&self, tls: &TlsInfo) -> bool {
+        let config = &self.config;
+        config.tls_expired.is_none_or(|expected| (tls.days_until_expiry < 0) == expected)
+            && config.tls_expires_within_days
+                .is_none_or(|days| (0..days).contains(&tls.days_until_expiry))
+            && config.tls_self_signed.is_none_or(|expected| tls.self_signed == expected)
+            && regex_matches(&self.tls_signature_regex, Some(&tls.signature_algorithm))
+            && config.tls_key_type.as_ref().is_none_or(|key_type| key_type.eq_ignore_ascii_case(&tls.key_type))
+            && config.tls_key_bits_below.is_none_or(|bits| tls.key_bits < bits)
+            && config.tls_legacy_protocols.is_none_or(|expected| legacy_protocols(tls).is_empty() != expected)
+    }
+}
+
+fn regex_matches(regex: &Option<Regex>, value: Option<&str>) -> bool {
+    match regex {
+        Some(regex) => value.is_some_and(|value| regex.is_match(value)),
+        None => true,
+    }
+}
+
+/// Akzeptierte veraltete Protokolle inkl. eines ausgehandelten TLS 1.0/1.1
+fn legacy_protocols(tls: &TlsInfo) -> Vec<String> {
+    let mut legacy = tls.legacy_protocols.clone();
+    if matches!(tls.protocol.as_str(), "TLSv1" | "TLSv1.1") && legacy.is_empty() {
+        legacy.push(tls.protocol.clone());
+    }
+    legacy
+}
+
+/// Ersetzt Platzhalter wie `{version}` durch Werte des Ports
+fn fill_placeholders(template: &str, port: &PortResult) -> String {
+    if !template.contains('{') {
+        return template.to_string();
+    }
+
+    let info = port.service.as_ref();
+    let text_of = |value: Option<&String>| value.cloned().unwrap_or_default();
+    let mut values = vec![
+        ("port", port.port.to_string()),
+        ("service", port.service_name().to_string()),
+        ("product", text_of(info.and_then(|info| info.product.as_ref()))),
+        ("version", text_of(info.and_then(|info| info.version.as_ref()))),
+        ("banner", text_of(info.and_then(|info| info.banner.as_ref()))),
+    ];
+    if let Some(tls) = &port.tls {
+        values.extend([
+            ("days", tls.days_until_expiry.to_string()),
+            ("expired_days", (-tls.days_until_expiry).to_string()),
+            ("key_type", tls.key_type.clone()),
+            ("key_bits", tls.key_bits.to_string()),
+            ("signature", tls.signature_algorithm.clone()),
+            ("protocol", tls.protocol.clone()),
+            ("legacy", legacy_protocols(tls).join(", ")),
+        ]);
+    }
+    if let Some(http) = &port.http {
+        values.extend([
+            ("http_status", http.status.to_string()),
+            ("http_server", http.server.clone().unwrap_or_default()),
+            ("powered_by", http.powered_by.clone().unwrap_or_default()),
+            ("title", http.title.clone().unwrap_or_default()),
+            ("admin_panels", http.admin_panels.join(", ")),
+            ("missing_headers", http.missing_headers.join(", ")),
+        ]);
+    }
+    if let Some(smb) = &port.smb {
+        values.extend([
+            ("smb_dialect", smb.dialect.clone().unwrap_or_default()),
+            ("smb_os", smb.os.clone().unwrap_or_default()),
+        ]);
+    }
+
+    values.into_iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{}}}", key), &value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn port(port: u16, protocol: &str, service: Option<serde_json::Value>) -> PortResult {
+        serde_json::from_value(json!({
+            "port": port,
+            "protocol": protocol,
+            "state": "open",
+            "service": service,
+            "warnings": [],
+        }))
+        .unwrap()
+    }
+
+    fn ssh(version: &str) -> PortResult {
+        port(22, "tcp", Some(json!({ "service": "SSH", "product": "OpenSSH", "version": version })))
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<&str> {
+        findings.iter().map(|finding| finding.rule.as_str()).collect()
+    }
+
+    fn local() -> IpAddr {
+        IpAddr::from([192, 0, 2, 1])
+    }
+
+    #[test]
+    fn port_rules_ignore_detected_service_and_protocol() {
+        let policy = Policy::build(None, "").unwrap();
+        let unknown = port(23, "tcp", Some(json!({ "service": "Unbekannt" })));
+        assert_eq!(rules(&policy.evaluate(local(), &unknown)), vec!["telnet"]);
+        assert!(policy.evaluate(local(), &port(23, "udp", None)).is_empty());
+        assert!(policy.evaluate(local(), &port(2323, "tcp", Some(json!({ "service": "Telnet" })))).is_empty());
+        assert_eq!(rules(&policy.evaluate(local(), &port(161, "udp", None))), vec!["snmp-public"]);
+    }
+
+    #[test]
+    fn version_rules_need_a_detected_version() {
+        let policy = Policy::build(None, "").unwrap();
+        assert_eq!(rules(&policy.evaluate(local(), &ssh("7.4"))), vec!["openssh-outdated"]);
+        assert_eq!(rules(&policy.evaluate(local(), &ssh("9.6p1"))), vec!["openssh-regresshion"]);
+        assert!(policy.evaluate(local(), &ssh("9.8")).is_empty());
+        assert!(policy.evaluate(local(), &port(22, "tcp", None)).is_empty());
+    }
+
+    #[test]
+    fn custom_rules_replace_disable_and_sort_by_severity() {
+        let custom = r#"
+            disable = ["openssh-outdated"]
+
+            [[rule]]
+            id = "telnet"
+            ports = [23]
+            severity = "low"
+            message = "Telnet auf Port {port}"
+
+            [[rule]]
+            id = "ssh-intern"
+            service = "ssh"
+            network = ["192.0.2.0/24"]
+            severity = "info"
+            message = "{product} {version} im internen Netz"
+
+            [[rule]]
+            id = "alt-ssh"
+            severity = "critical"
+            version_regex = "^7\\."
+            message = "Version {version}"
+        "#;
+        let policy = Policy::build(Some(custom), "test.toml").unwrap();
+
+        let findings = policy.evaluate(local(), &ssh("7.4"));
+        assert_eq!(rules(&findings), vec!["alt-ssh", "ssh-intern"]);
+        assert_eq!(findings[1].message, "OpenSSH 7.4 im internen Netz");
+        assert!(rules(&policy.evaluate(IpAddr::from([198, 51, 100, 1]), &ssh("9.8"))).is_empty());
+
+        let telnet = policy.evaluate(local(), &port(23, "udp", None));
+        assert_eq!(rules(&telnet), vec!["telnet"]);
+        assert_eq!(telnet[0].message, "Telnet auf Port 23");
+    }
+
+    #[test]
+    fn without_defaults_only_custom_rules_apply() {
+        let custom = r#"
+            include_default = false
+
+            [[rule]]
+            id = "web"
+            ports = [80, 8080]
+            protocol = "tcp"
+            severity = "info"
+            message = "Web"
+        "#;
+        let policy = Policy::build(Some(custom), "test.toml").unwrap();
+        assert!(policy.evaluate(local(), &port(23, "tcp", None)).is_empty());
+        assert_eq!(rules(&policy.evaluate(local(), &port(8080, "tcp", None))), vec!["web"]);
+    }
+
+    #[test]
+    fn invalid_policies_are_rejected() {
+        let rule = |condition: &str| format!("[[rule]]\nid = \"x\"\nseverity = \"low\"\nmessage = \"x\"\n{}", condition);
+        assert!(Policy::build(Some(&rule("")), "test.toml").is_ok());
+        assert!(Policy::build(Some(&rule("banner_regex = \"(\"")), "test.toml").is_err());
+        assert!(Policy::build(Some(&rule("version_below = \"8.x\"")), "test.toml").is_err());
+        assert!(Policy::build(Some(&rule("network = [\"10.0.0.0/33\"]")), "test.toml").is_err());
+        assert!(Policy::build(Some(&rule("unbekannt = true")), "test.toml").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use super::{text, xml, HostResult, Protocol};
+use crate::csv::escape_csv;
+
+/// Ausgabeformat des Scan-Berichts
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Lesbare Ausgabe mit Symbolen (Standard)
+    Text,
+    Json,
+    /// Eine Zeile pro Port, z.B. für Tabellenkalkulationen
+    Csv,
+    /// Nmap-kompatibles XML
+    Xml,
+}
+
+/// Strukturiertes Ergebnis eines Scans, Grundlage für alle Ausgabeformate
+#[derive(Serialize, Deserialize)]
+pub struct ScanReport {
+    /// Ziel wie auf der Kommandozeile angegeben
+    pub target: String,
+    pub protocol: Protocol,
+    /// Startzeitpunkt im Format RFC 3339
+    pub started_at: String,
+    pub duration_seconds: f64,
+    /// Gescannte Ports in Kurzform, z.B. "1-1024,8080"
+    pub ports: String,
+    pub port_count: usize,
+    /// Anzahl der Adressen im Ziel (vor der Host-Erkennung)
+    pub hosts_total: usize,
+    /// Nur Host-Erkennung, kein Port-Scan (`--discover-only`)
+    pub discover_only: bool,
+    pub hosts: Vec<HostResult>,
+}
+
+impl ScanReport {
+    pub fn render(&self, format: OutputFormat, show_closed: bool) -> String {
+        match format {
+            OutputFormat::Text => text::render_text(self, show_closed),
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Csv => self.to_csv(),
+            OutputFormat::Xml => xml::render_xml(self),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self)
+            .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e));
+        json.push('\n');
+        json
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "ip,hostname,reverse_dns,mac,vendor,port,protocol,state,service,product,version,banner,tls_protocol,tls_expiry_days,http_status,http_server,http_title,warnings\n"
+        );
+
+        for host in &self.hosts {
+            let host_fields = [
+                host.ip.to_string(),
+                host.hostname.clone().unwrap_or_default(),
+                host.reverse_dns.clone().unwrap_or_default(),
+                host.mac.clone().unwrap_or_default(),
+                host.vendor.clone().unwrap_or_default(),
+            ];
+
+            // Hosts ohne gelistete Ports erscheinen trotzdem (z.B. für Inventar-Skripte)
+            if host.ports.is_empty() {
+                let mut row: Vec<String> = host_fields.to_vec();
+                row.resize(18, String::new());
+                push_csv_row(&mut csv, &row);
+                continue;
+            }
+
+            for port in &host.ports {
+                let detected = port.service.as_ref();
+                let mut row: Vec<String> = host_fields.to_vec();
+                row.extend([
+                    port.port.to_string(),
+                    port.protocol.as_str().to_string(),
+                    port.state.as_str().to_string(),
+                    port.service_name().to_string(),
+                    detected.and_then(|info| info.product.clone()).unwrap_or_default(),
+                    detected.and_then(|info| info.version.clone()).unwrap_or_default(),
+                    detected.and_then(|info| info.banner.clone()).unwrap_or_default(),
+                    port.tls.as_ref().map(|tls| tls.protocol.clone()).unwrap_or_default(),
+                    port.tls.as_ref().map(|tls| tls.days_until_expiry.to_string()).unwrap_or_default(),
+                    port.http.as_ref().map(|http| http.status.to_string()).unwrap_or_default(),
+                    port.http.as_ref().and_then(|http| http.server.clone()).unwrap_or_default(),
+                    port.http.as_ref().and_then(|http| http.title.clone()).unwrap_or_default(),
+                    port.warnings.iter()
+                        .map(|finding| format!("[{}] {}", finding.severity.label(), finding.message))
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                ]);
+                push_csv_row(&mut csv, &row);
+            }
+        }
+        csv
+    }
+}
+
+fn push_csv_row(csv: &mut String, fields: &[String]) {
+    let escaped: Vec<String> = fields.iter().map(|field| escape_csv(field)).collect();
+    csv.push_str(&escaped.join(","));
+    csv.push('\n');
+}
+
+/// Fasst eine sortierte Port-Liste zu Bereichen zusammen, z.B. "1-1024,8080"
+pub fn compact_ports(ports: &[u16]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = ports.iter().copied().peekable();
+
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end.wrapping_add(1))) && end != u16::MAX {
+            end = iter.next().unwrap_or(end);
+        }
+        if start == end {
+            ranges.push(start.to_string());
+        } else {
+            ranges.push(format!("{}-{}", start, end));
+        }
+    }
+    ranges.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report() -> ScanReport {
+        serde_json::from_value(json!({
+            "target": "192.0.2.0/24",
+            "protocol": "tcp",
+            "started_at": "2026-01-01T00:00:00Z",
+            "duration_seconds": 2.5,
+            "ports": "22,80",
+            "port_count": 2,
+            "hosts_total": 254,
+            "discover_only": false,
+            "hosts": [
+                {
+                    "ip": "192.0.2.10",
+                    "hostname": "nas.local",
+                    "mac": "00:11:32:0a:0b:0c",
+                    "vendor": "Synology",
+                    "ports": [
+                        {
+                            "port": 22,
+                            "protocol": "tcp",
+                            "state": "open",
+                            "service": { "service": "SSH", "product": "OpenSSH", "version": "8.9p1",
+                                         "banner": "SSH-2.0-OpenSSH_8.9p1 Ubuntu-3, \"jammy\"" },
+                            "warnings": [],
+                        },
+                        {
+                            "port": 80,
+                            "protocol": "tcp",
+                            "state": "open",
+                            "warnings": [
+                                { "rule": "http", "severity": "medium", "message": "Unverschlüsseltes HTTP" },
+                                { "rule": "admin", "severity": "high", "message": "Admin-Oberfläche" },
+                            ],
+                        },
+                    ],
+                    "counts": { "open": 2, "open_filtered": 0, "closed": 0, "filtered": 0, "unreachable": 0 },
+                },
+                {
+                    "ip": "192.0.2.20",
+                    "ports": [],
+                    "counts": { "open": 0, "open_filtered": 0, "closed": 2, "filtered": 0, "unreachable": 0 },
+                },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn compacts_port_ranges() {
+        assert_eq!(compact_ports(&[]), "");
+        assert_eq!(compact_ports(&[80]), "80");
+        assert_eq!(compact_ports(&[1, 2, 3, 5, 8080, 8081]), "1-3,5,8080-8081");
+        assert_eq!(compact_ports(&[22, 80, 443]), "22,80,443");
+        assert_eq!(compact_ports(&[1, 65534, 65535]), "1,65534-65535");
+        assert_eq!(compact_ports(&[65535]), "65535");
+    }
+
+    #[test]
+    fn renders_csv_rows() {
+        let csv = report().render(OutputFormat::Csv, false);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("ip,hostname,reverse_dns,mac,vendor,port,protocol,state,service,"));
+        assert_eq!(
+            lines[1],
+            "192.0.2.10,nas.local,,00:11:32:0a:0b:0c,Synology,22,tcp,open,SSH,OpenSSH,8.9p1,\
+             \"SSH-2.0-OpenSSH_8.9p1 Ubuntu-3, \"\"jammy\"\"\",,,,,,"
+        );
+        assert_eq!(
+            lines[2],
+            "192.0.2.10,nas.local,,00:11:32:0a:0b:0c,Synology,80,tcp,open,HTTP,,,,,,,,,\
+             [MITTEL] Unverschlüsseltes HTTP; [HOCH] Admin-Oberfläche"
+        );
+        // Host ohne gelistete Ports: eine Zeile mit leeren Port-Spalten
+        assert_eq!(lines[3], format!("192.0.2.20{}", ",".repeat(17)));
+        assert!(lines.iter().all(|line| line.matches(',').count() >= 17));
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+use super::targets::ScanTarget;
+use super::udp::probe_udp_port;
+use super::{HostResult, PortResult, PortState, Protocol, ScanOptions};
+
+/// Verteilt Verbindungsversuche gleichmäßig, damit `--rate` eingehalten wird
+struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / per_second,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+pub async fn scan_ports(hosts: &[ScanTarget], ports: &[u16], options: &ScanOptions) -> Vec<HostResult> {
+    let mut results: Vec<HostResult> = hosts.iter().map(HostResult::new).collect();
+
+    let semaphore = Arc::new(Semaphore::new(options.concurrency));
+    let limiter = options.rate.map(|rate| Arc::new(RateLimiter::new(rate)));
+    let timeout = options.timeout;
+    let protocol = options.protocol();
+    let show_closed = options.show_closed;
+    let mut tasks = JoinSet::new();
+
+    for (host_index, host) in hosts.iter().enumerate() {
+        let host = host.ip;
+        for &port in ports {
+            // Erst auf einen freien Slot warten, damit nie mehr als
+            // `concurrency` Tasks gleichzeitig existieren
+            let permit = semaphore.clone().acquire_owned().await
+                .expect("Semaphore wird nie geschlossen");
+            let limiter = limiter.clone();
+
+            tasks.spawn(async move {
+                if let Some(limiter) = limiter {
+                    limiter.acquire().await;
+                }
+                let addr = SocketAddr::new(host, port);
+                let (state, service) = match protocol {
+                    Protocol::Tcp => (probe_tcp_port(addr, timeout).await, None),
+                    Protocol::Udp => probe_udp_port(addr, timeout).await,
+                };
+                drop(permit);
+                (host_index, PortResult { port, protocol, state, service, tls: None, http: None, smb: None, warnings: Vec::new() })
+            });
+
+            while let Some(finished) = tasks.try_join_next() {
+                collect_probe_result(&mut results, finished, show_closed);
+            }
+        }
+    }
+
+    while let Some(finished) = tasks.join_next().await {
+        collect_probe_result(&mut results, finished, show_closed);
+    }
+
+    for result in &mut results {
+        result.ports.sort_unstable_by_key(|port| port.port);
+    }
+    results
+}
+
+fn collect_probe_result(
+    results: &mut [HostResult],
+    finished: Result<(usize, PortResult), tokio::task::JoinError>,
+    show_closed: bool,
+) {
+    match finished {
+        Ok((host_index, port)) => {
+            let result = &mut results[host_index];
+            result.counts.add(port.state);
+            // Geschlossene, gefilterte und unerreichbare Ports nur auf Wunsch
+            // behalten, um die Ausgabe sauber zu halten
+            if show_closed || matches!(port.state, PortState::Open | PortState::OpenFiltered) {
+                result.ports.push(port);
+            }
+        }
+        // stderr, damit JSON/CSV/XML auf stdout parsebar bleibt
+        Err(e) => eprintln!("⚠️  Scan-Task abgebrochen: {}", e),
+    }
+}
+
+async fn probe_tcp_port(socket_addr: SocketAddr, timeout: Duration) -> PortState {
+    match tokio::time::timeout(timeout, TcpStream::connect(socket_addr)).await {
+        Ok(Ok(_)) => PortState::Open,
+        // RST: Host ist erreichbar, aber auf dem Port lauscht nichts
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => PortState::Closed,
+        // ICMP Host/Network Unreachable bzw. keine Route zum Ziel
+        Ok(Err(e)) if is_unreachable(&e) => PortState::Unreachable,
+        // Keine Antwort oder sonstiger Fehler: ein Paketfilter verwirft die Anfrage
+        _ => PortState::Filtered,
+    }
+}
+
+/// Erkennt Fehler, die auf ein nicht erreichbares Ziel hindeuten
+pub fn is_unreachable(error: &std::io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::HostUnreachable | ErrorKind::NetworkUnreachable)
+}
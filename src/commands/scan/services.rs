@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::Protocol;
+
+/// Eingebettete Dienst-Tabelle (Port/Protokoll -> Anzeigename)
+const SERVICES: &str = include_str!("services.txt");
+
+fn service_table() -> &'static HashMap<(u16, &'static str), &'static str> {
+    static TABLE: OnceLock<HashMap<(u16, &'static str), &'static str>> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        SERVICES.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (port_proto, name) = line.split_once(char::is_whitespace)?;
+                let (port, proto) = port_proto.split_once('/')?;
+                Some(((port.parse().ok()?, proto), name.trim()))
+            })
+            .collect()
+    })
+}
+
+/// Dienst-Datenbank des Systems (`/etc/services`). Viele Distributionen
+/// (z.B. Fedora, Arch) liefern hier die vollständige IANA-Registry mit.
+fn system_table() -> &'static HashMap<(u16, &'static str), String> {
+    static TABLE: OnceLock<HashMap<(u16, &'static str), String>> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        std::fs::read_to_string("/etc/services")
+            .map(|content| parse_system_services(&content))
+            .unwrap_or_default()
+    })
+}
+
+/// Liest Einträge im Format `<Name> <Port>/<Protokoll> [Aliase] [# Kommentar]`.
+/// Bei mehrfach vergebenen Ports gilt der erste Eintrag.
+fn parse_system_services(content: &str) -> HashMap<(u16, &'static str), String> {
+    let mut table = HashMap::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (fields.next(), fields.next()) else { continue };
+        let Some((port, proto)) = port_proto.split_once('/') else { continue };
+        let proto = match proto.to_ascii_lowercase().as_str() {
+            "tcp" => "tcp",
+            "udp" => "udp",
+            _ => continue,
+        };
+        if let Ok(port) = port.parse::<u16>() {
+            table.entry((port, proto)).or_insert_with(|| name.to_string());
+        }
+    }
+    table
+}
+
+/// Anzeigename eines Ports: eingebettete Tabelle, sonst die Dienst-Datenbank
+/// des Systems
+pub fn get_service_name(port: u16, protocol: Protocol) -> &'static str {
+    let key = (port, protocol.as_str());
+    service_table()
+        .get(&key)
+        .copied()
+        .or_else(|| system_table().get(&key).map(String::as_str))
+        .unwrap_or("Unbekannt")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_names_take_precedence() {
+        assert_eq!(get_service_name(22, Protocol::Tcp), "SSH");
+        assert_eq!(get_service_name(161, Protocol::Udp), "SNMP");
+        assert_eq!(get_service_name(0, Protocol::Tcp), "Unbekannt");
+    }
+
+    #[test]
+    fn parses_system_services_file() {
+        let table = parse_system_services("\
+# Kommentar
+tcpmux          1/tcp                           # TCP port service multiplexer
+echo            7/tcp
+echo            7/udp
+sieve           4190/tcp    managesieve
+ddp             2/ddp
+zweiter         7/tcp
+kaputt          x/tcp
+");
+        assert_eq!(table.len(), 4);
+        assert_eq!(table[&(1, "tcp")], "tcpmux");
+        assert_eq!(table[&(7, "tcp")], "echo");
+        assert_eq!(table[&(7, "udp")], "echo");
+        assert_eq!(table[&(4190, "tcp")], "sieve");
+    }
+}
@@ -0,0 +1,612 @@
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::udp::netbios_node_status;
+use super::{HostResult, PortResult, PortState, Protocol};
+
+/// Angebotene SMB2/3-Dialekte (2.0.2 bis 3.1.1)
+const SMB2_DIALECTS: [u16; 5] = [0x0202, 0x0210, 0x0300, 0x0302, 0x0311];
+/// OID von NTLMSSP (1.3.6.1.4.1.311.2.2.10) für SPNEGO
+const NTLMSSP_OID: [u8; 10] = [0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x02, 0x02, 0x0a];
+/// OID von SPNEGO (1.3.6.1.5.5.2)
+const SPNEGO_OID: [u8; 6] = [0x2b, 0x06, 0x01, 0x05, 0x05, 0x02];
+/// Größte akzeptierte SMB-Nachricht
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// Ergebnis der SMB-Abfrage eines Ports
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SmbInfo {
+    /// Höchster ausgehandelter Dialekt, z.B. "3.1.1" oder "NT LM 0.12" (SMBv1)
+    pub dialect: Option<String>,
+    /// Server akzeptiert noch SMBv1
+    pub smb1: bool,
+    pub signing_enabled: bool,
+    pub signing_required: bool,
+    pub netbios_name: Option<String>,
+    /// Arbeitsgruppe bzw. NetBIOS-Domäne
+    pub workgroup: Option<String>,
+    pub dns_name: Option<String>,
+    pub dns_domain: Option<String>,
+    /// Betriebssystem laut NTLMSSP-Version, z.B. "Windows 10.0 Build 19041"
+    pub os: Option<String>,
+}
+
+/// Ergebnis einer Sitzungseinrichtung per NTLMSSP
+#[derive(Default)]
+struct NtlmChallenge {
+    netbios_name: Option<String>,
+    workgroup: Option<String>,
+    dns_name: Option<String>,
+    dns_domain: Option<String>,
+    os: Option<String>,
+}
+
+/// Fragt offene SMB-Ports (139/445) nach Dialekt, Signing und Namen ab
+pub async fn inspect_smb(results: &mut [HostResult], concurrency: usize, timeout: Duration) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (host_index, result) in results.iter().enumerate() {
+        for (port_index, port) in result.ports.iter().enumerate() {
+            if !is_smb_port(port) {
+                continue;
+            }
+            let addr = SocketAddr::new(result.ip, port.port);
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                (host_index, port_index, inspect_endpoint(addr, timeout).await)
+            });
+        }
+    }
+
+    while let Some(finished) = tasks.join_next().await {
+        if let Ok((host_index, port_index, info)) = finished {
+            results[host_index].ports[port_index].smb = info;
+        }
+    }
+}
+
+fn is_smb_port(port: &PortResult) -> bool {
+    port.protocol == Protocol::Tcp
+        && port.state == PortState::Open
+        && (matches!(port.port, 139 | 445) || port.service_name() == "SMB")
+}
+
+async fn inspect_endpoint(addr: SocketAddr, timeout: Duration) -> Option<SmbInfo> {
+    // SMBv1 getrennt prüfen: Server ohne SMBv1 trennen oft die Verbindung
+    let smb1 = negotiate_smb1(addr, timeout).await;
+    let smb2 = negotiate_smb2(addr, timeout).await;
+
+    let mut info = match (smb2, smb1) {
+        (Some((dialect, security_mode, challenge)), smb1) => {
+            let challenge = challenge.unwrap_or_default();
+            SmbInfo {
+                dialect: Some(dialect_name(dialect)),
+                smb1: smb1.is_some(),
+                signing_enabled: security_mode & 0x01 != 0,
+                signing_required: security_mode & 0x02 != 0,
+                netbios_name: challenge.netbios_name,
+                workgroup: challenge.workgroup,
+                dns_name: challenge.dns_name,
+                dns_domain: challenge.dns_domain,
+                os: challenge.os,
+            }
+        }
+        // Nur SMBv1 (z.B. Windows XP/Server 2003)
+        (None, Some(security_mode)) => SmbInfo {
+            dialect: Some("NT LM 0.12".to_string()),
+            smb1: true,
+            signing_enabled: security_mode & 0x04 != 0,
+            signing_required: security_mode & 0x08 != 0,
+            netbios_name: None,
+            workgroup: None,
+            dns_name: None,
+            dns_domain: None,
+            os: None,
+        },
+        (None, None) => return None,
+    };
+
+    // Namen notfalls per NetBIOS Node Status nachschlagen
+    if info.netbios_name.is_none() {
+        if let Some(names) = netbios_node_status(addr.ip(), timeout).await {
+            let unique = names.iter().find(|entry| entry.suffix == 0x00 && !entry.is_group);
+            let group = names.iter().find(|entry| entry.suffix == 0x00 && entry.is_group);
+            info.netbios_name = unique.map(|entry| entry.name.clone());
+            info.workgroup = info.workgroup.or_else(|| group.map(|entry| entry.name.clone()));
+        }
+    }
+
+    Some(info)
+}
+
+/// Verbindet sich und eröffnet bei Port 139 zuerst eine NetBIOS-Sitzung
+async fn connect(addr: SocketAddr, timeout: Duration) -> Option<TcpStream> {
+    let mut stream = tokio::time::timeout(timeout, TcpStream::connect(addr)).await.ok()?.ok()?;
+    if addr.port() == 139 {
+        let mut request = vec![0x81, 0x00, 0x00, 0x44];
+        request.extend(encode_netbios_name(b"*SMBSERVER", 0x20));
+        request.extend(encode_netbios_name(b"SHC-TOOL", 0x00));
+        stream.write_all(&request).await.ok()?;
+
+        let mut response = [0u8; 4];
+        tokio::time::timeout(timeout, stream.read_exact(&mut response)).await.ok()?.ok()?;
+        // 0x82 = Positive Session Response
+        if response[0] != 0x82 {
+            return None;
+        }
+    }
+    Some(stream)
+}
+
+/// NetBIOS-Name in "First Level Encoding" (RFC 1001)
+fn encode_netbios_name(name: &[u8], suffix: u8) -> Vec<u8> {
+    let mut padded = [b' '; 16];
+    padded[..name.len()].copy_from_slice(name);
+    padded[15] = suffix;
+
+    let mut encoded = vec![0x20];
+    for byte in padded {
+        encoded.push(b'A' + (byte >> 4));
+        encoded.push(b'A' + (byte & 0x0f));
+    }
+    encoded.push(0x00);
+    encoded
+}
+
+/// Sendet eine SMB-Nachricht mit NetBIOS-Session-Header und liest die Antwort
+async fn exchange(stream: &mut TcpStream, message: &[u8], timeout: Duration) -> Option<Vec<u8>> {
+    let length = message.len() as u32;
+    let mut packet = vec![0x00, (length >> 16) as u8, (length >> 8) as u8, length as u8];
+    packet.extend_from_slice(message);
+    stream.write_all(&packet).await.ok()?;
+
+    let mut header = [0u8; 4];
+    tokio::time::timeout(timeout, stream.read_exact(&mut header)).await.ok()?.ok()?;
+    let length = (usize::from(header[1]) << 16) | (usize::from(header[2]) << 8) | usize::from(header[3]);
+    if length > MAX_MESSAGE {
+        return None;
+    }
+    let mut response = vec![0u8; length];
+    tokio::time::timeout(timeout, stream.read_exact(&mut response)).await.ok()?.ok()?;
+    Some(response)
+}
+
+/// SMBv1 NEGOTIATE nur mit "NT LM 0.12"; liefert den SecurityMode bei Erfolg
+async fn negotiate_smb1(addr: SocketAddr, timeout: Duration) -> Option<u8> {
+    let mut stream = connect(addr, timeout).await?;
+
+    let mut message = vec![0xff, b'S', b'M', b'B', 0x72];
+    message.extend_from_slice(&[0x00; 4]); // Status
+    message.push(0x18); // Flags: Pfadnamen ohne Groß-/Kleinschreibung
+    message.extend_from_slice(&0xc853u16.to_le_bytes()); // Flags2
+    message.extend_from_slice(&[0x00; 12]); // PIDHigh, SecurityFeatures, Reserved
+    message.extend_from_slice(&[0x00, 0x00]); // TID
+    message.extend_from_slice(&0x5348u16.to_le_bytes()); // PIDLow
+    message.extend_from_slice(&[0x00; 4]); // UID, MID
+    message.push(0x00); // WordCount
+    let dialect = b"\x02NT LM 0.12\x00";
+    message.extend_from_slice(&(dialect.len() as u16).to_le_bytes());
+    message.extend_from_slice(dialect);
+
+    let response = exchange(&mut stream, &message, timeout).await?;
+    parse_smb1_negotiate(&response)
+}
+
+/// SecurityMode aus einer SMBv1 NEGOTIATE-Antwort, `None` wenn der Server
+/// keinen Dialekt akzeptiert hat (DialectIndex 0xFFFF)
+fn parse_smb1_negotiate(response: &[u8]) -> Option<u8> {
+    let accepted = response.starts_with(b"\xffSMB")
+        && response.get(4) == Some(&0x72)
+        && response.get(5..9) == Some(&[0, 0, 0, 0])
+        && response.get(32).is_some_and(|&words| words >= 1)
+        && response.get(33..35) != Some(&[0xff, 0xff]);
+    accepted.then(|| response.get(35).copied().unwrap_or_default())
+}
+
+/// SMB2 NEGOTIATE und SESSION_SETUP mit NTLMSSP;
+/// liefert Dialekt, SecurityMode und die Angaben aus der NTLM-Challenge
+async fn negotiate_smb2(addr: SocketAddr, timeout: Duration) -> Option<(u16, u16, Option<NtlmChallenge>)> {
+    let mut stream = connect(addr, timeout).await?;
+
+    let response = exchange(&mut stream, &smb2_negotiate_request(), timeout).await?;
+    let (dialect, security_mode) = parse_smb2_negotiate(&response)?;
+
+    let session = exchange(&mut stream, &smb2_session_setup_request(), timeout).await;
+    let challenge = session.as_deref().and_then(parse_ntlm_challenge);
+    Some((dialect, security_mode, challenge))
+}
+
+/// Dialekt und SecurityMode aus einer erfolgreichen SMB2 NEGOTIATE-Antwort
+fn parse_smb2_negotiate(response: &[u8]) -> Option<(u16, u16)> {
+    if !response.starts_with(b"\xfeSMB") || read_u32(response, 8)? != 0 {
+        return None;
+    }
+    Some((read_u16(response, 68)?, read_u16(response, 66)?))
+}
+
+fn smb2_header(command: u16, message_id: u64) -> Vec<u8> {
+    let mut header = vec![0xfe, b'S', b'M', b'B'];
+    header.extend_from_slice(&64u16.to_le_bytes()); // StructureSize
+    header.extend_from_slice(&[0x00; 6]); // CreditCharge, Status
+    header.extend_from_slice(&command.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // CreditRequest
+    header.extend_from_slice(&[0x00; 8]); // Flags, NextCommand
+    header.extend_from_slice(&message_id.to_le_bytes());
+    header.extend_from_slice(&[0x00; 32]); // Reserved, TreeId, SessionId, Signature
+    header
+}
+
+fn smb2_negotiate_request() -> Vec<u8> {
+    let mut message = smb2_header(0x0000, 0);
+    message.extend_from_slice(&36u16.to_le_bytes()); // StructureSize
+    message.extend_from_slice(&(SMB2_DIALECTS.len() as u16).to_le_bytes());
+    message.extend_from_slice(&1u16.to_le_bytes()); // SecurityMode: Signing aktiviert
+    message.extend_from_slice(&[0x00; 2]);
+    message.extend_from_slice(&[0x00; 4]); // Capabilities
+    message.extend_from_slice(b"SHC-Tool-SMBscan"); // ClientGuid
+    let context_offset_position = message.len();
+    message.extend_from_slice(&[0x00; 4]); // NegotiateContextOffset (folgt)
+    message.extend_from_slice(&2u16.to_le_bytes()); // NegotiateContextCount
+    message.extend_from_slice(&[0x00; 2]);
+    for dialect in SMB2_DIALECTS {
+        message.extend_from_slice(&dialect.to_le_bytes());
+    }
+
+    // SMB 3.1.1 verlangt Negotiate Contexts, jeweils auf 8 Byte ausgerichtet
+    pad_to_8(&mut message);
+    let offset = message.len() as u32;
+    message[context_offset_position..context_offset_position + 4].copy_from_slice(&offset.to_le_bytes());
+
+    // Preauth Integrity: SHA-512 mit 32 Byte Salt
+    let mut preauth = Vec::new();
+    preauth.extend_from_slice(&1u16.to_le_bytes());
+    preauth.extend_from_slice(&32u16.to_le_bytes());
+    preauth.extend_from_slice(&1u16.to_le_bytes());
+    preauth.extend_from_slice(&[0x53; 32]);
+    push_negotiate_context(&mut message, 0x0001, &preauth);
+    pad_to_8(&mut message);
+
+    // Encryption: AES-128-GCM und AES-128-CCM
+    let mut encryption = Vec::new();
+    encryption.extend_from_slice(&2u16.to_le_bytes());
+    encryption.extend_from_slice(&2u16.to_le_bytes());
+    encryption.extend_from_slice(&1u16.to_le_bytes());
+    push_negotiate_context(&mut message, 0x0002, &encryption);
+
+    message
+}
+
+fn push_negotiate_context(message: &mut Vec<u8>, context_type: u16, data: &[u8]) {
+    message.extend_from_slice(&context_type.to_le_bytes());
+    message.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    message.extend_from_slice(&[0x00; 4]);
+    message.extend_from_slice(data);
+}
+
+fn pad_to_8(message: &mut Vec<u8>) {
+    while !message.len().is_multiple_of(8) {
+        message.push(0x00);
+    }
+}
+
+fn smb2_session_setup_request() -> Vec<u8> {
+    let token = spnego_init(&ntlm_negotiate());
+
+    let mut message = smb2_header(0x0001, 1);
+    message.extend_from_slice(&25u16.to_le_bytes()); // StructureSize
+    message.push(0x00); // Flags
+    message.push(0x01); // SecurityMode: Signing aktiviert
+    message.extend_from_slice(&[0x00; 8]); // Capabilities, Channel
+    message.extend_from_slice(&(64u16 + 24).to_le_bytes()); // SecurityBufferOffset
+    message.extend_from_slice(&(token.len() as u16).to_le_bytes());
+    message.extend_from_slice(&[0x00; 8]); // PreviousSessionId
+    message.extend_from_slice(&token);
+    message
+}
+
+/// NTLMSSP NEGOTIATE_MESSAGE, fordert Target-Info und Versionsangabe an
+fn ntlm_negotiate() -> Vec<u8> {
+    let mut message = b"NTLMSSP\0".to_vec();
+    message.extend_from_slice(&1u32.to_le_bytes());
+    message.extend_from_slice(&0xe208_8297u32.to_le_bytes());
+    message.extend_from_slice(&[0x00; 16]); // Domain, Workstation
+    message.extend_from_slice(&[0x06, 0x01, 0xb1, 0x1d, 0x00, 0x00, 0x00, 0x0f]); // Version
+    message
+}
+
+/// Verpackt ein NTLMSSP-Token in ein SPNEGO NegTokenInit
+fn spnego_init(token: &[u8]) -> Vec<u8> {
+    let mech_types = der(0xa0, &der(0x30, &der(0x06, &NTLMSSP_OID)));
+    let mech_token = der(0xa2, &der(0x04, token));
+    let neg_token_init = der(0xa0, &der(0x30, &[mech_types, mech_token].concat()));
+    der(0x60, &[der(0x06, &SPNEGO_OID), neg_token_init].concat())
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    match content.len() {
+        length @ 0..=0x7f => encoded.push(length as u8),
+        length @ 0x80..=0xff => encoded.extend_from_slice(&[0x81, length as u8]),
+        length => encoded.extend_from_slice(&[0x82, (length >> 8) as u8, length as u8]),
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+/// Wertet die NTLMSSP CHALLENGE_MESSAGE in einer SESSION_SETUP-Antwort aus
+fn parse_ntlm_challenge(response: &[u8]) -> Option<NtlmChallenge> {
+    let start = response.windows(8).position(|window| window == b"NTLMSSP\0")?;
+    let message = &response[start..];
+    if read_u32(message, 8)? != 2 {
+        return None;
+    }
+
+    let mut challenge = NtlmChallenge::default();
+    let flags = read_u32(message, 20)?;
+
+    let info_length = usize::from(read_u16(message, 40)?);
+    let info_offset = read_u32(message, 44)? as usize;
+    if let Some(mut info) = message.get(info_offset..info_offset + info_length) {
+        while info.len() >= 4 {
+            let id = u16::from_le_bytes([info[0], info[1]]);
+            let length = usize::from(u16::from_le_bytes([info[2], info[3]]));
+            let Some(value) = info.get(4..4 + length) else { break };
+            let text = Some(utf16_string(value)).filter(|text| !text.is_empty());
+            match id {
+                0 => break,
+                1 => challenge.netbios_name = text,
+                2 => challenge.workgroup = text,
+                3 => challenge.dns_name = text,
+                4 => challenge.dns_domain = text,
+                _ => {}
+            }
+            info = &info[4 + length..];
+        }
+    }
+
+    // NTLMSSP_NEGOTIATE_VERSION
+    if flags & 0x0200_0000 != 0 {
+        if let Some(version) = message.get(48..56) {
+            let build = u16::from_le_bytes([version[2], version[3]]);
+            challenge.os = Some(describe_windows_version(version[0], version[1], build));
+        }
+    }
+
+    Some(challenge)
+}
+
+/// Windows-Version aus der NTLMSSP-Versionsangabe
+fn describe_windows_version(major: u8, minor: u8, build: u16) -> String {
+    let name = match (major, minor) {
+        (5, 0) => Some("Windows 2000"),
+        (5, 1) => Some("Windows XP"),
+        (5, 2) => Some("Windows Server 2003"),
+        (6, 0) => Some("Windows Vista / Server 2008"),
+        (6, 1) => Some("Windows 7 / Server 2008 R2"),
+        (6, 2) => Some("Windows 8 / Server 2012"),
+        (6, 3) => Some("Windows 8.1 / Server 2012 R2"),
+        (10, 0) if build >= 22000 => Some("Windows 11 / Server 2025"),
+        (10, 0) => Some("Windows 10 / Server 2016-2022"),
+        _ => None,
+    };
+    match name {
+        Some(name) => format!("Windows {}.{} Build {} ({})", major, minor, build, name),
+        None => format!("Windows {}.{} Build {}", major, minor, build),
+    }
+}
+
+fn dialect_name(dialect: u16) -> String {
+    match dialect {
+        0x0202 => "2.0.2".to_string(),
+        0x0210 => "2.1".to_string(),
+        0x0300 => "3.0".to_string(),
+        0x0302 => "3.0.2".to_string(),
+        0x0311 => "3.1.1".to_string(),
+        other => format!("0x{:04x}", other),
+    }
+}
+
+fn utf16_string(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// SMBv1 NEGOTIATE-Antwort wie von Samba mit `server min protocol = NT1`
+    fn smb1_response(dialect_index: u16, security_mode: u8) -> Vec<u8> {
+        let mut response = vec![0xff, b'S', b'M', b'B', 0x72, 0, 0, 0, 0, 0x98];
+        response.resize(32, 0x00);
+        response.push(17); // WordCount
+        response.extend_from_slice(&dialect_index.to_le_bytes());
+        response.push(security_mode);
+        response.resize(33 + 34 + 2, 0x00);
+        response
+    }
+
+    fn smb2_response(command: u16, status: u32, body: &[u8]) -> Vec<u8> {
+        let mut response = vec![0xfe, b'S', b'M', b'B'];
+        response.extend_from_slice(&64u16.to_le_bytes());
+        response.extend_from_slice(&[0x00; 2]);
+        response.extend_from_slice(&status.to_le_bytes());
+        response.extend_from_slice(&command.to_le_bytes());
+        response.resize(64, 0x00);
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn smb2_negotiate_response(dialect: u16, security_mode: u16) -> Vec<u8> {
+        let mut body = 65u16.to_le_bytes().to_vec();
+        body.extend_from_slice(&security_mode.to_le_bytes());
+        body.extend_from_slice(&dialect.to_le_bytes());
+        body.resize(64, 0x00);
+        smb2_response(0x0000, 0, &body)
+    }
+
+    /// NTLMSSP CHALLENGE_MESSAGE mit Target-Info und optionaler Versionsangabe
+    fn ntlm_challenge(pairs: &[(u16, &str)], version: Option<(u8, u8, u16)>) -> Vec<u8> {
+        let mut info = Vec::new();
+        for (id, text) in pairs {
+            let value: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            info.extend_from_slice(&id.to_le_bytes());
+            info.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            info.extend_from_slice(&value);
+        }
+        info.extend_from_slice(&[0x00; 4]); // MsvAvEOL
+
+        let flags: u32 = if version.is_some() { 0x0281_0215 } else { 0x0081_0215 };
+        let mut message = b"NTLMSSP\0".to_vec();
+        message.extend_from_slice(&2u32.to_le_bytes());
+        message.extend_from_slice(&[0x00; 8]); // TargetName
+        message.extend_from_slice(&flags.to_le_bytes());
+        message.extend_from_slice(&[0x11; 8]); // Server-Challenge
+        message.extend_from_slice(&[0x00; 8]);
+        message.extend_from_slice(&(info.len() as u16).to_le_bytes());
+        message.extend_from_slice(&(info.len() as u16).to_le_bytes());
+        message.extend_from_slice(&56u32.to_le_bytes());
+        let (major, minor, build) = version.unwrap_or_default();
+        message.extend_from_slice(&[major, minor]);
+        message.extend_from_slice(&build.to_le_bytes());
+        message.extend_from_slice(&[0x00, 0x00, 0x00, 0x0f]);
+        message.extend_from_slice(&info);
+        message
+    }
+
+    #[test]
+    fn smb1_negotiate_accepted_and_rejected() {
+        assert_eq!(parse_smb1_negotiate(&smb1_response(0, 0x0f)), Some(0x0f));
+        // Kein gemeinsamer Dialekt
+        assert_eq!(parse_smb1_negotiate(&smb1_response(0xffff, 0x03)), None);
+        // Fehlerstatus, z.B. STATUS_NOT_SUPPORTED
+        let mut error = smb1_response(0, 0x03);
+        error[5..9].copy_from_slice(&0xc000_00bbu32.to_le_bytes());
+        assert_eq!(parse_smb1_negotiate(&error), None);
+        assert_eq!(parse_smb1_negotiate(b"\xffSMBr"), None);
+    }
+
+    #[test]
+    fn smb2_negotiate_dialect_and_signing() {
+        assert_eq!(parse_smb2_negotiate(&smb2_negotiate_response(0x0311, 0x03)), Some((0x0311, 0x03)));
+        assert_eq!(parse_smb2_negotiate(&smb2_response(0x0000, 0xc000_0022, &[])), None);
+        assert_eq!(parse_smb2_negotiate(&smb1_response(0, 0x03)), None);
+        assert_eq!(parse_smb2_negotiate(&smb2_negotiate_response(0x0311, 0x03)[..68]), None);
+    }
+
+    #[test]
+    fn ntlm_challenge_names_and_os() {
+        let message = ntlm_challenge(
+            &[(2, "WORKGROUP"), (1, "FILESERVER"), (4, "corp.example"), (3, "fileserver.corp.example")],
+            Some((10, 0, 19041)),
+        );
+        // Die Challenge steckt in einem SPNEGO-Token hinter dem SMB2-Header
+        let response = smb2_response(0x0001, 0xc000_0016, &spnego_init(&message));
+        let challenge = parse_ntlm_challenge(&response).expect("Challenge erwartet");
+        assert_eq!(challenge.netbios_name.as_deref(), Some("FILESERVER"));
+        assert_eq!(challenge.workgroup.as_deref(), Some("WORKGROUP"));
+        assert_eq!(challenge.dns_name.as_deref(), Some("fileserver.corp.example"));
+        assert_eq!(challenge.dns_domain.as_deref(), Some("corp.example"));
+        assert_eq!(challenge.os.as_deref(), Some("Windows 10.0 Build 19041 (Windows 10 / Server 2016-2022)"));
+    }
+
+    #[test]
+    fn ntlm_challenge_without_version_or_wrong_type() {
+        let challenge = parse_ntlm_challenge(&ntlm_challenge(&[(1, "SAMBA")], None)).expect("Challenge erwartet");
+        assert_eq!(challenge.netbios_name.as_deref(), Some("SAMBA"));
+        assert_eq!(challenge.os, None);
+
+        let mut negotiate = ntlm_challenge(&[], None);
+        negotiate[8] = 1;
+        assert!(parse_ntlm_challenge(&negotiate).is_none());
+
+        // Abgeschnittenes AV-Paar darf nicht zu einem Absturz führen
+        let mut truncated = ntlm_challenge(&[(1, "SAMBA")], None);
+        truncated.truncate(truncated.len() - 6);
+        assert!(parse_ntlm_challenge(&truncated).is_some());
+    }
+
+    /// Lokaler Ersatz für einen SMB-Server: beantwortet SMBv1 nur mit
+    /// `smb1`, SMB2 nur mit `smb2` und trennt sonst die Verbindung
+    async fn stand_in_server(smb1: bool, smb2: bool) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    loop {
+                        let mut header = [0u8; 4];
+                        if stream.read_exact(&mut header).await.is_err() {
+                            return;
+                        }
+                        let length = (usize::from(header[2]) << 8) | usize::from(header[3]);
+                        let mut request = vec![0u8; length];
+                        if stream.read_exact(&mut request).await.is_err() {
+                            return;
+                        }
+                        let response = match (&request[..4], read_u16(&request, 12)) {
+                            (b"\xffSMB", _) if smb1 => smb1_response(0, 0x03),
+                            (b"\xfeSMB", Some(0x0000)) if smb2 => smb2_negotiate_response(0x0311, 0x03),
+                            (b"\xfeSMB", Some(0x0001)) if smb2 => {
+                                let challenge = ntlm_challenge(&[(1, "STANDIN"), (2, "TESTGROUP")], Some((6, 1, 7601)));
+                                smb2_response(0x0001, 0xc000_0016, &spnego_init(&challenge))
+                            }
+                            _ => return,
+                        };
+                        let mut packet = (response.len() as u32).to_be_bytes().to_vec();
+                        packet.extend_from_slice(&response);
+                        if stream.write_all(&packet).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn inspects_smb2_stand_in() {
+        let addr = stand_in_server(false, true).await;
+        let info = inspect_endpoint(addr, Duration::from_secs(2)).await.expect("SMB-Antwort erwartet");
+        assert_eq!(info.dialect.as_deref(), Some("3.1.1"));
+        assert!(!info.smb1);
+        assert!(info.signing_enabled && info.signing_required);
+        assert_eq!(info.netbios_name.as_deref(), Some("STANDIN"));
+        assert_eq!(info.workgroup.as_deref(), Some("TESTGROUP"));
+        assert_eq!(info.os.as_deref(), Some("Windows 6.1 Build 7601 (Windows 7 / Server 2008 R2)"));
+    }
+
+    #[tokio::test]
+    async fn inspects_smb1_only_stand_in() {
+        let addr = stand_in_server(true, false).await;
+        let info = inspect_endpoint(addr, Duration::from_millis(300)).await.expect("SMB-Antwort erwartet");
+        assert_eq!(info.dialect.as_deref(), Some("NT LM 0.12"));
+        assert!(info.smb1);
+        // SecurityMode 0x03: User-Level und Verschlüsselung, kein Signing
+        assert!(!info.signing_enabled && !info.signing_required);
+    }
+
+    #[tokio::test]
+    async fn no_smb_server() {
+        let addr = stand_in_server(false, false).await;
+        assert!(inspect_endpoint(addr, Duration::from_millis(300)).await.is_none());
+    }
+}
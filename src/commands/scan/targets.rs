@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+// Obergrenze, damit ein Tippfehler wie /8 oder /64 nicht Millionen Hosts erzeugt
+const MAX_HOSTS: u128 = 65_536;
+
+/// Welche Adressen eines Hostnamens gescannt werden
+#[derive(Clone, Copy, PartialEq)]
+pub enum AddressFamily {
+    /// Alle A- und AAAA-Einträge
+    Any,
+    /// Nur die erste IPv4-Adresse
+    V4,
+    /// Nur die erste IPv6-Adresse
+    V6,
+}
+
+/// Ein einzelner zu scannender Host
+pub struct ScanTarget {
+    pub ip: IpAddr,
+    /// Hostname, falls die Adresse aus einer Namensauflösung stammt
+    pub hostname: Option<String>,
+    /// MAC-Adresse, sofern bei der Host-Erkennung ermittelt
+    pub mac: Option<String>,
+    /// Woran die Host-Erkennung den Host erkannt hat
+    pub discovered_by: Option<String>,
+}
+
+/// Zerlegt ein Scan-Ziel in eine Liste von Hosts.
+///
+/// Unterstützt einzelne Adressen, CIDR-Blöcke (`10.0.0.0/24`, `fd00::/120`),
+/// Bereiche (`192.168.1.10-50` oder `192.168.1.10-192.168.1.50`), Hostnamen
+/// sowie kommagetrennte Kombinationen davon. Doppelte Hosts werden entfernt.
+pub async fn parse_target(target: &str, family: AddressFamily) -> Result<Vec<ScanTarget>, String> {
+    let mut hosts = Vec::new();
+    let mut seen = HashSet::new();
+
+    for part in target.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        for host in parse_target_part(part, family).await? {
+            if seen.insert(host.ip) {
+                hosts.push(host);
+            }
+        }
+
+        if hosts.len() as u128 > MAX_HOSTS {
+            return Err(format!("Zu viele Hosts (maximal {})", MAX_HOSTS));
+        }
+    }
+
+    Ok(hosts)
+}
+
+async fn parse_target_part(part: &str, family: AddressFamily) -> Result<Vec<ScanTarget>, String> {
+    let unnamed = |ips: Vec<IpAddr>| {
+        ips.into_iter().map(|ip| ScanTarget { ip, hostname: None, mac: None, discovered_by: None }).collect()
+    };
+
+    // Versuche direkt als IP zu parsen
+    if let Ok(ip) = IpAddr::from_str(part) {
+        return Ok(unnamed(vec![ip]));
+    }
+
+    // Netzwerk in CIDR-Notation (z.B. 192.168.1.0/24)
+    if let Some((addr, prefix)) = part.split_once('/') {
+        let ip = IpAddr::from_str(addr)
+            .map_err(|_| format!("'{}' ist keine gültige Netzwerkadresse", addr))?;
+        let prefix: u8 = prefix.parse()
+            .map_err(|_| format!("'{}' ist keine gültige Präfixlänge", prefix))?;
+        return expand_cidr(ip, prefix).map(unnamed);
+    }
+
+    // Adressbereich (z.B. 192.168.1.10-50 oder 192.168.1.10-192.168.1.50).
+    // Hostnamen dürfen ebenfalls Bindestriche enthalten, daher muss der
+    // Anfang eine gültige Adresse sein.
+    if let Some((start, end)) = part.split_once('-') {
+        if IpAddr::from_str(start.trim()).is_ok() {
+            return expand_range(start, end).map(unnamed);
+        }
+    }
+
+    // Fallback: Als Hostname über den System-Resolver auflösen
+    resolve_hostname(part, family).await
+}
+
+async fn resolve_hostname(hostname: &str, family: AddressFamily) -> Result<Vec<ScanTarget>, String> {
+    let addrs = tokio::net::lookup_host((hostname, 0)).await
+        .map_err(|e| format!("Konnte '{}' nicht auflösen: {}", hostname, e))?;
+
+    let mut ips: Vec<IpAddr> = Vec::new();
+    for addr in addrs {
+        if !ips.contains(&addr.ip()) {
+            ips.push(addr.ip());
+        }
+    }
+
+    match family {
+        AddressFamily::Any => {}
+        AddressFamily::V4 => {
+            ips = ips.into_iter().find(IpAddr::is_ipv4).into_iter().collect();
+        }
+        AddressFamily::V6 => {
+            ips = ips.into_iter().find(IpAddr::is_ipv6).into_iter().collect();
+        }
+    }
+
+    if ips.is_empty() {
+        let kind = match family {
+            AddressFamily::V6 => "IPv6-Adresse",
+            AddressFamily::V4 => "IPv4-Adresse",
+            AddressFamily::Any => "Adresse",
+        };
+        return Err(format!("Für '{}' wurde keine {} gefunden", hostname, kind));
+    }
+
+    Ok(ips.into_iter()
+        .map(|ip| ScanTarget { ip, hostname: Some(hostname.to_string()), mac: None, discovered_by: None })
+        .collect())
+}
+
+/// Sucht per Reverse-DNS (PTR) den Namen einer Adresse über den System-Resolver
+pub async fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    tokio::task::spawn_blocking(move || dns_lookup::lookup_addr(&ip).ok())
+        .await
+        .ok()
+        .flatten()
+        .filter(|name| name.parse::<IpAddr>().is_err())
+}
+
+fn expand_cidr(ip: IpAddr, prefix: u8) -> Result<Vec<IpAddr>, String> {
+    match ip {
+        IpAddr::V4(v4) => {
+            if prefix > 32 {
+                return Err(format!("Präfixlänge /{} ist für IPv4 ungültig", prefix));
+            }
+            let host_bits = 32 - u32::from(prefix);
+            check_host_count(1u128 << host_bits)?;
+
+            let mask = if prefix == 0 { 0 } else { u32::MAX << host_bits };
+            let network = u32::from(v4) & mask;
+            let broadcast = network | !mask;
+
+            // Netz- und Broadcast-Adresse nur bei /31 und /32 mitscannen
+            let (first, last) = if prefix >= 31 {
+                (network, broadcast)
+            } else {
+                (network + 1, broadcast - 1)
+            };
+
+            Ok((first..=last).map(|n| IpAddr::V4(Ipv4Addr::from(n))).collect())
+        }
+        IpAddr::V6(v6) => {
+            if prefix > 128 {
+                return Err(format!("Präfixlänge /{} ist für IPv6 ungültig", prefix));
+            }
+            let host_bits = 128 - u32::from(prefix);
+            if host_bits >= 64 {
+                return Err(format!("Zu viele Hosts in /{} (maximal {})", prefix, MAX_HOSTS));
+            }
+            check_host_count(1u128 << host_bits)?;
+
+            let mask = if prefix == 0 { 0 } else { u128::MAX << host_bits };
+            let network = u128::from(v6) & mask;
+            let last = network | !mask;
+
+            Ok((network..=last).map(|n| IpAddr::V6(Ipv6Addr::from(n))).collect())
+        }
+    }
+}
+
+fn expand_range(start: &str, end: &str) -> Result<Vec<IpAddr>, String> {
+    let start_ip = IpAddr::from_str(start.trim())
+        .map_err(|_| format!("'{}' ist keine gültige Startadresse", start))?;
+    let end = end.trim();
+
+    let end_ip = match (start_ip, IpAddr::from_str(end)) {
+        (_, Ok(ip)) => ip,
+        // Kurzform: nur das letzte Oktett ist angegeben (192.168.1.10-50)
+        (IpAddr::V4(v4), Err(_)) => {
+            let last_octet: u8 = end.parse()
+                .map_err(|_| format!("'{}' ist kein gültiges Bereichsende", end))?;
+            let [a, b, c, _] = v4.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, c, last_octet))
+        }
+        (IpAddr::V6(_), Err(_)) => {
+            return Err(format!("'{}' ist kein gültiges Bereichsende", end));
+        }
+    };
+
+    match (start_ip, end_ip) {
+        (IpAddr::V4(s), IpAddr::V4(e)) => {
+            let (s, e) = (u32::from(s), u32::from(e));
+            if s > e {
+                return Err(format!("Bereichsanfang {} liegt hinter dem Ende {}", start_ip, end_ip));
+            }
+            check_host_count(u128::from(e - s) + 1)?;
+            Ok((s..=e).map(|n| IpAddr::V4(Ipv4Addr::from(n))).collect())
+        }
+        (IpAddr::V6(s), IpAddr::V6(e)) => {
+            let (s, e) = (u128::from(s), u128::from(e));
+            if s > e {
+                return Err(format!("Bereichsanfang {} liegt hinter dem Ende {}", start_ip, end_ip));
+            }
+            check_host_count((e - s).saturating_add(1))?;
+            Ok((s..=e).map(|n| IpAddr::V6(Ipv6Addr::from(n))).collect())
+        }
+        _ => Err("Bereichsanfang und -ende müssen dieselbe IP-Version haben".to_string()),
+    }
+}
+
+fn check_host_count(count: u128) -> Result<(), String> {
+    if count > MAX_HOSTS {
+        Err(format!("Zu viele Hosts ({}, maximal {})", count, MAX_HOSTS))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(address: &str) -> IpAddr {
+        IpAddr::from_str(address).unwrap()
+    }
+
+    #[test]
+    fn cidr_keeps_network_and_broadcast_only_for_31_and_32() {
+        assert_eq!(expand_cidr(ip("10.0.0.7"), 32).unwrap(), vec![ip("10.0.0.7")]);
+        assert_eq!(expand_cidr(ip("10.0.0.7"), 31).unwrap(), vec![ip("10.0.0.6"), ip("10.0.0.7")]);
+        assert_eq!(expand_cidr(ip("10.0.0.7"), 30).unwrap(), vec![ip("10.0.0.5"), ip("10.0.0.6")]);
+
+        let hosts = expand_cidr(ip("192.168.1.77"), 24).unwrap();
+        assert_eq!(hosts.len(), 254);
+        assert_eq!(hosts.first(), Some(&ip("192.168.1.1")));
+        assert_eq!(hosts.last(), Some(&ip("192.168.1.254")));
+    }
+
+    #[test]
+    fn cidr_respects_host_cap() {
+        assert_eq!(expand_cidr(ip("10.0.0.0"), 16).unwrap().len(), 65_534);
+        assert!(expand_cidr(ip("10.0.0.0"), 15).is_err());
+        assert!(expand_cidr(ip("10.0.0.0"), 0).is_err());
+        assert!(expand_cidr(ip("10.0.0.0"), 33).is_err());
+
+        assert_eq!(expand_cidr(ip("fd00::1"), 120).unwrap().len(), 256);
+        assert_eq!(expand_cidr(ip("fd00::1"), 128).unwrap(), vec![ip("fd00::1")]);
+        assert!(expand_cidr(ip("fd00::"), 112).is_ok());
+        assert!(expand_cidr(ip("fd00::"), 111).is_err());
+        assert!(expand_cidr(ip("fd00::"), 64).is_err());
+        assert!(expand_cidr(ip("fd00::"), 129).is_err());
+    }
+
+    #[test]
+    fn range_short_and_full_form() {
+        let expected = vec![ip("192.168.1.10"), ip("192.168.1.11"), ip("192.168.1.12")];
+        assert_eq!(expand_range("192.168.1.10", "12").unwrap(), expected);
+        assert_eq!(expand_range("192.168.1.10", " 192.168.1.12").unwrap(), expected);
+        assert_eq!(expand_range("192.168.1.10", "10").unwrap(), vec![ip("192.168.1.10")]);
+
+        // Über Oktettgrenzen hinweg nur in der Langform
+        assert_eq!(expand_range("10.0.0.255", "10.0.1.1").unwrap().len(), 3);
+        assert_eq!(expand_range("fd00::1", "fd00::3").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn range_rejects_invalid_bounds() {
+        assert!(expand_range("192.168.1.10", "9").is_err());
+        assert!(expand_range("192.168.1.10", "300").is_err());
+        assert!(expand_range("fd00::1", "3").is_err());
+        assert!(expand_range("10.0.0.1", "fd00::1").is_err());
+        assert!(expand_range("10.0.0.0", "10.1.0.0").is_err());
+        assert_eq!(expand_range("10.0.0.0", "10.0.255.255").unwrap().len(), 65_536);
+    }
+
+    #[tokio::test]
+    async fn target_list_is_deduplicated() {
+        let hosts = parse_target("10.0.0.1-3, 10.0.0.2, 10.0.0.0/31", AddressFamily::Any).await.unwrap();
+        let ips: Vec<IpAddr> = hosts.iter().map(|host| host.ip).collect();
+        assert_eq!(ips, vec![ip("10.0.0.1"), ip("10.0.0.2"), ip("10.0.0.3"), ip("10.0.0.0")]);
+    }
+}
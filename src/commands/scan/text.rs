@@ -0,0 +1,315 @@
+use std::fmt::{self, Write};
+
+use super::http::HttpInfo;
+use super::report::ScanReport;
+use super::smb::SmbInfo;
+use super::services::get_service_name;
+use super::tls::TlsInfo;
+use super::{HostResult, PortResult, Protocol};
+
+/// Lesbare Ausgabe des Berichts, wie sie bisher direkt ausgegeben wurde
+pub fn render_text(report: &ScanReport, show_closed: bool) -> String {
+    let mut out = String::new();
+    // In einen String zu schreiben schlägt nie fehl
+    let _ = write_report(&mut out, report, show_closed);
+    out
+}
+
+fn write_report(out: &mut String, report: &ScanReport, show_closed: bool) -> fmt::Result {
+    if report.discover_only {
+        return write_live_hosts(out, &report.hosts);
+    }
+
+    // Bei Netzwerk-Scans nur Hosts mit offenen Ports einzeln auflisten
+    for result in &report.hosts {
+        if report.hosts.len() == 1 || result.is_listed(show_closed) {
+            write_host_result(out, result, report.port_count)?;
+            writeln!(out)?;
+        }
+    }
+
+    if report.hosts.len() > 1 {
+        write_network_summary(out, &report.hosts)?;
+    }
+    writeln!(out, "⏱️  Scan-Dauer: {:.2} s", report.duration_seconds)
+}
+
+/// Listet die aktiven Hosts einer Host-Erkennung (`--discover-only`)
+fn write_live_hosts(out: &mut String, hosts: &[HostResult]) -> fmt::Result {
+    if hosts.is_empty() {
+        return writeln!(out, "🚫 Keine aktiven Hosts gefunden.");
+    }
+
+    writeln!(out, "📋 Aktive Hosts:")?;
+    for host in hosts {
+        let method = host.discovered_by.as_deref().unwrap_or("aktiv");
+        match host.hostname.as_ref().or(host.reverse_dns.as_ref()) {
+            Some(name) => writeln!(out, "🟢 {} ({}) - {}", host.ip, name, method)?,
+            None => writeln!(out, "🟢 {} - {}", host.ip, method)?,
+        }
+        if let Some(mac) = &host.mac {
+            writeln!(out, "    🔌 MAC: {}", format_mac(mac, host.vendor.as_deref()))?;
+        }
+    }
+    Ok(())
+}
+
+/// MAC-Adresse mit Hersteller, z.B. `b8:27:eb:12:34:56 (Raspberry Pi)`
+fn format_mac(mac: &str, vendor: Option<&str>) -> String {
+    format!("{} ({})", mac, vendor.unwrap_or("Hersteller unbekannt"))
+}
+
+fn write_host_result(out: &mut String, result: &HostResult, port_count: usize) -> fmt::Result {
+    writeln!(out, "🖥️  Host {}", result.label())?;
+    if let Some(name) = &result.reverse_dns {
+        writeln!(out, "🔁 PTR: {}", name)?;
+    }
+    if let Some(mac) = &result.mac {
+        writeln!(out, "🔌 MAC: {}", format_mac(mac, result.vendor.as_deref()))?;
+    }
+    for port in &result.ports {
+        let icon = port.state.icon();
+        let label = port_label(port);
+        let state = port.state.label();
+        match &port.service {
+            Some(info) => {
+                let expected = get_service_name(port.port, port.protocol);
+                if info.service == expected || expected == "Unbekannt" {
+                    writeln!(out, "{} Port {}: {} ({})", icon, label, state, info.describe())?;
+                } else {
+                    writeln!(out, "{} Port {}: {} ({}, erwartet: {})", icon, label, state, info.describe(), expected)?;
+                }
+                if let Some(banner) = &info.banner {
+                    writeln!(out, "    📝 Banner: {}", banner)?;
+                }
+            }
+            None => writeln!(out, "{} Port {}: {} ({})", icon, label, state, port.service_name())?,
+        }
+        if let Some(tls) = &port.tls {
+            write_tls_details(out, tls)?;
+        }
+        if let Some(http) = &port.http {
+            write_http_details(out, http)?;
+        }
+        if let Some(smb) = &port.smb {
+            write_smb_details(out, smb)?;
+        }
+    }
+
+    writeln!(out, "\n📊 Scan-Zusammenfassung:")?;
+    writeln!(out, "🎯 Ziel: {}", result.label())?;
+    writeln!(out, "🔍 Gescannte Ports: {}", port_count)?;
+    let counts = &result.counts;
+    writeln!(out, "✅ Offene Ports: {}", counts.open)?;
+    if counts.open_filtered > 0 {
+        writeln!(out, "❔ Offen|gefiltert (keine Antwort): {}", counts.open_filtered)?;
+    }
+    if counts.closed > 0 {
+        writeln!(out, "🚫 Geschlossen (abgewiesen): {}", counts.closed)?;
+    }
+    if counts.filtered > 0 {
+        writeln!(out, "🧱 Gefiltert (Timeout): {}", counts.filtered)?;
+    }
+    if counts.unreachable > 0 {
+        writeln!(out, "📵 Unerreichbar (Host/Netz nicht erreichbar): {}", counts.unreachable)?;
+    }
+
+    let open: Vec<&PortResult> = result.open_ports().collect();
+    if open.is_empty() {
+        writeln!(out, "🚫 Keine offenen Ports in der gewählten Port-Liste gefunden.")?;
+    } else {
+        let port_numbers: Vec<u16> = open.iter().map(|p| p.port).collect();
+        writeln!(out, "📋 Offene Ports: {:?}", port_numbers)?;
+
+        // Überschrift nur, wenn die Richtlinie etwas gefunden hat
+        if open.iter().any(|port| !port.warnings.is_empty()) {
+            writeln!(out, "\n🔒 Sicherheitshinweise:")?;
+        }
+        for port in open {
+            for finding in &port.warnings {
+                writeln!(out, "  {} Port {} [{}]: {}", finding.severity.icon(), port_label(port),
+                         finding.severity.label(), finding.message)?;
+                if let Some(remediation) = &finding.remediation {
+                    writeln!(out, "      💡 Empfehlung: {}", remediation)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Portnummer, bei UDP mit Protokoll-Suffix (z.B. `161/udp`)
+fn port_label(port: &PortResult) -> String {
+    match port.protocol {
+        Protocol::Tcp => port.port.to_string(),
+        Protocol::Udp => format!("{}/udp", port.port),
+    }
+}
+
+fn write_tls_details(out: &mut String, tls: &TlsInfo) -> fmt::Result {
+    writeln!(out, "    🔐 TLS: {}, {}", tls.protocol, tls.cipher)?;
+    writeln!(out, "    📜 Zertifikat: {}", tls.subject)?;
+    if !tls.san.is_empty() {
+        writeln!(out, "    🏷️  SAN: {}", tls.san.join(", "))?;
+    }
+    writeln!(out, "    🏢 Aussteller: {}", tls.issuer)?;
+    let remaining = if tls.days_until_expiry < 0 {
+        format!("seit {} Tagen abgelaufen", -tls.days_until_expiry)
+    } else {
+        format!("noch {} Tage", tls.days_until_expiry)
+    };
+    writeln!(out, "    📅 Gültig: {} bis {} ({})", tls.not_before, tls.not_after, remaining)?;
+    writeln!(out, "    🔑 Schlüssel: {} {} Bit, Signatur: {}", tls.key_type, tls.key_bits, tls.signature_algorithm)
+}
+
+fn write_http_details(out: &mut String, http: &HttpInfo) -> fmt::Result {
+    match &http.title {
+        Some(title) => writeln!(out, "    🌐 HTTP {}: {}", http.status, title)?,
+        None => writeln!(out, "    🌐 HTTP {}", http.status)?,
+    }
+    if !http.redirects.is_empty() {
+        writeln!(out, "    ↪️  Weiterleitung: {} → {}", http.url, http.redirects.join(" → "))?;
+    }
+    if let Some(server) = &http.server {
+        writeln!(out, "    🖧  Server: {}", server)?;
+    }
+    if let Some(powered_by) = &http.powered_by {
+        writeln!(out, "    ⚙️  X-Powered-By: {}", powered_by)?;
+    }
+    if !http.missing_headers.is_empty() {
+        writeln!(out, "    🛡️  Fehlende Sicherheits-Header: {}", http.missing_headers.join(", "))?;
+    }
+    for panel in &http.admin_panels {
+        writeln!(out, "    🔧 Admin-Oberfläche: {}", panel)?;
+    }
+    Ok(())
+}
+
+fn write_smb_details(out: &mut String, smb: &SmbInfo) -> fmt::Result {
+    let signing = match (smb.signing_required, smb.signing_enabled) {
+        (true, _) => "erforderlich",
+        (false, true) => "aktiviert, nicht erforderlich",
+        (false, false) => "deaktiviert",
+    };
+    writeln!(out, "    🗂️  SMB: Dialekt {}, Signing {}", smb.dialect.as_deref().unwrap_or("unbekannt"), signing)?;
+    if smb.smb1 {
+        writeln!(out, "    ☠️  SMBv1 wird akzeptiert")?;
+    }
+    if let Some(name) = &smb.netbios_name {
+        match &smb.workgroup {
+            Some(workgroup) => writeln!(out, "    🏷️  NetBIOS: {} (Arbeitsgruppe/Domäne: {})", name, workgroup)?,
+            None => writeln!(out, "    🏷️  NetBIOS: {}", name)?,
+        }
+    }
+    if let Some(dns_name) = &smb.dns_name {
+        match &smb.dns_domain {
+            Some(domain) if !dns_name.ends_with(domain.as_str()) => writeln!(out, "    🌍 DNS: {} ({})", dns_name, domain)?,
+            _ => writeln!(out, "    🌍 DNS: {}", dns_name)?,
+        }
+    }
+    if let Some(os) = &smb.os {
+        writeln!(out, "    💻 OS: {}", os)?;
+    }
+    Ok(())
+}
+
+fn write_network_summary(out: &mut String, results: &[HostResult]) -> fmt::Result {
+    let hosts_with_open: Vec<&HostResult> = results.iter()
+        .filter(|result| result.open_ports().next().is_some())
+        .collect();
+    let total_open: usize = results.iter().map(|result| result.open_ports().count()).sum();
+
+    writeln!(out, "{}", "=".repeat(50))?;
+    writeln!(out, "🌐 Netzwerk-Zusammenfassung:")?;
+    writeln!(out, "🖥️  Gescannte Hosts: {}", results.len())?;
+    writeln!(out, "✅ Hosts mit offenen Ports: {}", hosts_with_open.len())?;
+    writeln!(out, "🔓 Offene Ports insgesamt: {}", total_open)?;
+
+    let closed: usize = results.iter().map(|result| result.counts.closed).sum();
+    let filtered: usize = results.iter().map(|result| result.counts.filtered).sum();
+    let unreachable_hosts = results.iter()
+        .filter(|result| result.counts.unreachable > 0 && result.counts.unreachable == result.ports_scanned())
+        .count();
+    writeln!(out, "🚫 Geschlossene Ports insgesamt: {}", closed)?;
+    writeln!(out, "🧱 Gefilterte Ports insgesamt: {}", filtered)?;
+    if unreachable_hosts > 0 {
+        writeln!(out, "📵 Nicht erreichbare Hosts: {}", unreachable_hosts)?;
+    }
+
+    if hosts_with_open.is_empty() {
+        return writeln!(out, "🚫 Auf keinem Host wurden offene Ports gefunden.");
+    }
+
+    writeln!(out, "\n📋 Hosts mit offenen Ports:")?;
+    for result in &hosts_with_open {
+        let services: Vec<String> = result.open_ports()
+            .map(|port| format!("{} ({})", port_label(port), port.service_name()))
+            .collect();
+        writeln!(out, "  • {}: {}", result.label(), services.join(", "))?;
+    }
+
+    // Welche Dienste kommen im Netzwerk wie oft vor?
+    let mut port_counts: Vec<(&PortResult, usize)> = Vec::new();
+    for result in &hosts_with_open {
+        for port in result.open_ports() {
+            match port_counts.iter_mut().find(|(p, _)| p.port == port.port && p.protocol == port.protocol) {
+                Some((_, count)) => *count += 1,
+                None => port_counts.push((port, 1)),
+            }
+        }
+    }
+    port_counts.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then(a.0.port.cmp(&b.0.port))
+            .then(a.0.protocol.as_str().cmp(b.0.protocol.as_str()))
+    });
+
+    writeln!(out, "\n📈 Dienste im Netzwerk:")?;
+    for (port, count) in port_counts {
+        writeln!(out, "  • Port {} ({}): {} Host(s)", port_label(port),
+                 get_service_name(port.port, port.protocol), count)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn host(ip: &str, open: &[(u16, &str)]) -> HostResult {
+        let ports: Vec<Value> = open.iter()
+            .map(|(port, protocol)| json!({ "port": port, "protocol": protocol, "state": "open", "warnings": [] }))
+            .collect();
+        serde_json::from_value(json!({
+            "ip": ip,
+            "ports": ports,
+            "counts": { "open": open.len(), "open_filtered": 0, "closed": 0, "filtered": 0, "unreachable": 0 },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn groups_services_by_port_and_protocol() {
+        let hosts = [
+            host("192.0.2.1", &[(53, "tcp"), (53, "udp")]),
+            host("192.0.2.2", &[(53, "udp"), (22, "tcp")]),
+            host("192.0.2.3", &[]),
+        ];
+        let mut out = String::new();
+        write_network_summary(&mut out, &hosts).unwrap();
+
+        let services: Vec<&str> = out.lines()
+            .skip_while(|line| !line.contains("Dienste im Netzwerk"))
+            .skip(1)
+            .collect();
+        assert_eq!(services, [
+            "  • Port 53/udp (DNS): 2 Host(s)",
+            "  • Port 22 (SSH): 1 Host(s)",
+            "  • Port 53 (DNS): 1 Host(s)",
+        ]);
+        assert!(out.contains("✅ Hosts mit offenen Ports: 2"));
+        assert!(out.contains("🔓 Offene Ports insgesamt: 4"));
+        assert!(out.contains("  • 192.0.2.1: 53 (DNS), 53/udp (DNS)"));
+    }
+}
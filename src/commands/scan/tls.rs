@@ -0,0 +1,194 @@
+use openssl::asn1::Asn1Time;
+use openssl::pkey::Id;
+use openssl::ssl::{Ssl, SslConnector, SslMethod, SslVerifyMode, SslVersion};
+use openssl::x509::{X509NameRef, X509};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_openssl::SslStream;
+
+use super::{HostResult, PortState, Protocol};
+
+/// Zertifikats- und Verbindungsdetails eines TLS-Ports
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TlsInfo {
+    /// Subject des Zertifikats, z.B. "CN=example.com"
+    pub subject: String,
+    /// Subject Alternative Names (DNS-Namen und IP-Adressen)
+    pub san: Vec<String>,
+    pub issuer: String,
+    pub not_before: String,
+    pub not_after: String,
+    /// Negativ, wenn das Zertifikat bereits abgelaufen ist
+    pub days_until_expiry: i32,
+    /// Schlüsseltyp, z.B. "RSA" oder "EC"
+    pub key_type: String,
+    pub key_bits: u32,
+    pub signature_algorithm: String,
+    /// Ausgehandelte Protokollversion, z.B. "TLSv1.3"
+    pub protocol: String,
+    pub cipher: String,
+    pub self_signed: bool,
+    /// Zusätzlich akzeptierte veraltete Protokolle (TLS 1.0/1.1)
+    pub legacy_protocols: Vec<String>,
+}
+
+/// Prüft alle offenen Ports auf TLS und liest die Zertifikate aus
+pub async fn inspect_tls(results: &mut [HostResult], concurrency: usize, timeout: Duration) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut tasks = JoinSet::new();
+
+    for (host_index, result) in results.iter().enumerate() {
+        let server_name = result.hostname.clone();
+        for (port_index, port) in result.ports.iter().enumerate() {
+            if port.protocol != Protocol::Tcp || port.state != PortState::Open {
+                continue;
+            }
+            let addr = SocketAddr::new(result.ip, port.port);
+            let semaphore = semaphore.clone();
+            let server_name = server_name.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let info = inspect_endpoint(addr, server_name.as_deref(), timeout).await;
+                (host_index, port_index, info)
+            });
+        }
+    }
+
+    while let Some(finished) = tasks.join_next().await {
+        if let Ok((host_index, port_index, info)) = finished {
+            results[host_index].ports[port_index].tls = info;
+        }
+    }
+}
+
+async fn inspect_endpoint(addr: SocketAddr, server_name: Option<&str>, timeout: Duration) -> Option<TlsInfo> {
+    let stream = handshake(addr, server_name, None, timeout).await?;
+    let ssl = stream.ssl();
+    let cert = ssl.peer_certificate()?;
+
+    let protocol = ssl.version_str().to_string();
+    let cipher = ssl.current_cipher().map(|c| c.name().to_string()).unwrap_or_default();
+    drop(stream);
+
+    let mut info = describe_certificate(&cert, protocol, cipher);
+
+    // Veraltete Protokolle separat prüfen, da der Server sonst die höchste
+    // gemeinsame Version aushandelt
+    for (version, name) in [(SslVersion::TLS1, "TLSv1.0"), (SslVersion::TLS1_1, "TLSv1.1")] {
+        if handshake(addr, server_name, Some(version), timeout).await.is_some() {
+            info.legacy_protocols.push(name.to_string());
+        }
+    }
+
+    Some(info)
+}
+
+/// Baut eine TLS-Verbindung ohne Zertifikatsprüfung auf. Mit `only` wird
+/// genau diese Protokollversion angeboten.
+async fn handshake(
+    addr: SocketAddr,
+    server_name: Option<&str>,
+    only: Option<SslVersion>,
+    timeout: Duration,
+) -> Option<SslStream<TcpStream>> {
+    let mut builder = SslConnector::builder(SslMethod::tls_client()).ok()?;
+    builder.set_verify(SslVerifyMode::NONE);
+    // Auch alte Protokolle und schwache Cipher zulassen, damit sie erkannt werden
+    builder.set_security_level(0);
+    builder.set_cipher_list("ALL:COMPLEMENTOFALL").ok()?;
+    builder.set_min_proto_version(only).ok()?;
+    if only.is_some() {
+        builder.set_max_proto_version(only).ok()?;
+    }
+
+    let connector = builder.build();
+    let mut config = connector.configure().ok()?;
+    config.set_verify_hostname(false);
+    config.set_use_server_name_indication(server_name.is_some());
+    let ssl: Ssl = config.into_ssl(server_name.unwrap_or("localhost")).ok()?;
+
+    let tcp = tokio::time::timeout(timeout, TcpStream::connect(addr)).await.ok()?.ok()?;
+    let mut stream = SslStream::new(ssl, tcp).ok()?;
+    tokio::time::timeout(timeout, Pin::new(&mut stream).connect()).await.ok()?.ok()?;
+    Some(stream)
+}
+
+fn describe_certificate(cert: &X509, protocol: String, cipher: String) -> TlsInfo {
+    let san = cert.subject_alt_names()
+        .map(|names| {
+            names.iter()
+                .filter_map(|name| {
+                    name.dnsname().map(str::to_string).or_else(|| {
+                        name.ipaddress().and_then(|bytes| match bytes.len() {
+                            4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).to_string()),
+                            16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).to_string()),
+                            _ => None,
+                        })
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let days_until_expiry = Asn1Time::days_from_now(0)
+        .ok()
+        .and_then(|now| now.diff(cert.not_after()).ok())
+        .map(|diff| diff.days)
+        .unwrap_or_default();
+
+    let (key_type, key_bits) = match cert.public_key() {
+        Ok(key) => {
+            let key_type = match key.id() {
+                Id::RSA => "RSA",
+                Id::EC => "EC",
+                Id::DSA => "DSA",
+                Id::ED25519 => "Ed25519",
+                Id::ED448 => "Ed448",
+                _ => "Unbekannt",
+            };
+            (key_type.to_string(), key.bits())
+        }
+        Err(_) => ("Unbekannt".to_string(), 0),
+    };
+
+    // Selbstsigniert: Aussteller = Subject und die Signatur passt zum eigenen Schlüssel
+    let self_signed = cert.issuer_name().try_cmp(cert.subject_name())
+        .map(|ordering| ordering.is_eq())
+        .unwrap_or(false)
+        && cert.public_key().and_then(|key| cert.verify(&key)).unwrap_or(false);
+
+    let signature_nid = cert.signature_algorithm().object().nid();
+
+    TlsInfo {
+        subject: format_name(cert.subject_name()),
+        san,
+        issuer: format_name(cert.issuer_name()),
+        not_before: cert.not_before().to_string(),
+        not_after: cert.not_after().to_string(),
+        days_until_expiry,
+        key_type,
+        key_bits,
+        signature_algorithm: signature_nid.long_name().unwrap_or("Unbekannt").to_string(),
+        protocol,
+        cipher,
+        self_signed,
+        legacy_protocols: Vec::new(),
+    }
+}
+
+fn format_name(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            let value = entry.data().as_utf8().map(|v| v.to_string()).unwrap_or_default();
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
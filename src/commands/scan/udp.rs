@@ -0,0 +1,380 @@
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+use super::banners::ServiceInfo;
+use super::scanner::is_unreachable;
+use super::PortState;
+
+// Wie oft eine Sonde ohne Antwort wiederholt wird (UDP kann Pakete verlieren)
+const RETRIES: usize = 2;
+
+/// Sendet eine zum Port passende UDP-Sonde und wertet die Reaktion aus.
+///
+/// Eine Antwort bedeutet "offen", ein ICMP Port Unreachable (vom Kernel als
+/// `ConnectionRefused` gemeldet) "geschlossen", ein ICMP Host/Network
+/// Unreachable "unerreichbar". Ohne Reaktion lässt sich offen nicht von
+/// gefiltert unterscheiden.
+pub async fn probe_udp_port(addr: SocketAddr, timeout: Duration) -> (PortState, Option<ServiceInfo>) {
+    let bind_addr = match addr.ip() {
+        IpAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        IpAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    };
+
+    let socket = match UdpSocket::bind(bind_addr).await {
+        Ok(socket) => socket,
+        Err(_) => return (PortState::OpenFiltered, None),
+    };
+    if socket.connect(addr).await.is_err() {
+        return (PortState::OpenFiltered, None);
+    }
+
+    let payload = payload_for_port(addr.port());
+    let mut buffer = [0u8; 2048];
+
+    for _ in 0..RETRIES {
+        if let Err(e) = socket.send(&payload).await {
+            if e.kind() == ErrorKind::ConnectionRefused {
+                return (PortState::Closed, None);
+            }
+            if is_unreachable(&e) {
+                return (PortState::Unreachable, None);
+            }
+            continue;
+        }
+
+        match tokio::time::timeout(timeout, socket.recv(&mut buffer)).await {
+            Ok(Ok(n)) => return (PortState::Open, describe_response(addr.port(), &buffer[..n])),
+            Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => {
+                return (PortState::Closed, None);
+            }
+            Ok(Err(e)) if is_unreachable(&e) => return (PortState::Unreachable, None),
+            _ => {}
+        }
+    }
+
+    (PortState::OpenFiltered, None)
+}
+
+/// Protokollgerechte Nutzdaten, damit der Dienst überhaupt antwortet
+fn payload_for_port(port: u16) -> Vec<u8> {
+    match port {
+        // DNS: Anfrage nach den NS-Einträgen der Root-Zone
+        53 | 5353 => vec![
+            0x53, 0x48, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x02, 0x00, 0x01,
+        ],
+        // TFTP: Leseanfrage für eine nicht existierende Datei
+        69 => [&[0x00, 0x01][..], b"shc-probe\0octet\0"].concat(),
+        // NTP: Client-Anfrage (Version 3, Mode 3)
+        123 => {
+            let mut packet = vec![0u8; 48];
+            packet[0] = 0x1b;
+            packet
+        }
+        // NetBIOS-NS: Node Status Request für "*"
+        137 => {
+            let mut packet = vec![
+                0x53, 0x48, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+            ];
+            packet.extend_from_slice(b"CKAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+            packet.extend_from_slice(&[0x00, 0x00, 0x21, 0x00, 0x01]);
+            packet
+        }
+        // SNMPv1 GetRequest mit Community "public" für sysDescr.0
+        161 => vec![
+            0x30, 0x29, 0x02, 0x01, 0x00, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c',
+            0xa0, 0x1c, 0x02, 0x04, 0x53, 0x48, 0x43, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00,
+            0x30, 0x0e, 0x30, 0x0c, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00,
+            0x05, 0x00,
+        ],
+        // RIPv2: Anfrage nach der gesamten Routing-Tabelle
+        520 => {
+            let mut packet = vec![0x01, 0x02, 0x00, 0x00];
+            packet.extend_from_slice(&[0u8; 16]);
+            packet.extend_from_slice(&[0x00, 0x00, 0x00, 0x10]);
+            packet
+        }
+        // IPMI: RMCP/ASF Presence Ping
+        623 => vec![0x06, 0x00, 0xff, 0x06, 0x00, 0x00, 0x11, 0xbe, 0x80, 0x00, 0x00, 0x00],
+        // OpenVPN: P_CONTROL_HARD_RESET_CLIENT_V2
+        1194 => vec![
+            0x38, 0x53, 0x48, 0x43, 0x2d, 0x54, 0x6f, 0x6f, 0x6c, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+        // SQL Server Browser: Instanzen auflisten
+        1434 => vec![0x02],
+        // SSDP: M-SEARCH per Unicast
+        1900 => b"M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: ssdp:all\r\n\r\n".to_vec(),
+        // STUN: Binding Request
+        3478 => vec![
+            0x00, 0x01, 0x00, 0x00, 0x21, 0x12, 0xa4, 0x42,
+            0x53, 0x48, 0x43, 0x2d, 0x54, 0x6f, 0x6f, 0x6c, 0x2d, 0x55, 0x44, 0x50,
+        ],
+        // Memcached: "version" mit UDP-Frame-Header
+        11211 => [&[0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00][..], b"version\r\n"].concat(),
+        // Unbekannte Dienste: leeres Datagramm, löst ggf. ICMP Unreachable aus
+        _ => Vec::new(),
+    }
+}
+
+/// Leitet aus der Antwort Dienst und ggf. Details ab
+fn describe_response(port: u16, response: &[u8]) -> Option<ServiceInfo> {
+    let mut info = ServiceInfo::new(super::get_service_name(port, super::Protocol::Udp));
+
+    match port {
+        123 if response.len() >= 48 => {
+            info.version = Some(format!("v{}", (response[0] >> 3) & 0x07));
+            info.extra_info = Some(format!("Stratum {}", response[1]));
+        }
+        137 => info.banner = netbios_names(response).map(|names| names.join(", ")),
+        161 => {
+            info.extra_info = Some("Community 'public'".to_string());
+            info.banner = snmp_sys_descr(response);
+        }
+        1900 | 11211 => {
+            // Memcached stellt der Antwort einen 8 Byte langen Frame-Header voran
+            let payload = if port == 11211 { response.get(8..).unwrap_or_default() } else { response };
+            let text = String::from_utf8_lossy(payload);
+            info.banner = text.lines()
+                .find(|line| line.to_lowercase().starts_with("server:") || line.starts_with("VERSION"))
+                .map(|line| line.trim().to_string());
+        }
+        _ => {}
+    }
+
+    Some(info)
+}
+
+/// Ein Eintrag der NetBIOS-Namenstabelle
+pub struct NetbiosName {
+    pub name: String,
+    /// Dienst-Suffix, z.B. 0x00 (Rechner/Arbeitsgruppe) oder 0x20 (Dateiserver)
+    pub suffix: u8,
+    pub is_group: bool,
+}
+
+/// Fragt die NetBIOS-Namenstabelle eines Hosts ab (Node Status über 137/udp)
+pub async fn netbios_node_status(ip: IpAddr, timeout: Duration) -> Option<Vec<NetbiosName>> {
+    let bind_addr = match ip {
+        IpAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        IpAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    };
+    let socket = UdpSocket::bind(bind_addr).await.ok()?;
+    socket.connect(SocketAddr::new(ip, 137)).await.ok()?;
+
+    let mut buffer = [0u8; 2048];
+    for _ in 0..RETRIES {
+        socket.send(&payload_for_port(137)).await.ok()?;
+        if let Ok(Ok(n)) = tokio::time::timeout(timeout, socket.recv(&mut buffer)).await {
+            return netbios_entries(&buffer[..n]);
+        }
+    }
+    None
+}
+
+/// Liest die Namen aus einer NetBIOS Node Status Response
+fn netbios_entries(response: &[u8]) -> Option<Vec<NetbiosName>> {
+    // Header (12) + Name (34) + Typ/Klasse/TTL/Länge (10)
+    let count = usize::from(*response.get(56)?);
+    let mut names = Vec::new();
+
+    for index in 0..count {
+        let start = 57 + index * 18;
+        let entry = response.get(start..start + 18)?;
+        names.push(NetbiosName {
+            name: String::from_utf8_lossy(&entry[..15]).trim_end().to_string(),
+            suffix: entry[15],
+            is_group: entry[16] & 0x80 != 0,
+        });
+    }
+
+    Some(names)
+}
+
+/// Lesbare Namen aus einer NetBIOS Node Status Response
+fn netbios_names(response: &[u8]) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+
+    for entry in netbios_entries(response)? {
+        let kind = match (entry.suffix, entry.is_group) {
+            (0x00, false) => "Rechner",
+            (0x00, true) => "Arbeitsgruppe",
+            (0x20, false) => "Dateiserver",
+            _ => continue,
+        };
+        let label = format!("{} ({})", entry.name, kind);
+        if !names.contains(&label) {
+            names.push(label);
+        }
+    }
+
+    Some(names)
+}
+
+/// Sucht den sysDescr-Wert (OID 1.3.6.1.2.1.1.1.0) in einer SNMP-Antwort
+fn snmp_sys_descr(response: &[u8]) -> Option<String> {
+    const OID: [u8; 8] = [0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00];
+
+    let position = response.windows(OID.len()).position(|window| window == OID)? + OID.len();
+    if *response.get(position)? != 0x04 {
+        return None;
+    }
+    // Längen ab 128 Byte werden in der Langform (0x81 <Länge>) kodiert
+    let (length, start) = match *response.get(position + 1)? {
+        0x81 => (usize::from(*response.get(position + 2)?), position + 3),
+        length => (usize::from(length), position + 2),
+    };
+    let value = response.get(start..start + length)?;
+
+    Some(String::from_utf8_lossy(value).chars().filter(|c| !c.is_control()).take(120).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Node Status Response wie von Samba bzw. Windows
+    fn node_status_response(entries: &[(&str, u8, u16)]) -> Vec<u8> {
+        let mut packet = vec![0x53, 0x48, 0x84, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x20];
+        packet.extend_from_slice(b"CKAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+        packet.extend_from_slice(&[0x00, 0x00, 0x21, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        packet.extend_from_slice(&((entries.len() * 18 + 47) as u16).to_be_bytes());
+        packet.push(entries.len() as u8);
+        for (name, suffix, flags) in entries {
+            packet.extend_from_slice(format!("{:<15}", name).as_bytes());
+            packet.push(*suffix);
+            packet.extend_from_slice(&flags.to_be_bytes());
+        }
+        // Statistik mit MAC-Adresse
+        packet.extend_from_slice(&[0x00, 0x11, 0x32, 0x0a, 0x0b, 0x0c]);
+        packet.extend_from_slice(&[0u8; 40]);
+        packet
+    }
+
+    /// SNMPv1 GetResponse für sysDescr.0
+    fn snmp_response(value: &[u8]) -> Vec<u8> {
+        fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+            let mut encoded = vec![tag];
+            if content.len() >= 128 {
+                encoded.extend_from_slice(&[0x81, content.len() as u8]);
+            } else {
+                encoded.push(content.len() as u8);
+            }
+            encoded.extend_from_slice(content);
+            encoded
+        }
+
+        let oid = [0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00];
+        let binding = tlv(0x30, &[tlv(0x06, &oid), tlv(0x04, value)].concat());
+        let pdu = [
+            tlv(0x02, &[0x53, 0x48, 0x43, 0x01]),
+            tlv(0x02, &[0x00]),
+            tlv(0x02, &[0x00]),
+            tlv(0x30, &binding),
+        ].concat();
+        tlv(0x30, &[tlv(0x02, &[0x00]), tlv(0x04, b"public"), tlv(0xa2, &pdu)].concat())
+    }
+
+    #[test]
+    fn parses_netbios_node_status() {
+        let response = node_status_response(&[
+            ("NAS", 0x00, 0x0400),
+            ("NAS", 0x03, 0x0400),
+            ("NAS", 0x20, 0x0400),
+            ("WORKGROUP", 0x00, 0x8400),
+            ("WORKGROUP", 0x1e, 0x8400),
+        ]);
+
+        let entries = netbios_entries(&response).unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!((entries[0].name.as_str(), entries[0].suffix, entries[0].is_group), ("NAS", 0x00, false));
+        assert_eq!((entries[3].name.as_str(), entries[3].suffix, entries[3].is_group), ("WORKGROUP", 0x00, true));
+
+        assert_eq!(
+            netbios_names(&response).unwrap(),
+            ["NAS (Rechner)", "NAS (Dateiserver)", "WORKGROUP (Arbeitsgruppe)"]
+        );
+        let info = describe_response(137, &response).unwrap();
+        assert_eq!(info.service, "NetBIOS-NS");
+        assert_eq!(info.banner.as_deref(), Some("NAS (Rechner), NAS (Dateiserver), WORKGROUP (Arbeitsgruppe)"));
+    }
+
+    #[test]
+    fn rejects_truncated_netbios_responses() {
+        let response = node_status_response(&[("NAS", 0x00, 0x0400), ("NAS", 0x20, 0x0400)]);
+        // Zweiter Eintrag abgeschnitten
+        assert!(netbios_entries(&response[..57 + 18 + 10]).is_none());
+        assert!(netbios_entries(&response[..40]).is_none());
+        assert!(netbios_entries(&[]).is_none());
+        assert_eq!(netbios_entries(&node_status_response(&[])).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn reads_snmp_sys_descr() {
+        let response = snmp_response(b"Linux nas 5.10.60 #1 SMP x86_64");
+        assert_eq!(snmp_sys_descr(&response).as_deref(), Some("Linux nas 5.10.60 #1 SMP x86_64"));
+
+        let info = describe_response(161, &response).unwrap();
+        assert_eq!(info.service, "SNMP");
+        assert_eq!(info.extra_info.as_deref(), Some("Community 'public'"));
+        assert_eq!(info.banner.as_deref(), Some("Linux nas 5.10.60 #1 SMP x86_64"));
+
+        // Cisco-typische Beschreibung mit Zeilenumbrüchen und Langform-Länge
+        let long = "Cisco IOS Software, C2960 Software (C2960-LANBASEK9-M), Version 15.0(2)SE11, RELEASE SOFTWARE (fc3)\r\n\
+                    Technical Support: http://www.cisco.com/techsupport";
+        let descr = snmp_sys_descr(&snmp_response(long.as_bytes())).unwrap();
+        assert!(descr.starts_with("Cisco IOS Software, C2960 Software"));
+        assert!(!descr.contains('\r') && descr.chars().count() == 120);
+    }
+
+    #[test]
+    fn ignores_snmp_errors_and_truncation() {
+        // noSuchName: Wert ist NULL statt OCTET STRING
+        let mut response = snmp_response(b"");
+        let value = response.len() - 2;
+        response[value] = 0x05;
+        assert!(snmp_sys_descr(&response).is_none());
+
+        let response = snmp_response(b"Linux nas");
+        assert!(snmp_sys_descr(&response[..response.len() - 1]).is_none());
+        assert!(snmp_sys_descr(b"\x30\x00").is_none());
+
+        let info = describe_response(161, b"garbage").unwrap();
+        assert_eq!(info.service, "SNMP");
+        assert!(info.banner.is_none());
+    }
+
+    #[test]
+    fn describes_dns_and_ntp_responses() {
+        // Antwort auf die NS-Anfrage der Root-Zone, gekürzt auf einen Eintrag
+        let mut dns = payload_for_port(53);
+        dns[2..8].copy_from_slice(&[0x81, 0x80, 0x00, 0x01, 0x00, 0x01]);
+        dns.extend_from_slice(&[0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x07, 0xe9, 0x00, 0x00, 0x14, 0x01, b'a']);
+        dns.extend_from_slice(b"\x0croot-servers\x03net\x00");
+        let info = describe_response(53, &dns).unwrap();
+        assert_eq!(info.service, "DNS");
+        assert!(info.banner.is_none() && info.version.is_none());
+        assert_eq!(describe_response(5353, &dns).unwrap().service, "mDNS");
+
+        let mut ntp = vec![0u8; 48];
+        ntp[0] = 0x24;
+        ntp[1] = 2;
+        let info = describe_response(123, &ntp).unwrap();
+        assert_eq!(info.version.as_deref(), Some("v4"));
+        assert_eq!(info.extra_info.as_deref(), Some("Stratum 2"));
+        assert!(describe_response(123, &ntp[..12]).unwrap().version.is_none());
+    }
+
+    #[test]
+    fn reads_memcached_and_ssdp_banners() {
+        let memcached = [&[0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00][..], b"VERSION 1.6.21\r\n"].concat();
+        assert_eq!(describe_response(11211, &memcached).unwrap().banner.as_deref(), Some("VERSION 1.6.21"));
+
+        let ssdp = b"HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=1800\r\nSERVER: Linux/5.4 UPnP/1.0 MiniUPnPd/2.2\r\n\r\n";
+        assert_eq!(
+            describe_response(1900, ssdp).unwrap().banner.as_deref(),
+            Some("SERVER: Linux/5.4 UPnP/1.0 MiniUPnPd/2.2")
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Eingebettete Hersteller-Tabelle (OUI -> Hersteller)
+const VENDORS: &str = include_str!("vendors.txt");
+
+fn vendor_table() -> &'static HashMap<&'static str, &'static str> {
+    static TABLE: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        VENDORS.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (oui, vendor) = line.split_once(char::is_whitespace)?;
+                Some((oui, vendor.trim()))
+            })
+            .collect()
+    })
+}
+
+/// Hersteller zu einer MAC-Adresse im Format `aa:bb:cc:dd:ee:ff`
+pub fn get_vendor(mac: &str) -> Option<&'static str> {
+    let oui = mac.get(..8)?.to_uppercase();
+    if let Some(vendor) = vendor_table().get(oui.as_str()) {
+        return Some(vendor);
+    }
+
+    // Gesetztes "locally administered"-Bit: zufällige bzw. virtuelle Adresse
+    let first_octet = u8::from_str_radix(mac.get(..2)?, 16).ok()?;
+    if first_octet & 0x02 != 0 {
+        return Some("lokal verwaltete Adresse");
+    }
+    None
+}
@@ -0,0 +1,289 @@
+use std::fmt::{self, Write};
+
+use super::report::ScanReport;
+use super::{HostResult, PortResult, PortState, Protocol};
+
+/// Bericht im XML-Format von nmap (`-oX`), damit vorhandene Viewer und
+/// Importer (z.B. Zenmap, xsltproc mit nmap.xsl) ihn öffnen können
+pub fn render_xml(report: &ScanReport) -> String {
+    let mut out = String::new();
+    // In einen String zu schreiben schlägt nie fehl
+    let _ = write_nmaprun(&mut out, report);
+    out
+}
+
+fn write_nmaprun(out: &mut String, report: &ScanReport) -> fmt::Result {
+    let started = chrono::DateTime::parse_from_rfc3339(&report.started_at).ok();
+    let start = started.map(|time| time.timestamp()).unwrap_or_default();
+    let start_str = started.map(|time| time.format("%a %b %e %H:%M:%S %Y").to_string()).unwrap_or_default();
+    let end = start + report.duration_seconds.round() as i64;
+    let args: Vec<String> = std::env::args().collect();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, "<!DOCTYPE nmaprun>")?;
+    writeln!(out, r#"<?xml-stylesheet href="file:///usr/share/nmap/nmap.xsl" type="text/xsl"?>"#)?;
+    writeln!(
+        out,
+        r#"<nmaprun scanner="shc-tool" args="{}" start="{}" startstr="{}" version="{}" xmloutputversion="1.05">"#,
+        escape(&args.join(" ")), start, escape(&start_str), env!("CARGO_PKG_VERSION"),
+    )?;
+
+    if report.discover_only {
+        writeln!(out, r#"<scaninfo type="ping" protocol="ip" numservices="0" services=""/>"#)?;
+    } else {
+        let scan_type = match report.protocol {
+            Protocol::Tcp => "connect",
+            Protocol::Udp => "udp",
+        };
+        writeln!(
+            out,
+            r#"<scaninfo type="{}" protocol="{}" numservices="{}" services="{}"/>"#,
+            scan_type, report.protocol.as_str(), report.port_count, report.ports,
+        )?;
+    }
+
+    for host in &report.hosts {
+        write_host(out, host, start, end)?;
+    }
+
+    let up = report.hosts.len();
+    let down = report.hosts_total.saturating_sub(up);
+    writeln!(out, "<runstats>")?;
+    writeln!(
+        out,
+        r#"<finished time="{}" elapsed="{:.2}" summary="{} IP address(es) ({} host(s) up) scanned in {:.2} seconds" exit="success"/>"#,
+        end, report.duration_seconds, report.hosts_total, up, report.duration_seconds,
+    )?;
+    writeln!(out, r#"<hosts up="{}" down="{}" total="{}"/>"#, up, down, report.hosts_total)?;
+    writeln!(out, "</runstats>")?;
+    writeln!(out, "</nmaprun>")
+}
+
+fn write_host(out: &mut String, host: &HostResult, start: i64, end: i64) -> fmt::Result {
+    writeln!(out, r#"<host starttime="{}" endtime="{}">"#, start, end)?;
+    let reason = match host.discovered_by.as_deref() {
+        Some(method) if method.starts_with("ICMP") => "echo-reply",
+        Some(method) if method.starts_with("ARP") => "arp-response",
+        Some(method) if method.contains("RST") => "reset",
+        Some(_) => "syn-ack",
+        None => "user-set",
+    };
+    writeln!(out, r#"<status state="up" reason="{}" reason_ttl="0"/>"#, reason)?;
+
+    let addr_type = if host.ip.is_ipv4() { "ipv4" } else { "ipv6" };
+    writeln!(out, r#"<address addr="{}" addrtype="{}"/>"#, host.ip, addr_type)?;
+    if let Some(mac) = &host.mac {
+        match &host.vendor {
+            Some(vendor) => writeln!(
+                out, r#"<address addr="{}" addrtype="mac" vendor="{}"/>"#,
+                escape(&mac.to_uppercase()), escape(vendor),
+            )?,
+            None => writeln!(out, r#"<address addr="{}" addrtype="mac"/>"#, escape(&mac.to_uppercase()))?,
+        }
+    }
+
+    writeln!(out, "<hostnames>")?;
+    if let Some(name) = &host.hostname {
+        writeln!(out, r#"<hostname name="{}" type="user"/>"#, escape(name))?;
+    }
+    if let Some(name) = &host.reverse_dns {
+        writeln!(out, r#"<hostname name="{}" type="PTR"/>"#, escape(name))?;
+    }
+    writeln!(out, "</hostnames>")?;
+
+    writeln!(out, "<ports>")?;
+    // Nicht einzeln gelistete Ports fasst nmap als "extraports" zusammen
+    let listed = |state: PortState| host.ports.iter().filter(|port| port.state == state).count();
+    for (state, count, reason) in [
+        (PortState::Closed, host.counts.closed, "resets"),
+        (PortState::Filtered, host.counts.filtered, "no-responses"),
+        (PortState::Unreachable, host.counts.unreachable, "host-unreaches"),
+    ] {
+        let hidden = count.saturating_sub(listed(state));
+        if hidden > 0 {
+            writeln!(out, r#"<extraports state="{}" count="{}">"#, state.as_str(), hidden)?;
+            writeln!(out, r#"<extrareasons reason="{}" count="{}"/>"#, reason, hidden)?;
+            writeln!(out, "</extraports>")?;
+        }
+    }
+    for port in &host.ports {
+        write_port(out, port)?;
+    }
+    writeln!(out, "</ports>")?;
+    writeln!(out, "</host>")
+}
+
+fn write_port(out: &mut String, port: &PortResult) -> fmt::Result {
+    writeln!(out, r#"<port protocol="{}" portid="{}">"#, port.protocol.as_str(), port.port)?;
+    let reason = match (port.state, port.protocol) {
+        (PortState::Open, Protocol::Tcp) => "syn-ack",
+        (PortState::Open, Protocol::Udp) => "udp-response",
+        (PortState::Closed, Protocol::Tcp) => "conn-refused",
+        (PortState::Closed, Protocol::Udp) => "port-unreach",
+        (PortState::Unreachable, _) => "host-unreach",
+        (PortState::OpenFiltered | PortState::Filtered, _) => "no-response",
+    };
+    writeln!(out, r#"<state state="{}" reason="{}" reason_ttl="0"/>"#, port.state.as_str(), reason)?;
+
+    let name = port.service_name().to_lowercase();
+    match &port.service {
+        Some(info) => {
+            let mut attributes = format!(r#"name="{}""#, escape(&name));
+            for (key, value) in [("product", &info.product), ("version", &info.version), ("extrainfo", &info.extra_info)] {
+                if let Some(value) = value {
+                    write!(attributes, r#" {}="{}""#, key, escape(value))?;
+                }
+            }
+            if port.tls.is_some() {
+                attributes.push_str(r#" tunnel="ssl""#);
+            }
+            writeln!(out, r#"<service {} method="probed" conf="10"/>"#, attributes)?;
+        }
+        None => writeln!(out, r#"<service name="{}" method="table" conf="3"/>"#, escape(&name))?,
+    }
+
+    if let Some(banner) = port.service.as_ref().and_then(|info| info.banner.as_ref()) {
+        writeln!(out, r#"<script id="banner" output="{}"/>"#, escape(banner))?;
+    }
+    if let Some(tls) = &port.tls {
+        let output = format!(
+            "Subject: {}\nIssuer: {}\nPublic Key type: {}\nPublic Key bits: {}\nSignature Algorithm: {}\nNot valid before: {}\nNot valid after:  {}",
+            tls.subject, tls.issuer, tls.key_type, tls.key_bits, tls.signature_algorithm, tls.not_before, tls.not_after,
+        );
+        writeln!(out, r#"<script id="ssl-cert" output="{}"/>"#, escape(&output))?;
+    }
+    if let Some(http) = &port.http {
+        if let Some(title) = &http.title {
+            writeln!(out, r#"<script id="http-title" output="{}"/>"#, escape(title))?;
+        }
+        if let Some(server) = &http.server {
+            writeln!(out, r#"<script id="http-server-header" output="{}"/>"#, escape(server))?;
+        }
+    }
+    if let Some(smb) = &port.smb {
+        let mut output = Vec::new();
+        if let Some(os) = &smb.os {
+            output.push(format!("OS: {}", os));
+        }
+        if let Some(name) = &smb.netbios_name {
+            output.push(format!("NetBIOS computer name: {}", name));
+        }
+        if let Some(workgroup) = &smb.workgroup {
+            output.push(format!("Workgroup: {}", workgroup));
+        }
+        if let Some(dns_name) = &smb.dns_name {
+            output.push(format!("FQDN: {}", dns_name));
+        }
+        if !output.is_empty() {
+            writeln!(out, r#"<script id="smb-os-discovery" output="{}"/>"#, escape(&output.join("\n")))?;
+        }
+        let signing = if smb.signing_required { "required" } else if smb.signing_enabled { "enabled but not required" } else { "disabled" };
+        writeln!(
+            out, r#"<script id="smb-security-mode" output="{}"/>"#,
+            escape(&format!("Dialect: {}\nSMBv1: {}\nMessage signing {}",
+                            smb.dialect.as_deref().unwrap_or("unknown"), smb.smb1, signing)),
+        )?;
+    }
+    if !port.warnings.is_empty() {
+        let output: Vec<String> = port.warnings.iter()
+            .map(|finding| format!("[{}] {}", finding.severity.label(), finding.message))
+            .collect();
+        writeln!(out, r#"<script id="shc-warnings" output="{}"/>"#, escape(&output.join("\n")))?;
+    }
+    writeln!(out, "</port>")
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#xa;"),
+            // In XML 1.0 nicht erlaubte Steuerzeichen weglassen
+            c if c.is_control() && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report() -> ScanReport {
+        serde_json::from_value(json!({
+            "target": "192.0.2.0/24",
+            "protocol": "tcp",
+            "started_at": "2026-01-01T12:00:00Z",
+            "duration_seconds": 2.5,
+            "ports": "22,80,443",
+            "port_count": 3,
+            "hosts_total": 254,
+            "discover_only": false,
+            "hosts": [{
+                "ip": "192.0.2.10",
+                "hostname": "nas.local",
+                "mac": "00:11:32:0a:0b:0c",
+                "vendor": "Synology",
+                "discovered_by": "ARP",
+                "ports": [
+                    {
+                        "port": 22,
+                        "protocol": "tcp",
+                        "state": "open",
+                        "service": { "service": "SSH", "product": "OpenSSH", "version": "8.9p1",
+                                     "banner": "SSH-2.0-OpenSSH_8.9p1 <test> & \"more\"" },
+                        "warnings": [],
+                    },
+                    { "port": 80, "protocol": "tcp", "state": "open", "warnings": [] },
+                ],
+                "counts": { "open": 2, "open_filtered": 0, "closed": 1, "filtered": 0, "unreachable": 0 },
+            }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn renders_nmap_structure() {
+        let xml = render_xml(&report());
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE nmaprun>\n"));
+        assert!(xml.contains(r#"start="1767268800""#));
+        assert!(xml.contains(r#"<scaninfo type="connect" protocol="tcp" numservices="3" services="22,80,443"/>"#));
+        assert!(xml.contains(r#"<status state="up" reason="arp-response" reason_ttl="0"/>"#));
+        assert!(xml.contains(r#"<address addr="192.0.2.10" addrtype="ipv4"/>"#));
+        assert!(xml.contains(r#"<address addr="00:11:32:0A:0B:0C" addrtype="mac" vendor="Synology"/>"#));
+        assert!(xml.contains(r#"<hostname name="nas.local" type="user"/>"#));
+        assert!(xml.contains("<extraports state=\"closed\" count=\"1\">\n<extrareasons reason=\"resets\" count=\"1\"/>"));
+        assert!(xml.contains(r#"<hosts up="1" down="253" total="254"/>"#));
+        assert!(xml.trim_end().ends_with("</nmaprun>"));
+    }
+
+    #[test]
+    fn renders_ports_and_escapes_values() {
+        let xml = render_xml(&report());
+        assert!(xml.contains(
+            "<port protocol=\"tcp\" portid=\"22\">\n<state state=\"open\" reason=\"syn-ack\" reason_ttl=\"0\"/>\n\
+             <service name=\"ssh\" product=\"OpenSSH\" version=\"8.9p1\" method=\"probed\" conf=\"10\"/>"
+        ));
+        assert!(xml.contains(r#"<script id="banner" output="SSH-2.0-OpenSSH_8.9p1 &lt;test&gt; &amp; &quot;more&quot;"/>"#));
+        assert!(xml.contains(r#"<service name="http" method="table" conf="3"/>"#));
+        assert_eq!(xml.matches("<port ").count(), xml.matches("</port>").count());
+        assert_eq!(escape("a'b\nc\u{1}\td"), "a&apos;b&#xa;c\td");
+    }
+
+    #[test]
+    fn renders_discovery_only_scans() {
+        let mut report = report();
+        report.discover_only = true;
+        report.hosts[0].ports.clear();
+        let xml = render_xml(&report);
+        assert!(xml.contains(r#"<scaninfo type="ping" protocol="ip" numservices="0" services=""/>"#));
+        assert!(!xml.contains("<port "));
+    }
+}
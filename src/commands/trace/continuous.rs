@@ -0,0 +1,240 @@
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, IsTerminal};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+use super::probe::{send_probe, ReplyKind};
+use super::TraceOptions;
+use crate::commands::ping::{format_duration, OutputFormat};
+use crate::commands::scan::reverse_lookup;
+use crate::stats::{Samples, Summary};
+
+/// Messwerte eines Hops über alle Runden
+#[derive(Default)]
+struct HopStats {
+    samples: Samples,
+    /// Antwortende Adressen mit Anzahl der Antworten, in Reihenfolge des Auftretens
+    addresses: Vec<(IpAddr, u32)>,
+    /// traceroute-Kürzel der letzten Unreachable-Meldung
+    code: Option<&'static str>,
+}
+
+impl HopStats {
+    fn record_address(&mut self, address: IpAddr) {
+        match self.addresses.iter_mut().find(|(known, _)| *known == address) {
+            Some((_, replies)) => *replies += 1,
+            None => self.addresses.push((address, 1)),
+        }
+    }
+}
+
+/// Stand aller Hops während `--continuous`
+struct PathState {
+    hops: BTreeMap<u8, HopStats>,
+    /// Höchste TTL, die noch abgefragt wird; sinkt, sobald das Ziel antwortet
+    last_ttl: u8,
+    names: HashMap<IpAddr, Option<String>>,
+    cycles: u64,
+}
+
+/// Abschlussbericht für `--format json`
+#[derive(Serialize)]
+struct ContinuousReport {
+    target: String,
+    ip: IpAddr,
+    mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    started_at: String,
+    ended_at: String,
+    cycles: u64,
+    hops: Vec<HopReport>,
+}
+
+#[derive(Serialize)]
+struct HopReport {
+    ttl: u8,
+    addresses: Vec<AddressRecord>,
+    last_ms: Option<f64>,
+    statistics: Summary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct AddressRecord {
+    address: IpAddr,
+    hostname: Option<String>,
+    replies: u32,
+}
+
+/// Fragt alle Hops in Runden ab, bis Strg+C oder `--count` Runden erreicht
+/// sind, und zeigt die Tabelle im Terminal laufend neu an (wie mtr)
+pub async fn trace_continuous(target: &str, address: SocketAddr, port: Option<u16>, options: &TraceOptions, header: &str) {
+    let text = options.format == OutputFormat::Text;
+    let live = text && io::stdout().is_terminal();
+    if text && !live {
+        println!("{}", header);
+        println!("👀 Messe fortlaufend, Bericht folgt nach dem Ende (Strg+C)");
+    }
+
+    let started_at = chrono::Local::now();
+    let mut state = PathState { hops: BTreeMap::new(), last_ttl: options.max_hops, names: HashMap::new(), cycles: 0 };
+    let mut lookups = JoinSet::new();
+
+    let run = async {
+        loop {
+            let started = Instant::now();
+            run_cycle(address, options, &mut state, &mut lookups).await?;
+            // Inzwischen aufgelöste Hostnamen übernehmen
+            while let Some(Ok((ip, name))) = lookups.try_join_next() {
+                state.names.insert(ip, name);
+            }
+
+            if live {
+                // Bildschirm löschen und Tabelle oben neu zeichnen
+                print!("\x1b[H\x1b[2J");
+                println!("{}", header);
+                println!("🔄 Runde {} seit {}, beenden mit Strg+C\n",
+                         state.cycles, started_at.format("%H:%M:%S"));
+                print_table(&state);
+            }
+            if options.count != 0 && state.cycles >= u64::from(options.count) {
+                return Ok::<(), io::Error>(());
+            }
+            tokio::time::sleep(options.interval.saturating_sub(started.elapsed())).await;
+        }
+    };
+    let result = tokio::select! {
+        result = run => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    if let Err(e) = result {
+        println!("❌ Probes konnten nicht gesendet werden: {}", e);
+        println!("💡 Tipp: Für ICMP Root-Rechte (CAP_NET_RAW) oder net.ipv4.ping_group_range freigeben.");
+        std::process::exit(2);
+    }
+
+    let ended_at = chrono::Local::now();
+    if text {
+        println!("\n📋 Abschlussbericht: {} Runden in {} ({} bis {})",
+                 state.cycles, format_duration(ended_at - started_at),
+                 started_at.format("%Y-%m-%d %H:%M:%S"), ended_at.format("%H:%M:%S"));
+        println!("{}", header);
+        print_table(&state);
+    } else {
+        let report = ContinuousReport {
+            target: target.to_string(),
+            ip: address.ip(),
+            mode: options.mode.name(),
+            port,
+            started_at: started_at.to_rfc3339(),
+            ended_at: ended_at.to_rfc3339(),
+            cycles: state.cycles,
+            hops: state.hops.iter()
+                .map(|(ttl, hop)| HopReport {
+                    ttl: *ttl,
+                    addresses: hop.addresses.iter()
+                        .map(|(address, replies)| AddressRecord {
+                            address: *address,
+                            hostname: state.names.get(address).cloned().flatten(),
+                            replies: *replies,
+                        })
+                        .collect(),
+                    last_ms: hop.samples.last_rtt(),
+                    statistics: hop.samples.summary(),
+                    code: hop.code,
+                })
+                .collect(),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+}
+
+/// Eine Runde: je Hop eine Probe, alle gleichzeitig
+async fn run_cycle(
+    address: SocketAddr,
+    options: &TraceOptions,
+    state: &mut PathState,
+    lookups: &mut JoinSet<(IpAddr, Option<String>)>,
+) -> io::Result<()> {
+    let mut probes = JoinSet::new();
+    for ttl in 1..=state.last_ttl {
+        // Eindeutig innerhalb der letzten Runden, hält UDP-Ports im üblichen Bereich
+        let sequence = ((state.cycles % 64) * u64::from(options.max_hops) + u64::from(ttl - 1)) as u16;
+        let (mode, timeout) = (options.mode, options.timeout);
+        probes.spawn(async move { (ttl, send_probe(mode, address, ttl, sequence, timeout).await) });
+    }
+
+    while let Some(joined) = probes.join_next().await {
+        let Ok((ttl, result)) = joined else { continue };
+        let hop = state.hops.entry(ttl).or_default();
+        let Some(reply) = result? else {
+            hop.samples.add_loss();
+            continue;
+        };
+        hop.samples.add_reply(reply.rtt);
+        hop.record_address(reply.from);
+        hop.code = match reply.kind {
+            ReplyKind::Unreachable(code) => Some(code),
+            _ => None,
+        };
+        if reply.kind != ReplyKind::TimeExceeded {
+            state.last_ttl = state.last_ttl.min(ttl);
+        }
+        if let Entry::Vacant(entry) = state.names.entry(reply.from) {
+            entry.insert(None);
+            if options.resolve {
+                let from = reply.from;
+                lookups.spawn(async move { (from, reverse_lookup(from).await) });
+            }
+        }
+    }
+
+    // Hops hinter dem Ziel sind nur Wiederholungen des Ziels
+    let last_ttl = state.last_ttl;
+    state.hops.retain(|ttl, _| *ttl <= last_ttl);
+    state.cycles += 1;
+    Ok(())
+}
+
+/// Tabelle je Hop: Verlust, gesendet, letzte/mittlere/beste/schlechteste
+/// Laufzeit und Standardabweichung; weitere Adressen eines Hops darunter
+fn print_table(state: &PathState) {
+    let host = |address: &IpAddr| match state.names.get(address).cloned().flatten() {
+        Some(name) => format!("{} ({})", name, address),
+        None => address.to_string(),
+    };
+    let width = state.hops.values()
+        .flat_map(|hop| hop.addresses.iter().map(|(address, _)| host(address).chars().count() + 3))
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    println!("{:>3}  {:<width$}  {:>7}  {:>5}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}",
+             "Hop", "Host", "Verlust", "Ges.", "Letzte", "avg", "Beste", "Schlecht", "StdAbw", width = width);
+    for (ttl, hop) in &state.hops {
+        let summary = hop.samples.summary();
+        let latency = |value: Option<f64>| value.map(|ms| format!("{:.2}", ms)).unwrap_or_else(|| "-".to_string());
+        let rtt = summary.rtt.as_ref();
+        let mut name = hop.addresses.first().map(|(address, _)| host(address)).unwrap_or_else(|| "*".to_string());
+        if let Some(code) = hop.code {
+            name = format!("{} {}", name, code);
+        }
+        println!("{:>3}  {:<width$}  {:>6.1}%  {:>5}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}",
+                 ttl, name, summary.loss_percent, summary.sent, latency(hop.samples.last_rtt()),
+                 latency(rtt.map(|rtt| rtt.avg_ms)), latency(rtt.map(|rtt| rtt.min_ms)),
+                 latency(rtt.map(|rtt| rtt.max_ms)), latency(rtt.map(|rtt| rtt.stddev_ms)), width = width);
+        // Lastverteilung: weitere Router derselben Entfernung
+        for (address, _) in hop.addresses.iter().skip(1) {
+            println!("     {}", host(address));
+        }
+    }
+    println!("     Laufzeiten in ms");
+}
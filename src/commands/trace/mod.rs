@@ -0,0 +1,373 @@
+mod continuous;
+mod probe;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::commands::ping::{resolve_target, OutputFormat};
+use crate::commands::scan::reverse_lookup;
+use crate::stats::{Samples, Summary};
+use continuous::trace_continuous;
+use probe::{send_probe, ProbeReply, ReplyKind};
+
+/// Basis-Zielport der UDP-Probes (wie beim klassischen traceroute)
+const UDP_BASE_PORT: u16 = 33434;
+/// Zielport der TCP-SYN-Probes
+const TCP_DEFAULT_PORT: u16 = 80;
+/// So viele Hops werden gleichzeitig abgefragt
+const PARALLEL_HOPS: usize = 16;
+
+/// Art der Probes von `trace`
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum TraceMode {
+    /// UDP-Datagramme an hohe Ports (klassisches traceroute)
+    Udp,
+    /// ICMP Echo Requests
+    Icmp,
+    /// TCP-SYN an einen Port, kommt oft durch Firewalls
+    Tcp,
+}
+
+impl TraceMode {
+    fn name(&self) -> &'static str {
+        match self {
+            TraceMode::Udp => "udp",
+            TraceMode::Icmp => "icmp",
+            TraceMode::Tcp => "tcp",
+        }
+    }
+}
+
+/// Einstellungen für `trace`
+pub struct TraceOptions {
+    pub mode: TraceMode,
+    /// Zielport (UDP: erster Port, TCP: fester Port)
+    pub port: Option<u16>,
+    /// Höchste TTL
+    pub max_hops: u8,
+    /// Probes je Hop
+    pub queries: u32,
+    /// Wartezeit auf eine Antwort
+    pub timeout: Duration,
+    /// Hostnamen der Hops per PTR-Eintrag auflösen
+    pub resolve: bool,
+    pub format: OutputFormat,
+    /// Fortlaufend messen und eine Tabelle je Hop zeigen (wie mtr)
+    pub continuous: bool,
+    /// Anzahl der Runden bei `continuous`; 0 misst bis Strg+C
+    pub count: u32,
+    /// Abstand zwischen zwei Runden bei `continuous`
+    pub interval: Duration,
+}
+
+/// Ergebnis eines Hops
+struct Hop {
+    ttl: u8,
+    /// Eine Antwort je Probe; `None` bei Timeout
+    probes: Vec<Option<ProbeReply>>,
+    /// Hostnamen der antwortenden Adressen
+    names: BTreeMap<IpAddr, Option<String>>,
+}
+
+impl Hop {
+    /// Das Ziel oder eine Unreachable-Meldung beendet den Pfad
+    fn is_final(&self) -> bool {
+        self.probes.iter().flatten().any(|reply| reply.kind != ReplyKind::TimeExceeded)
+    }
+
+    fn samples(&self) -> Samples {
+        let mut samples = Samples::default();
+        for probe in &self.probes {
+            match probe {
+                Some(reply) => samples.add_reply(reply.rtt),
+                None => samples.add_loss(),
+            }
+        }
+        samples
+    }
+}
+
+/// Ergebnis für `--format json`
+#[derive(Serialize)]
+struct TraceReport {
+    target: String,
+    ip: IpAddr,
+    mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+    max_hops: u8,
+    queries: u32,
+    started_at: String,
+    reached: bool,
+    hops: Vec<HopRecord>,
+}
+
+#[derive(Serialize)]
+struct HopRecord {
+    ttl: u8,
+    probes: Vec<ProbeRecord>,
+    statistics: Summary,
+}
+
+/// Eine Probe; ohne Antwort sind alle Felder leer
+#[derive(Serialize)]
+struct ProbeRecord {
+    address: Option<IpAddr>,
+    hostname: Option<String>,
+    rtt_ms: Option<f64>,
+    /// "time_exceeded", "reached" oder "unreachable"
+    result: Option<&'static str>,
+    /// traceroute-Kürzel bei Unreachable, z.B. "!H"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+pub async fn handle_trace_command(target: &str, options: TraceOptions) {
+    let ip = match resolve_target(target).await {
+        Ok(ip) => ip,
+        Err(e) => {
+            println!("❌ Ziel '{}' konnte nicht aufgelöst werden: {}", target, e);
+            std::process::exit(2);
+        }
+    };
+    let port = match options.mode {
+        TraceMode::Udp => Some(options.port.unwrap_or(UDP_BASE_PORT)),
+        TraceMode::Tcp => Some(options.port.unwrap_or(TCP_DEFAULT_PORT)),
+        TraceMode::Icmp => None,
+    };
+    let address = SocketAddr::new(ip, port.unwrap_or(0));
+
+    let name = if target == ip.to_string() { ip.to_string() } else { format!("{} ({})", target, ip) };
+    let mode = match (options.mode, port) {
+        (TraceMode::Udp, Some(port)) => format!("UDP ab Port {}", port),
+        (TraceMode::Tcp, Some(port)) => format!("TCP-SYN an Port {}", port),
+        _ => "ICMP Echo".to_string(),
+    };
+    if options.continuous {
+        let header = format!("🧭 Traceroute zu {}, max. {} Hops, eine Probe je Hop alle {} ms ({})",
+                             name, options.max_hops, options.interval.as_millis(), mode);
+        trace_continuous(target, address, port, &options, &header).await;
+        return;
+    }
+
+    let text = options.format == OutputFormat::Text;
+    if text {
+        println!("🧭 Traceroute zu {}, max. {} Hops, {} Probes je Hop ({})",
+                 name, options.max_hops, options.queries, mode);
+    }
+
+    let started_at = chrono::Local::now();
+    let hops = match trace_path(address, &options, text).await {
+        Ok(hops) => hops,
+        Err(e) => {
+            println!("❌ Probes konnten nicht gesendet werden: {}", e);
+            println!("💡 Tipp: Für ICMP Root-Rechte (CAP_NET_RAW) oder net.ipv4.ping_group_range freigeben.");
+            std::process::exit(2);
+        }
+    };
+    let reached = hops.last().is_some_and(|hop| {
+        hop.probes.iter().flatten().any(|reply| reply.kind == ReplyKind::Reached)
+    });
+
+    if text {
+        if reached {
+            println!("\n🎯 Ziel nach {} Hops erreicht", hops.len());
+        } else {
+            println!("\n❌ Ziel nicht erreicht (max. {} Hops)", options.max_hops);
+        }
+    } else {
+        let report = TraceReport {
+            target: target.to_string(),
+            ip,
+            mode: options.mode.name(),
+            port,
+            max_hops: options.max_hops,
+            queries: options.queries,
+            started_at: started_at.to_rfc3339(),
+            reached,
+            hops: hops.iter().map(hop_record).collect(),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("❌ Ergebnis konnte nicht serialisiert werden: {}", e),
+        }
+    }
+
+    if !reached {
+        std::process::exit(1);
+    }
+}
+
+/// Fragt die Hops parallel ab und gibt sie bei Textausgabe der Reihe nach
+/// aus, sobald alle vorherigen feststehen. Liefert die Hops bis zum Ziel.
+async fn trace_path(target: SocketAddr, options: &TraceOptions, text: bool) -> std::io::Result<Vec<Hop>> {
+    // Kleinste TTL, bei der der Pfad endet; höhere Hops brechen früh ab
+    let final_ttl = Arc::new(AtomicU8::new(u8::MAX));
+    let semaphore = Arc::new(Semaphore::new(PARALLEL_HOPS));
+    let mut tasks = JoinSet::new();
+
+    for ttl in 1..=options.max_hops {
+        let final_ttl = final_ttl.clone();
+        let semaphore = semaphore.clone();
+        let (mode, queries, timeout, resolve) = (options.mode, options.queries, options.timeout, options.resolve);
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let mut probes = Vec::new();
+            for query in 0..queries {
+                if ttl > final_ttl.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Eindeutige Sequenz je Probe (auch Port-Offset bei UDP)
+                let sequence = (u32::from(ttl - 1) * queries + query) as u16;
+                let reply = send_probe(mode, target, ttl, sequence, timeout).await?;
+                if reply.as_ref().is_some_and(|reply| reply.kind != ReplyKind::TimeExceeded) {
+                    final_ttl.fetch_min(ttl, Ordering::Relaxed);
+                }
+                probes.push(reply);
+            }
+
+            let mut names = BTreeMap::new();
+            for reply in probes.iter().flatten() {
+                names.entry(reply.from).or_insert(None);
+            }
+            if resolve {
+                for (ip, name) in names.iter_mut() {
+                    *name = reverse_lookup(*ip).await;
+                }
+            }
+            Ok::<Hop, std::io::Error>(Hop { ttl, probes, names })
+        });
+    }
+
+    let mut finished: BTreeMap<u8, Hop> = BTreeMap::new();
+    let mut next_ttl = 1;
+    let mut path_end = false;
+    while let Some(joined) = tasks.join_next().await {
+        let Ok(result) = joined else { continue };
+        let hop = result?;
+        finished.insert(hop.ttl, hop);
+
+        // Fertige Hops in Reihenfolge ausgeben
+        while !path_end {
+            let Some(hop) = finished.get(&next_ttl) else { break };
+            if text {
+                println!("{}", format_hop(hop));
+            }
+            path_end = hop.is_final();
+            next_ttl += 1;
+        }
+    }
+
+    let last = finished.values()
+        .find(|hop| hop.is_final())
+        .map_or(options.max_hops, |hop| hop.ttl);
+    Ok(finished.into_values().filter(|hop| hop.ttl <= last).collect())
+}
+
+/// Zeile eines Hops: Adressen mit Hostnamen, Laufzeit je Probe, Verlust
+fn format_hop(hop: &Hop) -> String {
+    let mut line = format!("{:>2} ", hop.ttl);
+    let mut current: Option<IpAddr> = None;
+    for probe in &hop.probes {
+        match probe {
+            Some(reply) => {
+                // Adresse nur bei Wechsel erneut nennen, wie traceroute
+                if current != Some(reply.from) {
+                    current = Some(reply.from);
+                    match hop.names.get(&reply.from).cloned().flatten() {
+                        Some(name) => line.push_str(&format!(" {} ({})", name, reply.from)),
+                        None => line.push_str(&format!(" {}", reply.from)),
+                    }
+                }
+                line.push_str(&format!("  {:.2} ms", reply.rtt.as_micros() as f64 / 1000.0));
+                if let ReplyKind::Unreachable(code) = reply.kind {
+                    line.push_str(&format!(" {}", code));
+                }
+            }
+            None => line.push_str("  *"),
+        }
+    }
+
+    let summary = hop.samples().summary();
+    if summary.received > 0 && summary.received < summary.sent {
+        line.push_str(&format!("   📉 {:.0} % Verlust", summary.loss_percent));
+    }
+    line
+}
+
+fn hop_record(hop: &Hop) -> HopRecord {
+    let probes = hop.probes.iter()
+        .map(|probe| match probe {
+            Some(reply) => ProbeRecord {
+                address: Some(reply.from),
+                hostname: hop.names.get(&reply.from).cloned().flatten(),
+                rtt_ms: Some(reply.rtt.as_micros() as f64 / 1000.0),
+                result: Some(match reply.kind {
+                    ReplyKind::TimeExceeded => "time_exceeded",
+                    ReplyKind::Reached => "reached",
+                    ReplyKind::Unreachable(_) => "unreachable",
+                }),
+                code: match reply.kind {
+                    ReplyKind::Unreachable(code) => Some(code),
+                    _ => None,
+                },
+            },
+            None => ProbeRecord { address: None, hostname: None, rtt_ms: None, result: None, code: None },
+        })
+        .collect();
+    HopRecord { ttl: hop.ttl, probes, statistics: hop.samples().summary() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(from: &str, micros: u64, kind: ReplyKind) -> Option<ProbeReply> {
+        Some(ProbeReply { from: from.parse().unwrap(), rtt: Duration::from_micros(micros), kind })
+    }
+
+    #[test]
+    fn formats_hop_like_traceroute() {
+        let hop = Hop {
+            ttl: 1,
+            probes: vec![
+                reply("192.0.2.1", 1500, ReplyKind::TimeExceeded),
+                reply("192.0.2.1", 2250, ReplyKind::TimeExceeded),
+                None,
+            ],
+            names: BTreeMap::from([("192.0.2.1".parse().unwrap(), Some("router.local".to_string()))]),
+        };
+        assert_eq!(format_hop(&hop), " 1  router.local (192.0.2.1)  1.50 ms  2.25 ms  *   📉 33 % Verlust");
+    }
+
+    #[test]
+    fn repeats_address_on_change_and_shows_codes() {
+        let hop = Hop {
+            ttl: 12,
+            probes: vec![
+                reply("192.0.2.1", 10_000, ReplyKind::Unreachable("!H")),
+                reply("192.0.2.9", 11_000, ReplyKind::TimeExceeded),
+                reply("192.0.2.1", 12_500, ReplyKind::Unreachable("!H")),
+            ],
+            names: BTreeMap::from([("192.0.2.1".parse().unwrap(), None)]),
+        };
+        assert_eq!(
+            format_hop(&hop),
+            "12  192.0.2.1  10.00 ms !H 192.0.2.9  11.00 ms 192.0.2.1  12.50 ms !H"
+        );
+        assert!(hop.is_final());
+    }
+
+    #[test]
+    fn formats_silent_hop_without_loss_note() {
+        let hop = Hop { ttl: 3, probes: vec![None, None, None], names: BTreeMap::new() };
+        assert_eq!(format_hop(&hop), " 3   *  *  *");
+        assert!(!hop.is_final());
+    }
+}
@@ -0,0 +1,491 @@
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::io;
+use libc::c_int;
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+
+use super::TraceMode;
+use crate::icmp::{checksum, set_option};
+
+/// Antwort auf eine einzelne Probe
+#[derive(Clone)]
+pub struct ProbeReply {
+    /// Router bzw. Ziel, das geantwortet hat
+    pub from: IpAddr,
+    pub rtt: Duration,
+    pub kind: ReplyKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplyKind {
+    /// TTL abgelaufen: ein Router auf dem Weg
+    TimeExceeded,
+    /// Das Ziel selbst hat geantwortet
+    Reached,
+    /// Destination Unreachable mit traceroute-Kürzel, z.B. "!H"
+    Unreachable(&'static str),
+}
+
+/// Eine ausgewertete Meldung aus der Fehler-Warteschlange
+struct QueuedError {
+    offender: Option<IpAddr>,
+    origin: u8,
+    kind: u8,
+    code: u8,
+}
+
+/// Sendet eine Probe mit der angegebenen TTL. Jede Probe bekommt einen
+/// eigenen Socket, damit ICMP-Fehler eindeutig zugeordnet werden können;
+/// der Kernel stellt sie dank `IP_RECVERR` samt Absender in die
+/// Fehler-Warteschlange (`MSG_ERRQUEUE`).
+pub async fn send_probe(
+    mode: TraceMode,
+    target: SocketAddr,
+    ttl: u8,
+    sequence: u16,
+    timeout: Duration,
+) -> io::Result<Option<ProbeReply>> {
+    let socket = open_socket(mode, target.ip(), ttl)?;
+    let started = Instant::now();
+    match mode {
+        TraceMode::Udp => {
+            // Wie beim klassischen traceroute: Zielport je Probe hochzählen
+            let port = target.port().wrapping_add(sequence);
+            socket.connect(&SockAddr::from(SocketAddr::new(target.ip(), port)))?;
+            socket.send(b"SHC-Tool-Trace")?;
+        }
+        TraceMode::Icmp => {
+            // Nicht verbinden: ein verbundener Raw-Socket verwirft die Time
+            // Exceeded-Meldungen der Router, weil sie nicht vom Ziel stammen
+            socket.send_to(&echo_request(target.ip(), sequence), &SockAddr::from(SocketAddr::new(target.ip(), 0)))?;
+        }
+        TraceMode::Tcp => match socket.connect(&SockAddr::from(target)) {
+            Ok(()) => {}
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(e) => return Err(e),
+        },
+    }
+
+    let raw = socket.r#type()? == Type::RAW;
+    let socket = AsyncFd::with_interest(socket, Interest::READABLE | Interest::WRITABLE | Interest::ERROR)?;
+    let wait = async {
+        loop {
+            let interest = match mode {
+                TraceMode::Tcp => Interest::WRITABLE | Interest::ERROR,
+                _ => Interest::READABLE | Interest::ERROR,
+            };
+            let mut guard = socket.ready(interest).await?;
+            if let Some(reply) = receive(&socket, mode, target, sequence, raw, started)? {
+                return Ok(reply);
+            }
+            guard.clear_ready();
+        }
+    };
+    match tokio::time::timeout(timeout, wait).await {
+        Ok(result) => result.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+fn open_socket(mode: TraceMode, target: IpAddr, ttl: u8) -> io::Result<Socket> {
+    let domain = if target.is_ipv4() { Domain::IPV4 } else { Domain::IPV6 };
+    let socket = match mode {
+        TraceMode::Udp => Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?,
+        TraceMode::Tcp => Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?,
+        TraceMode::Icmp => {
+            let protocol = if target.is_ipv4() { Protocol::ICMPV4 } else { Protocol::ICMPV6 };
+            // Wie bei `ping`: unprivilegierter Datagram-Socket, sonst Raw-Socket
+            Socket::new(domain, Type::DGRAM, Some(protocol))
+                .or_else(|_| Socket::new(domain, Type::RAW, Some(protocol)))?
+        }
+    };
+    socket.set_nonblocking(true)?;
+
+    // Raw-Sockets bekämen auch die Fehler fremder Probes in die Warteschlange;
+    // sie werten die ICMP-Pakete stattdessen selbst aus (`parse_icmp`)
+    let recv_error = socket.r#type()? != Type::RAW;
+    let fd = socket.as_raw_fd();
+    if target.is_ipv4() {
+        socket.set_ttl(u32::from(ttl))?;
+        set_option(fd, libc::IPPROTO_IP, libc::IP_RECVERR, c_int::from(recv_error))?;
+    } else {
+        socket.set_unicast_hops_v6(u32::from(ttl))?;
+        set_option(fd, libc::IPPROTO_IPV6, libc::IPV6_RECVERR, c_int::from(recv_error))?;
+    }
+    Ok(socket)
+}
+
+/// Wertet Fehler-Warteschlange und eingegangene Daten aus; `None`, solange
+/// noch nichts Passendes da ist
+fn receive(
+    socket: &AsyncFd<Socket>,
+    mode: TraceMode,
+    target: SocketAddr,
+    sequence: u16,
+    raw: bool,
+    started: Instant,
+) -> io::Result<Option<ProbeReply>> {
+    let fd = socket.as_raw_fd();
+    let rtt = started.elapsed();
+    if let Some(error) = read_error_queue(fd)? {
+        if let Some(reply) = classify(&error, target.ip(), rtt) {
+            return Ok(Some(reply));
+        }
+    }
+
+    let socket = socket.get_ref();
+    match mode {
+        TraceMode::Tcp => match socket.take_error()? {
+            // SYN/ACK: Port offen, Ziel erreicht
+            None if socket.peer_addr().is_ok() => Ok(Some(ProbeReply { from: target.ip(), rtt, kind: ReplyKind::Reached })),
+            None => Ok(None),
+            // RST: Port geschlossen, aber das Ziel hat geantwortet
+            Some(e) if e.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                Ok(Some(ProbeReply { from: target.ip(), rtt, kind: ReplyKind::Reached }))
+            }
+            Some(e) => Err(e),
+        },
+        TraceMode::Udp => {
+            let mut buffer = [MaybeUninit::<u8>::uninit(); 512];
+            match socket.recv(&mut buffer) {
+                // Ein UDP-Dienst auf dem Zielport hat geantwortet
+                Ok(_) => Ok(Some(ProbeReply { from: target.ip(), rtt, kind: ReplyKind::Reached })),
+                Err(e) if is_transient(&e) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+        TraceMode::Icmp => {
+            let mut buffer = [MaybeUninit::<u8>::uninit(); 1024];
+            loop {
+                let (length, from) = match socket.recv_from(&mut buffer) {
+                    Ok(received) => received,
+                    Err(e) if is_transient(&e) => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                // SAFETY: recv_from hat die ersten `length` Bytes beschrieben
+                let packet: Vec<u8> = buffer[..length].iter().map(|byte| unsafe { byte.assume_init() }).collect();
+                let from = from.as_socket().map(|addr| addr.ip()).unwrap_or(target.ip());
+                if let Some(kind) = parse_icmp(&packet, from, target.ip(), sequence, raw) {
+                    return Ok(Some(ProbeReply { from, rtt, kind }));
+                }
+            }
+        }
+    }
+}
+
+/// Keine Daten oder ein bereits über die Fehler-Warteschlange gemeldeter Fehler
+fn is_transient(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::WouldBlock
+        || matches!(error.raw_os_error(), Some(libc::ECONNREFUSED | libc::EHOSTUNREACH | libc::ENETUNREACH))
+}
+
+fn classify(error: &QueuedError, target: IpAddr, rtt: Duration) -> Option<ProbeReply> {
+    let from = error.offender?;
+    let kind = match (error.origin, error.kind, error.code) {
+        (libc::SO_EE_ORIGIN_ICMP, 11, _) | (libc::SO_EE_ORIGIN_ICMP6, 3, _) => ReplyKind::TimeExceeded,
+        // Port Unreachable vom Ziel: UDP-Probe angekommen
+        (libc::SO_EE_ORIGIN_ICMP, 3, 3) | (libc::SO_EE_ORIGIN_ICMP6, 1, 4) => ReplyKind::Reached,
+        (libc::SO_EE_ORIGIN_ICMP, 3, code) => ReplyKind::Unreachable(match code {
+            0 => "!N",
+            1 => "!H",
+            2 => "!P",
+            4 => "!F",
+            9 | 10 | 13 => "!X",
+            _ => "!U",
+        }),
+        (libc::SO_EE_ORIGIN_ICMP6, 1, code) => ReplyKind::Unreachable(match code {
+            0 => "!N",
+            1 => "!X",
+            3 => "!H",
+            _ => "!U",
+        }),
+        (libc::SO_EE_ORIGIN_ICMP6, 2, _) => ReplyKind::Unreachable("!F"),
+        _ => return None,
+    };
+    // Manche Ziele melden sich mit Unreachable statt einer Antwort
+    let kind = match kind {
+        ReplyKind::Unreachable("!P") if from == target => ReplyKind::Reached,
+        other => other,
+    };
+    Some(ProbeReply { from, rtt, kind })
+}
+
+/// Liest eine Meldung aus der Fehler-Warteschlange des Sockets
+fn read_error_queue(fd: RawFd) -> io::Result<Option<QueuedError>> {
+    let mut data = [0u8; 512];
+    let mut control = [0u8; 512];
+    let mut iov = libc::iovec { iov_base: data.as_mut_ptr().cast(), iov_len: data.len() };
+    // SAFETY: msghdr ist ein reines C-Struct, Nullen sind gültige Startwerte
+    let mut message: libc::msghdr = unsafe { std::mem::zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = control.len() as _;
+
+    // SAFETY: Alle Puffer leben bis zum Ende der Funktion
+    let length = unsafe { libc::recvmsg(fd, &mut message, libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT) };
+    if length < 0 {
+        let error = io::Error::last_os_error();
+        return if error.kind() == io::ErrorKind::WouldBlock { Ok(None) } else { Err(error) };
+    }
+
+    // SAFETY: Die CMSG-Makros bewegen sich nur innerhalb von `control`
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+        while !header.is_null() {
+            let (level, kind) = ((*header).cmsg_level, (*header).cmsg_type);
+            if (level == libc::IPPROTO_IP && kind == libc::IP_RECVERR)
+                || (level == libc::IPPROTO_IPV6 && kind == libc::IPV6_RECVERR)
+            {
+                let extended = libc::CMSG_DATA(header) as *const libc::sock_extended_err;
+                let error: libc::sock_extended_err = std::ptr::read_unaligned(extended);
+                let offender = sockaddr_ip(libc::SO_EE_OFFENDER(extended));
+                return Ok(Some(QueuedError {
+                    offender,
+                    origin: error.ee_origin,
+                    kind: error.ee_type,
+                    code: error.ee_code,
+                }));
+            }
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+    Ok(None)
+}
+
+/// Adresse aus einer `sockaddr`, wie sie hinter `sock_extended_err` folgt
+///
+/// # Safety
+/// `address` muss auf eine gültige `sockaddr_in` bzw. `sockaddr_in6` zeigen
+unsafe fn sockaddr_ip(address: *const libc::sockaddr) -> Option<IpAddr> {
+    match i32::from(std::ptr::read_unaligned(address).sa_family) {
+        libc::AF_INET => {
+            let address: libc::sockaddr_in = std::ptr::read_unaligned(address.cast());
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr))))
+        }
+        libc::AF_INET6 => {
+            let address: libc::sockaddr_in6 = std::ptr::read_unaligned(address.cast());
+            Some(IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
+
+/// Identifier der Echo Requests; Datagram-Sockets ersetzen ihn ohnehin
+fn identifier() -> u16 {
+    std::process::id() as u16
+}
+
+fn echo_request(target: IpAddr, sequence: u16) -> Vec<u8> {
+    let kind = if target.is_ipv4() { 8 } else { 128 };
+    let mut packet = vec![kind, 0, 0, 0];
+    packet.extend_from_slice(&identifier().to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(b"SHC-Tool-Trace");
+    // Bei ICMPv6 berechnet der Kernel die Prüfsumme
+    if target.is_ipv4() {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+/// Wertet ein empfangenes ICMP-Paket aus. Raw-Sockets sehen jedes ICMP-Paket
+/// (bei IPv4 mit IP-Header), auch Time Exceeded für fremde Probes; deshalb
+/// zählen nur Pakete mit unserer Sequenznummer.
+fn parse_icmp(packet: &[u8], from: IpAddr, target: IpAddr, sequence: u16, raw: bool) -> Option<ReplyKind> {
+    let icmp = if target.is_ipv4() && raw {
+        packet.get(usize::from(packet.first()? & 0x0f) * 4..)?
+    } else {
+        packet
+    };
+    let (kind, code) = (*icmp.first()?, *icmp.get(1)?);
+    let echo_reply = if target.is_ipv4() { 0 } else { 129 };
+
+    if kind == echo_reply {
+        let matches = icmp.get(6..8)? == sequence.to_be_bytes()
+            && (!raw || icmp.get(4..6)? == identifier().to_be_bytes());
+        return matches.then_some(ReplyKind::Reached);
+    }
+    if !raw {
+        return None;
+    }
+
+    // Fehlermeldung: enthält den Anfang unseres ursprünglichen Pakets
+    let inner = icmp.get(8..)?;
+    let inner_icmp = if target.is_ipv4() {
+        inner.get(usize::from(inner.first()? & 0x0f) * 4..)?
+    } else {
+        inner.get(40..)?
+    };
+    // Nur eigene Echo Requests zählen, nicht zitierte UDP- oder TCP-Pakete
+    let echo_request = if target.is_ipv4() { 8 } else { 128 };
+    if *inner_icmp.first()? != echo_request
+        || inner_icmp.get(4..6)? != identifier().to_be_bytes()
+        || inner_icmp.get(6..8)? != sequence.to_be_bytes()
+    {
+        return None;
+    }
+    let error = QueuedError {
+        offender: Some(from),
+        origin: if target.is_ipv4() { libc::SO_EE_ORIGIN_ICMP } else { libc::SO_EE_ORIGIN_ICMP6 },
+        kind,
+        code,
+    };
+    classify(&error, target, Duration::ZERO).map(|reply| reply.kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTER_V4: [u8; 4] = [192, 0, 2, 1];
+    const TARGET_V4: [u8; 4] = [198, 51, 100, 7];
+    const ROUTER_V6: &str = "2001:db8::1";
+    const TARGET_V6: &str = "2001:db8:1::7";
+
+    fn v4(address: [u8; 4]) -> IpAddr {
+        IpAddr::from(address)
+    }
+
+    fn v6(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    /// IPv4-Header mit `options` zusätzlichen 32-Bit-Wörtern
+    fn ipv4_header(protocol: u8, source: [u8; 4], destination: [u8; 4], options: u8) -> Vec<u8> {
+        let mut header = vec![0x45 + options, 0, 0, 0, 0, 0, 0, 0, 1, protocol, 0, 0];
+        header.extend_from_slice(&source);
+        header.extend_from_slice(&destination);
+        header.extend(std::iter::repeat_n(1, usize::from(options) * 4));
+        header
+    }
+
+    fn ipv6_header(next_header: u8, source: &str, destination: &str) -> Vec<u8> {
+        let mut header = vec![0x60, 0, 0, 0, 0, 0, next_header, 1];
+        header.extend_from_slice(&source.parse::<Ipv6Addr>().unwrap().octets());
+        header.extend_from_slice(&destination.parse::<Ipv6Addr>().unwrap().octets());
+        header
+    }
+
+    /// ICMPv4-Fehlermeldung, wie ein Raw-Socket sie samt IP-Header empfängt;
+    /// zitiert werden der IP-Header und die ersten 8 Bytes der Probe
+    fn error_v4(kind: u8, code: u8, from: [u8; 4], quoted: &[u8]) -> Vec<u8> {
+        let mut packet = ipv4_header(1, from, [10, 0, 0, 2], 0);
+        packet.extend_from_slice(&[kind, code, 0, 0, 0, 0, 0, 0]);
+        packet.extend(ipv4_header(1, [10, 0, 0, 2], TARGET_V4, 0));
+        packet.extend_from_slice(&quoted[..8]);
+        packet
+    }
+
+    /// ICMPv6-Fehlermeldung; Raw-Sockets liefern hier keinen IP-Header mit
+    fn error_v6(kind: u8, code: u8, quoted: &[u8]) -> Vec<u8> {
+        let mut packet = vec![kind, code, 0, 0, 0, 0, 0, 0];
+        packet.extend(ipv6_header(58, "2001:db8::2", TARGET_V6));
+        packet.extend_from_slice(&quoted[..8]);
+        packet
+    }
+
+    #[test]
+    fn matches_time_exceeded_and_unreachable_v4() {
+        let target = v4(TARGET_V4);
+        let probe = echo_request(target, 7);
+        let parse = |packet: &[u8], from: [u8; 4]| parse_icmp(packet, v4(from), target, 7, true);
+
+        assert_eq!(parse(&error_v4(11, 0, ROUTER_V4, &probe), ROUTER_V4), Some(ReplyKind::TimeExceeded));
+        assert_eq!(parse(&error_v4(3, 3, TARGET_V4, &probe), TARGET_V4), Some(ReplyKind::Reached));
+        assert_eq!(parse(&error_v4(3, 1, ROUTER_V4, &probe), ROUTER_V4), Some(ReplyKind::Unreachable("!H")));
+        assert_eq!(parse(&error_v4(3, 13, ROUTER_V4, &probe), ROUTER_V4), Some(ReplyKind::Unreachable("!X")));
+        // Protocol Unreachable vom Ziel selbst gilt als angekommen
+        assert_eq!(parse(&error_v4(3, 2, TARGET_V4, &probe), TARGET_V4), Some(ReplyKind::Reached));
+        assert_eq!(parse(&error_v4(3, 2, ROUTER_V4, &probe), ROUTER_V4), Some(ReplyKind::Unreachable("!P")));
+    }
+
+    #[test]
+    fn honours_ipv4_header_length() {
+        let target = v4(TARGET_V4);
+        let mut packet = ipv4_header(1, ROUTER_V4, [10, 0, 0, 2], 2);
+        packet.extend_from_slice(&[11, 0, 0, 0, 0, 0, 0, 0]);
+        packet.extend(ipv4_header(1, [10, 0, 0, 2], TARGET_V4, 1));
+        packet.extend_from_slice(&echo_request(target, 3)[..8]);
+        assert_eq!(parse_icmp(&packet, v4(ROUTER_V4), target, 3, true), Some(ReplyKind::TimeExceeded));
+    }
+
+    #[test]
+    fn ignores_foreign_and_truncated_packets() {
+        let target = v4(TARGET_V4);
+        let parse = |packet: &[u8]| parse_icmp(packet, v4(ROUTER_V4), target, 7, true);
+
+        // Andere Sequenznummer, zitierte UDP-Probe, abgeschnittenes Paket
+        assert_eq!(parse(&error_v4(11, 0, ROUTER_V4, &echo_request(target, 8))), None);
+        let udp = [0x82, 0x9a, 0x82, 0x9b, 0x00, 0x16, 0x00, 0x00];
+        assert_eq!(parse(&error_v4(11, 0, ROUTER_V4, &udp)), None);
+        let packet = error_v4(11, 0, ROUTER_V4, &echo_request(target, 7));
+        assert_eq!(parse(&packet[..packet.len() - 1]), None);
+        assert_eq!(parse(&[]), None);
+
+        // Datagram-Sockets liefern keine Fehlermeldungen über recv
+        assert_eq!(parse_icmp(&packet[20..], v4(ROUTER_V4), target, 7, false), None);
+    }
+
+    #[test]
+    fn matches_echo_replies() {
+        let target = v4(TARGET_V4);
+        let mut reply = echo_request(target, 9);
+        reply[0] = 0;
+        let mut raw = ipv4_header(1, TARGET_V4, [10, 0, 0, 2], 0);
+        raw.extend_from_slice(&reply);
+        assert_eq!(parse_icmp(&raw, target, target, 9, true), Some(ReplyKind::Reached));
+        assert_eq!(parse_icmp(&raw, target, target, 10, true), None);
+
+        // Datagram-Socket: ohne IP-Header, der Kernel setzt den Identifier
+        reply[4..6].copy_from_slice(&[0xbe, 0xef]);
+        assert_eq!(parse_icmp(&reply, target, target, 9, false), Some(ReplyKind::Reached));
+
+        let target = v6(TARGET_V6);
+        let mut reply = echo_request(target, 9);
+        reply[0] = 129;
+        assert_eq!(parse_icmp(&reply, target, target, 9, true), Some(ReplyKind::Reached));
+    }
+
+    #[test]
+    fn matches_time_exceeded_and_unreachable_v6() {
+        let target = v6(TARGET_V6);
+        let probe = echo_request(target, 5);
+        let parse = |packet: &[u8], from: &str| parse_icmp(packet, v6(from), target, 5, true);
+
+        assert_eq!(parse(&error_v6(3, 0, &probe), ROUTER_V6), Some(ReplyKind::TimeExceeded));
+        assert_eq!(parse(&error_v6(1, 4, &probe), TARGET_V6), Some(ReplyKind::Reached));
+        assert_eq!(parse(&error_v6(1, 1, &probe), ROUTER_V6), Some(ReplyKind::Unreachable("!X")));
+        assert_eq!(parse(&error_v6(1, 3, &probe), ROUTER_V6), Some(ReplyKind::Unreachable("!H")));
+        assert_eq!(parse(&error_v6(2, 0, &probe), ROUTER_V6), Some(ReplyKind::Unreachable("!F")));
+        assert_eq!(parse(&error_v6(3, 0, &echo_request(target, 6)), ROUTER_V6), None);
+    }
+
+    #[test]
+    fn classifies_queued_errors() {
+        let target = v4(TARGET_V4);
+        let classify_v4 = |offender: Option<[u8; 4]>, kind, code| {
+            let error = QueuedError { offender: offender.map(v4), origin: libc::SO_EE_ORIGIN_ICMP, kind, code };
+            classify(&error, target, Duration::from_millis(3)).map(|reply| (reply.from, reply.kind))
+        };
+        assert_eq!(classify_v4(Some(ROUTER_V4), 11, 0), Some((v4(ROUTER_V4), ReplyKind::TimeExceeded)));
+        assert_eq!(classify_v4(Some(TARGET_V4), 3, 3), Some((target, ReplyKind::Reached)));
+        assert_eq!(classify_v4(Some(ROUTER_V4), 3, 0), Some((v4(ROUTER_V4), ReplyKind::Unreachable("!N"))));
+        assert_eq!(classify_v4(Some(ROUTER_V4), 3, 4), Some((v4(ROUTER_V4), ReplyKind::Unreachable("!F"))));
+        assert_eq!(classify_v4(Some(ROUTER_V4), 3, 7), Some((v4(ROUTER_V4), ReplyKind::Unreachable("!U"))));
+        assert_eq!(classify_v4(Some(ROUTER_V4), 5, 0), None);
+        assert_eq!(classify_v4(None, 11, 0), None);
+
+        let target = v6(TARGET_V6);
+        let error = QueuedError { offender: Some(target), origin: libc::SO_EE_ORIGIN_ICMP6, kind: 1, code: 4 };
+        assert_eq!(classify(&error, target, Duration::ZERO).map(|reply| reply.kind), Some(ReplyKind::Reached));
+        // Lokale Fehler (z.B. EMSGSIZE) stammen nicht aus ICMP
+        let local = QueuedError { offender: Some(target), origin: 1, kind: 0, code: 0 };
+        assert!(classify(&local, target, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,24 @@
+// src/csv.rs
+
+/// Setzt Felder mit Trennzeichen, Anführungszeichen oder Umbrüchen in Anführungszeichen
+pub fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape_csv;
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(escape_csv("SSH"), "SSH");
+        assert_eq!(escape_csv(""), "");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("Zeile 1\nZeile 2"), "\"Zeile 1\nZeile 2\"");
+        assert_eq!(escape_csv("sagt \"Hallo\""), "\"sagt \"\"Hallo\"\"\"");
+    }
+}
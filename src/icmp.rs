@@ -0,0 +1,237 @@
+// src/icmp.rs
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::Interest;
+use tokio::net::UdpSocket;
+
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+const ICMPV4_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+// Damit parallele Raw-Sockets unterschiedliche Identifier verwenden
+static NEXT_IDENTIFIER: AtomicU16 = AtomicU16::new(0);
+
+/// Antwort auf einen Echo Request
+pub struct EchoReply {
+    pub rtt: Duration,
+    /// TTL bzw. Hop Limit der Antwort, falls der Kernel es mitliefert
+    pub ttl: Option<u8>,
+    /// Größe der ICMP-Nachricht in Bytes (Header + Nutzdaten)
+    pub size: usize,
+}
+
+/// ICMP-Socket für Echo Requests an genau ein Ziel.
+///
+/// Bevorzugt wird ein unprivilegierter Datagram-Socket (Linux: abhängig von
+/// `net.ipv4.ping_group_range`), sonst ein Raw-Socket, der Root-Rechte bzw.
+/// `CAP_NET_RAW` benötigt.
+pub struct IcmpSocket {
+    socket: UdpSocket,
+    target: IpAddr,
+    /// Raw-Sockets liefern bei IPv4 den IP-Header mit und sehen fremde Pakete
+    raw: bool,
+    identifier: u16,
+}
+
+impl IcmpSocket {
+    pub fn new(target: IpAddr) -> io::Result<Self> {
+        let (domain, protocol) = match target {
+            IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+            IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+        };
+
+        let (socket, raw) = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+            Ok(socket) => (socket, false),
+            Err(_) => (Socket::new(domain, Type::RAW, Some(protocol))?, true),
+        };
+        socket.set_nonblocking(true)?;
+        socket.connect(&SocketAddr::new(target, 0).into())?;
+
+        // TTL/Hop Limit der Antworten als Kontrollnachricht mitliefern lassen
+        let _ = match target {
+            IpAddr::V4(_) => set_option(socket.as_raw_fd(), libc::IPPROTO_IP, libc::IP_RECVTTL, 1),
+            IpAddr::V6(_) => set_option(socket.as_raw_fd(), libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT, 1),
+        };
+
+        let identifier = std::process::id() as u16 ^ NEXT_IDENTIFIER.fetch_add(1, Ordering::Relaxed);
+        let socket = UdpSocket::from_std(std::net::UdpSocket::from(socket))?;
+        Ok(Self { socket, target, raw, identifier })
+    }
+
+    /// Raw-Socket statt unprivilegiertem Datagram-Socket
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    /// Zieladresse, an die der Socket gebunden ist
+    pub fn target(&self) -> IpAddr {
+        self.target
+    }
+
+    /// Setzt die TTL (IPv4) bzw. das Hop Limit (IPv6) ausgehender Pakete
+    pub fn set_ttl(&self, ttl: u8) -> io::Result<()> {
+        let socket = SockRef::from(&self.socket);
+        match self.target {
+            IpAddr::V4(_) => socket.set_ttl(u32::from(ttl)),
+            IpAddr::V6(_) => socket.set_unicast_hops_v6(u32::from(ttl)),
+        }
+    }
+
+    /// Setzt das Don't-Fragment-Bit und ignoriert die zwischengespeicherte
+    /// Pfad-MTU, sodass jede Paketgröße bis zur Interface-MTU gesendet wird.
+    /// Meldet ein Router "Fragmentation Needed", liefert `echo` `EMSGSIZE`.
+    pub fn set_dont_fragment(&self) -> io::Result<()> {
+        let fd = self.socket.as_raw_fd();
+        match self.target {
+            IpAddr::V4(_) => set_option(fd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER, libc::IP_PMTUDISC_PROBE),
+            IpAddr::V6(_) => set_option(fd, libc::IPPROTO_IPV6, libc::IPV6_MTU_DISCOVER, libc::IPV6_PMTUDISC_PROBE),
+        }
+    }
+
+    /// Pfad-MTU, die der Kernel für das Ziel kennt (z.B. aus einer
+    /// "Fragmentation Needed"-Meldung)
+    pub fn path_mtu(&self) -> Option<u32> {
+        let (level, name) = match self.target {
+            IpAddr::V4(_) => (libc::IPPROTO_IP, libc::IP_MTU),
+            IpAddr::V6(_) => (libc::IPPROTO_IPV6, libc::IPV6_MTU),
+        };
+        let mut value: libc::c_int = 0;
+        let mut length = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+        // SAFETY: Zeiger und Länge beschreiben einen gültigen c_int
+        let result = unsafe {
+            libc::getsockopt(self.socket.as_raw_fd(), level, name, (&mut value as *mut libc::c_int).cast(), &mut length)
+        };
+        (result == 0 && value > 0).then_some(value as u32)
+    }
+
+    /// Sendet einen Echo Request und wartet höchstens `timeout` auf die Antwort
+    pub async fn echo(&self, sequence: u16, payload: &[u8], timeout: Duration) -> io::Result<Option<EchoReply>> {
+        let packet = self.echo_request(sequence, payload);
+        let sent = Instant::now();
+        self.socket.send(&packet).await?;
+
+        let deadline = sent + timeout;
+        let mut buffer = [0u8; 2048];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let receive = self.socket.async_io(Interest::READABLE, || {
+                receive_with_ttl(self.socket.as_raw_fd(), &mut buffer)
+            });
+            let (length, ttl) = match tokio::time::timeout(remaining, receive).await {
+                Ok(result) => result?,
+                Err(_) => return Ok(None),
+            };
+
+            if let Some((reply_sequence, header_ttl, size)) = self.parse_reply(&buffer[..length]) {
+                if reply_sequence == sequence {
+                    return Ok(Some(EchoReply { rtt: sent.elapsed(), ttl: ttl.or(header_ttl), size }));
+                }
+            }
+        }
+    }
+
+    fn echo_request(&self, sequence: u16, payload: &[u8]) -> Vec<u8> {
+        let kind = match self.target {
+            IpAddr::V4(_) => ICMPV4_ECHO_REQUEST,
+            IpAddr::V6(_) => ICMPV6_ECHO_REQUEST,
+        };
+
+        let mut packet = vec![kind, 0, 0, 0];
+        packet.extend_from_slice(&self.identifier.to_be_bytes());
+        packet.extend_from_slice(&sequence.to_be_bytes());
+        packet.extend_from_slice(payload);
+
+        // Bei ICMPv6 berechnet der Kernel die Prüfsumme (Pseudo-Header)
+        if self.target.is_ipv4() {
+            let checksum = checksum(&packet);
+            packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+        }
+        packet
+    }
+
+    /// Liefert Sequenznummer, TTL aus dem IP-Header (nur Raw-Sockets bei IPv4)
+    /// und Größe, falls das Paket eine passende Echo-Antwort ist
+    fn parse_reply(&self, packet: &[u8]) -> Option<(u16, Option<u8>, usize)> {
+        let (icmp, expected_type, ttl) = match self.target {
+            IpAddr::V4(_) if self.raw => {
+                let header_length = usize::from(packet.first()? & 0x0f) * 4;
+                (packet.get(header_length..)?, ICMPV4_ECHO_REPLY, packet.get(8).copied())
+            }
+            IpAddr::V4(_) => (packet, ICMPV4_ECHO_REPLY, None),
+            IpAddr::V6(_) => (packet, ICMPV6_ECHO_REPLY, None),
+        };
+
+        if *icmp.first()? != expected_type || icmp.len() < 8 {
+            return None;
+        }
+        // Datagram-Sockets ersetzen den Identifier durch den lokalen Port und
+        // filtern selbst; bei Raw-Sockets müssen wir das übernehmen
+        if self.raw && u16::from_be_bytes([icmp[4], icmp[5]]) != self.identifier {
+            return None;
+        }
+        Some((u16::from_be_bytes([icmp[6], icmp[7]]), ttl, icmp.len()))
+    }
+}
+
+pub fn set_option(fd: RawFd, level: libc::c_int, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    // SAFETY: Zeiger und Länge beschreiben einen gültigen c_int
+    let result = unsafe {
+        libc::setsockopt(
+            fd, level, name,
+            (&value as *const libc::c_int).cast(),
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+}
+
+/// Empfängt ein Paket samt TTL/Hop Limit aus den Kontrollnachrichten
+fn receive_with_ttl(fd: RawFd, buffer: &mut [u8]) -> io::Result<(usize, Option<u8>)> {
+    let mut control = [0u8; 64];
+    let mut iov = libc::iovec { iov_base: buffer.as_mut_ptr().cast(), iov_len: buffer.len() };
+    // SAFETY: msghdr ist ein reines C-Struct, Nullen sind gültige Startwerte
+    let mut message: libc::msghdr = unsafe { std::mem::zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = control.len() as _;
+
+    // SAFETY: Alle Puffer leben bis zum Ende der Funktion
+    let length = unsafe { libc::recvmsg(fd, &mut message, 0) };
+    if length < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut ttl = None;
+    // SAFETY: Die CMSG-Makros bewegen sich nur innerhalb von `control`
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+        while !header.is_null() {
+            let (level, kind) = ((*header).cmsg_level, (*header).cmsg_type);
+            if (level == libc::IPPROTO_IP && kind == libc::IP_TTL)
+                || (level == libc::IPPROTO_IPV6 && kind == libc::IPV6_HOPLIMIT)
+            {
+                let value = std::ptr::read_unaligned(libc::CMSG_DATA(header) as *const libc::c_int);
+                ttl = u8::try_from(value).ok();
+            }
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+    Ok((length as usize, ttl))
+}
+
+/// Internet-Prüfsumme (RFC 1071)
+pub fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data.chunks(2)
+        .map(|chunk| u32::from(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -1,7 +1,17 @@
 use clap::{Parser, Subcommand};
+use std::time::Duration;
+
+// ping, trace und mtu nutzen Linux-spezifische Socket-Optionen
+// (IP_RECVERR/MSG_ERRQUEUE, IP_MTU_DISCOVER, ICMP-Datagram-Sockets), die
+// Host-Erkennung des Scanners liest /proc/net/arp
+#[cfg(not(target_os = "linux"))]
+compile_error!("SHC-Tool unterstützt nur Linux (native ICMP-, Traceroute- und MTU-Sockets)");
 
 mod commands;
+mod csv;
+mod icmp;
 mod ollama;
+mod stats;
 
 #[derive(Parser)]
 #[command(name = "shc-tool")]
@@ -15,11 +25,130 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Show local IP addresses and gateway information
-    Ip,
+    Ip {
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: commands::ip::IpFormat,
+    },
     /// Send ping to a target
     Ping {
+        /// Target IP addresses or hostnames, pinged concurrently
+        #[arg(required_unless_present_any = ["http", "from_file", "gateway", "dns"])]
+        targets: Vec<String>,
+        /// Read additional targets from a file (one per line, # for comments)
+        #[arg(long, value_name = "FILE")]
+        from_file: Option<String>,
+        /// Also ping the gateway of the default interface
+        #[arg(long)]
+        gateway: bool,
+        /// Also ping the DNS servers of the default interface
+        #[arg(long)]
+        dns: bool,
+        /// Measure the TCP handshake time to this port instead of ICMP echo
+        #[arg(long, value_name = "PORT", conflicts_with_all = ["udp", "http"])]
+        tcp: Option<u16>,
+        /// Measure UDP request/response time on this port (DNS query on port 53)
+        #[arg(long, value_name = "PORT", conflicts_with = "http")]
+        udp: Option<u16>,
+        /// Measure DNS, connect, TLS and time to first byte of an HTTP(S) request
+        #[arg(long, value_name = "URL", conflicts_with_all = ["targets", "from_file", "gateway", "dns"])]
+        http: Option<String>,
+        /// Number of echo requests (0 runs until Ctrl+C)
+        #[arg(short, long, default_value = "4")]
+        count: u32,
+        /// Run until Ctrl+C and log outages (same as --count 0)
+        #[arg(long)]
+        watch: bool,
+        /// Consecutive losses that count as an outage
+        #[arg(long, value_name = "N", default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+        outage_after: u32,
+        /// Write every reply to a CSV timeline file
+        #[arg(long, value_name = "FILE")]
+        csv: Option<String>,
+        /// Interval between echo requests in milliseconds
+        #[arg(short, long, default_value = "1000")]
+        interval: u64,
+        /// Payload size in bytes
+        #[arg(short, long, default_value = "56")]
+        size: usize,
+        /// Time to live (IPv4) or hop limit (IPv6) of outgoing packets
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+        ttl: Option<u8>,
+        /// Time to wait for each reply in milliseconds
+        #[arg(short = 'W', long, default_value = "1000")]
+        timeout: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: commands::ping::OutputFormat,
+    },
+    /// Discover the path MTU to a target
+    Mtu {
         /// Target IP address or hostname
         target: String,
+        /// Upper bound of the search instead of the interface MTU
+        #[arg(long, value_name = "BYTES")]
+        max: Option<u32>,
+        /// Time to wait for each reply in milliseconds
+        #[arg(short = 'W', long, default_value = "1000")]
+        timeout: u64,
+        /// Attempts per packet size before it counts as too big
+        #[arg(long, default_value = "2", value_parser = clap::value_parser!(u32).range(1..))]
+        attempts: u32,
+    },
+    /// Show the path to a target hop by hop
+    Trace {
+        /// Target IP address or hostname
+        target: String,
+        /// Probe type
+        #[arg(long, value_enum, default_value = "udp")]
+        mode: commands::trace::TraceMode,
+        /// Destination port (UDP: first port, default 33434; TCP: default 80)
+        #[arg(short, long)]
+        port: Option<u16>,
+        /// Maximum number of hops
+        #[arg(short, long, default_value = "30", value_parser = clap::value_parser!(u8).range(1..))]
+        max_hops: u8,
+        /// Probes per hop
+        #[arg(short, long, default_value = "3", value_parser = clap::value_parser!(u32).range(1..=10))]
+        queries: u32,
+        /// Time to wait for each reply in milliseconds
+        #[arg(short = 'W', long, default_value = "1000")]
+        timeout: u64,
+        /// Do not resolve hop addresses to host names
+        #[arg(short = 'n', long)]
+        no_dns: bool,
+        /// Keep probing every hop and show a live table until Ctrl+C (like mtr)
+        #[arg(long, conflicts_with = "queries")]
+        continuous: bool,
+        /// Number of rounds in continuous mode (0 = until Ctrl+C)
+        #[arg(short, long, default_value = "0", requires = "continuous")]
+        count: u32,
+        /// Interval between rounds in continuous mode in milliseconds
+        #[arg(short, long, default_value = "1000", requires = "continuous")]
+        interval: u64,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: commands::ping::OutputFormat,
+    },
+    /// Query DNS servers and compare their answers
+    Dns {
+        /// Name to look up (IP addresses are looked up as PTR)
+        name: String,
+        /// Record type (default: A, or PTR for IP addresses)
+        #[arg(short = 't', long = "type", value_enum)]
+        record_type: Option<commands::dns::RecordKind>,
+        /// DNS server to query (IP, IP:port or hostname, repeatable); default: servers of the default interface
+        #[arg(short, long = "server", value_name = "SERVER")]
+        servers: Vec<String>,
+        /// Time to wait for each answer in milliseconds
+        #[arg(short = 'W', long, default_value = "2000")]
+        timeout: u64,
+        /// Compare all resolvers from interfaces, /etc/resolv.conf and systemd-resolved incl. DNSSEC and EDNS
+        #[arg(long)]
+        compare: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: commands::ping::OutputFormat,
     },
     /// Collect and analyze system log files
     Logs {
@@ -46,6 +175,69 @@ enum Commands {
     Scan {
         /// Target network or host
         target: String,
+        /// Maximum number of simultaneous connection attempts
+        #[arg(long, default_value = "200")]
+        concurrency: usize,
+        /// Timeout per connection attempt in milliseconds
+        #[arg(long, default_value = "1000")]
+        timeout: u64,
+        /// Maximum connection attempts per second (default: unlimited)
+        #[arg(long)]
+        rate: Option<u32>,
+        /// Ports to scan, e.g. "1-1024,8080", "top100", "top1000" or "all" (default: common)
+        #[arg(short, long)]
+        ports: Option<String>,
+        /// Ports to skip, same syntax as --ports
+        #[arg(long)]
+        exclude_ports: Option<String>,
+        /// Only scan the first IPv4 address of hostnames
+        #[arg(short = '4', long, conflicts_with = "ipv6")]
+        ipv4: bool,
+        /// Only scan the first IPv6 address of hostnames
+        #[arg(short = '6', long)]
+        ipv6: bool,
+        /// Look up reverse DNS (PTR) names of found hosts
+        #[arg(short = 'R', long)]
+        reverse_dns: bool,
+        /// Read service banners and detect service versions
+        #[arg(long)]
+        banners: bool,
+        /// Inspect TLS certificates and protocol versions of open ports
+        #[arg(long)]
+        tls: bool,
+        /// Query web ports via HTTP(S): status, headers, title, redirects, admin panels
+        #[arg(long)]
+        http: bool,
+        /// Query SMB ports for dialect (flags SMBv1), signing, NetBIOS name, workgroup and OS
+        #[arg(long)]
+        smb: bool,
+        /// Scan UDP instead of TCP ports using protocol-specific probes
+        #[arg(long)]
+        udp: bool,
+        /// Also list closed, filtered and unreachable ports
+        #[arg(long)]
+        show_closed: bool,
+        /// Only discover live hosts (with MAC and vendor), skip the port scan (nmap's -sn is spelled --sn here)
+        #[arg(long, visible_alias = "sn", conflicts_with = "no_discovery")]
+        discover_only: bool,
+        /// Skip host discovery and port-scan every address (nmap's -Pn is spelled --Pn here)
+        #[arg(long, visible_alias = "Pn")]
+        no_discovery: bool,
+        /// Output format of the scan report
+        #[arg(long, value_enum, default_value = "text")]
+        format: commands::scan::OutputFormat,
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Save the result as a baseline (JSON) for later comparisons
+        #[arg(long, value_name = "FILE")]
+        save_baseline: Option<String>,
+        /// Compare the result with a saved baseline; exits with code 1 on drift
+        #[arg(long, value_name = "FILE")]
+        compare: Option<String>,
+        /// Security policy (TOML) that extends or overrides the built-in rules
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
     },
     /// Test tool functionality
     Test {
@@ -60,17 +252,108 @@ async fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Ip => {
-            commands::ip::handle_ip_command();
+        Commands::Ip { format } => {
+            commands::ip::handle_ip_command(format);
+        }
+        Commands::Ping {
+            targets, from_file, gateway, dns, tcp, udp, http, count, watch, outage_after, csv, interval, size, ttl, timeout, format,
+        } => {
+            let mode = match (tcp, udp, &http) {
+                (Some(port), _, _) => commands::ping::PingMode::Tcp(port),
+                (_, Some(port), _) => commands::ping::PingMode::Udp(port),
+                (_, _, Some(_)) => commands::ping::PingMode::Http,
+                _ => commands::ping::PingMode::Icmp,
+            };
+            let targets = commands::ping::TargetList {
+                targets: http.map(|url| vec![url]).unwrap_or(targets),
+                from_file,
+                gateway,
+                dns,
+            };
+            let options = commands::ping::PingOptions {
+                mode,
+                count: if watch { 0 } else { count },
+                interval: Duration::from_millis(interval),
+                size,
+                ttl,
+                timeout: Duration::from_millis(timeout),
+                format,
+                outage_threshold: outage_after,
+                timeline: csv,
+            };
+            commands::ping::handle_ping_command(targets, options).await;
+        }
+        Commands::Mtu { target, max, timeout, attempts } => {
+            let options = commands::mtu::MtuOptions {
+                max,
+                timeout: Duration::from_millis(timeout),
+                attempts,
+            };
+            commands::mtu::handle_mtu_command(&target, options).await;
+        }
+        Commands::Trace { target, mode, port, max_hops, queries, timeout, no_dns, continuous, count, interval, format } => {
+            let options = commands::trace::TraceOptions {
+                mode,
+                port,
+                max_hops,
+                queries,
+                timeout: Duration::from_millis(timeout),
+                resolve: !no_dns,
+                format,
+                continuous,
+                count,
+                interval: Duration::from_millis(interval),
+            };
+            commands::trace::handle_trace_command(&target, options).await;
         }
-        Commands::Ping { target } => {
-            commands::ping::handle_ping_command(&target);
+        Commands::Dns { name, record_type, servers, timeout, compare, format } => {
+            let options = commands::dns::DnsOptions {
+                record_type,
+                servers,
+                timeout: Duration::from_millis(timeout),
+                format,
+                compare,
+            };
+            commands::dns::handle_dns_command(&name, options).await;
         }
         Commands::Logs { zip, analyze, query, lines, model, file } => {
             commands::logs::handle_logs_command(zip, analyze, query, lines, model, file).await;
         }
-        Commands::Scan { target } => {
-            commands::scan::handle_scan_command(&target);
+        Commands::Scan {
+            target, concurrency, timeout, rate, ports, exclude_ports, ipv4, ipv6, reverse_dns,
+            banners, tls, http, smb, udp, show_closed, discover_only, no_discovery,
+            format, output, save_baseline, compare, policy,
+        } => {
+            let family = if ipv4 {
+                commands::scan::AddressFamily::V4
+            } else if ipv6 {
+                commands::scan::AddressFamily::V6
+            } else {
+                commands::scan::AddressFamily::Any
+            };
+            let options = commands::scan::ScanOptions {
+                concurrency,
+                timeout: Duration::from_millis(timeout),
+                rate,
+                ports,
+                exclude_ports,
+                family,
+                reverse_dns,
+                banners,
+                tls,
+                http,
+                smb,
+                udp,
+                show_closed,
+                discover_only,
+                no_discovery,
+                format,
+                output,
+                save_baseline,
+                compare,
+                policy,
+            };
+            commands::scan::handle_scan_command(&target, options).await;
         }
         Commands::Test { all } => {
             commands::test::handle_test_command(all);
@@ -91,6 +91,7 @@ Frage des Benutzers: {}"#,
         }
     }
 
+    #[allow(dead_code)]
     pub async fn list_available_models(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let url = format!("{}/api/tags", self.base_url);
 
@@ -0,0 +1,224 @@
+// src/stats.rs
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Höchstens so viele Laufzeiten werden für die Perzentile aufbewahrt
+const PERCENTILE_WINDOW: usize = 10_000;
+
+/// Sammelt Laufzeiten und Verluste einer Messreihe (Ping, Traceroute).
+///
+/// Minimum, Maximum, Mittelwert, Streuung und Jitter werden laufend über
+/// alle Antworten fortgeschrieben; die Perzentile beziehen sich auf die
+/// letzten `PERCENTILE_WINDOW` Antworten. So bleibt der Speicherbedarf auch
+/// im Dauerbetrieb konstant.
+#[derive(Default, Clone)]
+pub struct Samples {
+    sent: u32,
+    received: u32,
+    min: f64,
+    max: f64,
+    /// Laufender Mittelwert und Summe der quadrierten Abweichungen (Welford)
+    mean: f64,
+    squared_deviations: f64,
+    /// Summe der Abstände aufeinanderfolgender Laufzeiten
+    jitter_sum: f64,
+    /// Jüngste Laufzeiten in Millisekunden, in Empfangsreihenfolge
+    recent: VecDeque<f64>,
+}
+
+/// Auswertung einer Messreihe
+#[derive(Clone, Serialize)]
+pub struct Summary {
+    pub sent: u32,
+    pub received: u32,
+    pub loss_percent: f64,
+    /// Fehlt, wenn keine einzige Antwort kam
+    pub rtt: Option<RttSummary>,
+}
+
+/// Laufzeit-Kennzahlen in Millisekunden
+#[derive(Clone, Serialize)]
+pub struct RttSummary {
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub stddev_ms: f64,
+    /// Mittlere Abweichung zwischen aufeinanderfolgenden Laufzeiten
+    pub jitter_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl Samples {
+    pub fn add_reply(&mut self, rtt: Duration) {
+        let rtt = rtt.as_micros() as f64 / 1000.0;
+        self.sent += 1;
+        self.received += 1;
+
+        if let Some(last) = self.recent.back() {
+            self.jitter_sum += (rtt - last).abs();
+        }
+        if self.received == 1 {
+            (self.min, self.max) = (rtt, rtt);
+        } else {
+            self.min = self.min.min(rtt);
+            self.max = self.max.max(rtt);
+        }
+        let delta = rtt - self.mean;
+        self.mean += delta / f64::from(self.received);
+        self.squared_deviations += delta * (rtt - self.mean);
+
+        if self.recent.len() == PERCENTILE_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(rtt);
+    }
+
+    pub fn add_loss(&mut self) {
+        self.sent += 1;
+    }
+
+    /// Laufzeit der zuletzt empfangenen Antwort in Millisekunden
+    pub fn last_rtt(&self) -> Option<f64> {
+        self.recent.back().copied()
+    }
+
+    pub fn summary(&self) -> Summary {
+        let loss_percent = if self.sent == 0 {
+            0.0
+        } else {
+            f64::from(self.sent - self.received) * 100.0 / f64::from(self.sent)
+        };
+        Summary { sent: self.sent, received: self.received, loss_percent: round(loss_percent), rtt: self.rtt_summary() }
+    }
+
+    fn rtt_summary(&self) -> Option<RttSummary> {
+        if self.received == 0 {
+            return None;
+        }
+
+        let count = f64::from(self.received);
+        let jitter = if self.received > 1 { self.jitter_sum / (count - 1.0) } else { 0.0 };
+
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+
+        Some(RttSummary {
+            min_ms: self.min,
+            avg_ms: round(self.mean),
+            max_ms: self.max,
+            stddev_ms: round((self.squared_deviations / count).sqrt()),
+            jitter_ms: round(jitter),
+            p50_ms: percentile(&sorted, 50.0),
+            p95_ms: percentile(&sorted, 95.0),
+            p99_ms: percentile(&sorted, 99.0),
+        })
+    }
+}
+
+/// Auf Mikrosekunden runden, damit JSON-Ausgaben lesbar bleiben
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Perzentil nach dem Nearest-Rank-Verfahren (Werte aufsteigend sortiert)
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rtt_summary(rtts: &[f64]) -> Option<RttSummary> {
+        let mut samples = Samples::default();
+        for rtt in rtts {
+            samples.add_reply(Duration::from_micros((rtt * 1000.0).round() as u64));
+        }
+        samples.summary().rtt
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        assert_eq!(percentile(&[7.0], 0.0), 7.0);
+        assert_eq!(percentile(&[7.0], 50.0), 7.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+
+        let sorted: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 5.0);
+        assert_eq!(percentile(&sorted, 51.0), 6.0);
+        assert_eq!(percentile(&sorted, 95.0), 10.0);
+
+        let sorted: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 95.0), 19.0);
+        assert_eq!(percentile(&sorted, 99.0), 20.0);
+        assert_eq!(percentile(&sorted, 100.0), 20.0);
+    }
+
+    #[test]
+    fn single_sample_has_no_spread() {
+        let rtt = rtt_summary(&[12.5]).unwrap();
+        assert_eq!((rtt.min_ms, rtt.avg_ms, rtt.max_ms), (12.5, 12.5, 12.5));
+        assert_eq!((rtt.stddev_ms, rtt.jitter_ms), (0.0, 0.0));
+        assert_eq!((rtt.p50_ms, rtt.p95_ms, rtt.p99_ms), (12.5, 12.5, 12.5));
+        assert!(rtt_summary(&[]).is_none());
+    }
+
+    #[test]
+    fn summary_uses_arrival_order_for_jitter() {
+        let rtt = rtt_summary(&[10.0, 30.0, 20.0, 40.0]).unwrap();
+        assert_eq!((rtt.min_ms, rtt.max_ms, rtt.avg_ms), (10.0, 40.0, 25.0));
+        // |30-10| + |20-30| + |40-20| = 50 auf drei Abstände
+        assert_eq!(rtt.jitter_ms, 16.667);
+        assert_eq!(rtt.stddev_ms, 11.18);
+        assert_eq!((rtt.p50_ms, rtt.p95_ms), (20.0, 40.0));
+    }
+
+    #[test]
+    fn loss_is_counted_per_probe() {
+        assert_eq!(Samples::default().summary().loss_percent, 0.0);
+
+        let mut samples = Samples::default();
+        samples.add_reply(Duration::from_micros(1_500));
+        samples.add_loss();
+        samples.add_reply(Duration::from_micros(2_250));
+        assert_eq!(samples.last_rtt(), Some(2.25));
+
+        let summary = samples.summary();
+        assert_eq!((summary.sent, summary.received, summary.loss_percent), (3, 2, 33.333));
+        assert_eq!(summary.rtt.unwrap().min_ms, 1.5);
+
+        let mut lost = Samples::default();
+        lost.add_loss();
+        let summary = lost.summary();
+        assert_eq!(summary.loss_percent, 100.0);
+        assert!(summary.rtt.is_none());
+    }
+
+    #[test]
+    fn long_runs_keep_exact_aggregates_and_bounded_window() {
+        let mut samples = Samples::default();
+        // 1, 2, ..., 25000 ms, dazwischen jede zehnte Probe verloren
+        for index in 1..=25_000u64 {
+            samples.add_reply(Duration::from_millis(index));
+            if index % 10 == 0 {
+                samples.add_loss();
+            }
+        }
+        assert_eq!(samples.recent.len(), PERCENTILE_WINDOW);
+
+        let summary = samples.summary();
+        assert_eq!((summary.sent, summary.received), (27_500, 25_000));
+        let rtt = summary.rtt.unwrap();
+        assert_eq!((rtt.min_ms, rtt.max_ms, rtt.avg_ms), (1.0, 25_000.0, 12_500.5));
+        assert_eq!(rtt.jitter_ms, 1.0);
+        // Standardabweichung von 1..=n: sqrt((n² - 1) / 12)
+        assert_eq!(rtt.stddev_ms, 7216.878);
+        // Perzentile aus den letzten 10000 Antworten (15001..=25000)
+        assert_eq!((rtt.p50_ms, rtt.p99_ms), (20_000.0, 24_900.0));
+        assert_eq!(samples.last_rtt(), Some(25_000.0));
+    }
+}